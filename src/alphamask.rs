@@ -1,9 +1,37 @@
 //! Alphamask Adapator
+//!
+//! An [`AlphaMask`] holds per-pixel mask values taken from one channel of
+//!   any pixel format.  Wrapping a pixel format in a [`PixfmtAlphaMask`]
+//!   multiplies the cover of every drawing operation by the mask, so any
+//!   renderer draws through it
+//!
+//!     use agg::{Pixfmt,Rgb8,Rgba8,Gray8,Source,RenderingBase};
+//!     use agg::{AlphaMask,MaskChannel,PixfmtAlphaMask};
+//!
+//!     // Mask is the left half of the image
+//!     let mut mask = AlphaMask::new(Pixfmt::<Gray8>::new(20,20), MaskChannel::Luminance);
+//!     let mut half = agg::Path::new();
+//!     half.move_to(0.0, 0.0);
+//!     half.line_to(10.0, 0.0);
+//!     half.line_to(10.0, 20.0);
+//!     half.line_to(0.0, 20.0);
+//!     half.close_polygon();
+//!     mask.add_path(&half, Gray8::new(255));
+//!
+//!     let pix = PixfmtAlphaMask::new(Pixfmt::<Rgb8>::new(20,20), &mask);
+//!     let mut ren_base = RenderingBase::new(pix);
+//!     ren_base.blend_hline(0, 5, 19, Rgba8::white(), 255);
+//!     assert_eq!(ren_base.pixf.get((5,5)), Rgba8::white());
+//!     assert_eq!(ren_base.pixf.get((15,5)), Rgba8::black());
+//!
+//! [`AlphaMask`]: struct.AlphaMask.html
+//! [`PixfmtAlphaMask`]: struct.PixfmtAlphaMask.html
 
 //use crate::math::blend_pix;
 use crate::color::Rgb8;
 use crate::color::Gray8;
 use crate::pixfmt::Pixfmt;
+use crate::pixfmt::PixelSize;
 
 use crate::Color;
 use crate::Pixel;
@@ -11,6 +39,10 @@ use crate::Source;
 use crate::math::lerp_u8;
 use crate::math::multiply_u8;
 use crate::color::Rgba8;
+use crate::base::RenderingBase;
+use crate::raster::RasterizerScanline;
+use crate::render::render_scanlines_aa_solid;
+use crate::VertexSource;
 
 /// Alpha Mask Adaptor
 pub struct AlphaMaskAdaptor<T> where Pixfmt<T>: Pixel + Source {
@@ -74,3 +106,165 @@ fn blend_pix<C1: Color, C2: Color>(p: &C1, c: &C2, cover: u64) -> Rgba8 {
 
     Rgba8::new(red, green, blue, alpha)
 }
+
+/// Channel of a Pixel Format used as the mask value
+///
+/// Channels are read from the color of the pixel, independent of the
+///   component order and size of the format.  Gray formats report their
+///   value as red, green, blue and luminance
+#[derive(Debug,Copy,Clone,PartialEq,Eq)]
+pub enum MaskChannel {
+    /// Red component
+    Red,
+    /// Green component
+    Green,
+    /// Blue component
+    Blue,
+    /// Alpha component
+    Alpha,
+    /// Luminance of the red, green and blue components
+    Luminance,
+}
+
+/// Alpha Mask
+///
+/// Mask values are read from the `channel` of a Pixel Format; locations
+///   outside of the mask have a value of 0
+#[derive(Debug)]
+pub struct AlphaMask<T> {
+    /// Pixel Format holding the mask
+    pub pixf: Pixfmt<T>,
+    channel: MaskChannel,
+}
+
+impl<T> AlphaMask<T> where Pixfmt<T>: Pixel + Source {
+    /// Create a new Alpha Mask from a Pixel Format and a `channel`
+    pub fn new(pixf: Pixfmt<T>, channel: MaskChannel) -> Self {
+        Self { pixf, channel }
+    }
+    /// Channel used as the mask value
+    pub fn channel(&self) -> MaskChannel {
+        self.channel
+    }
+    /// Width of the mask in pixels
+    pub fn width(&self) -> usize {
+        self.pixf.width()
+    }
+    /// Height of the mask in pixels
+    pub fn height(&self) -> usize {
+        self.pixf.height()
+    }
+    /// Mask value at (`x`,`y`), 0 if outside of the mask
+    pub fn pixel(&self, x: i64, y: i64) -> u8 {
+        if x < 0 || y < 0 || x >= self.width() as i64 || y >= self.height() as i64 {
+            return 0;
+        }
+        let c = self.pixf.get((x as usize, y as usize));
+        match self.channel {
+            MaskChannel::Red       => c.r,
+            MaskChannel::Green     => c.g,
+            MaskChannel::Blue      => c.b,
+            MaskChannel::Alpha     => c.a,
+            MaskChannel::Luminance => Gray8::from_trait(Rgb8::new(c.r, c.g, c.b)).value,
+        }
+    }
+    /// Combine a `cover` with the mask value at (`x`,`y`)
+    pub fn combine_pixel(&self, x: i64, y: i64, cover: u64) -> u64 {
+        u64::from(multiply_u8(cover.min(255) as u8, self.pixel(x, y)))
+    }
+}
+
+impl<T> AlphaMask<T> where Pixfmt<T>: Pixel + PixelSize + Source {
+    /// Rasterize `path` into the mask, Anti-Aliased with `color`
+    pub fn add_path<VS: VertexSource, C: Color>(&mut self, path: &VS, color: C) {
+        let (w, h) = (self.width(), self.height());
+        let pixf = std::mem::replace(&mut self.pixf, Pixfmt::new(1, 1));
+        let mut base = RenderingBase::new(pixf);
+        let mut ras = RasterizerScanline::new();
+        ras.clip_box(0.0, 0.0, w as f64, h as f64);
+        ras.add_path(path);
+        render_scanlines_aa_solid(&mut ras, &mut base, color);
+        self.pixf = base.pixf;
+    }
+}
+
+/// Pixel Format Adaptor applying an [`AlphaMask`]
+///
+/// The cover of every blending operation is multiplied by the mask value.
+///   Copies replace the pixel with the color where the mask is full,
+///   interpolate towards the color where it is partial, and leave the
+///   pixel untouched where it is empty
+///
+/// [`AlphaMask`]: struct.AlphaMask.html
+#[derive(Debug)]
+pub struct PixfmtAlphaMask<'a,P,T> {
+    /// Underlying Pixel Format
+    pub pixf: P,
+    mask: &'a AlphaMask<T>,
+}
+
+impl<'a,P,T> PixfmtAlphaMask<'a,P,T> where P: Pixel + Source, Pixfmt<T>: Pixel + Source {
+    /// Create a new adaptor drawing onto `pixf` through `mask`
+    pub fn new(pixf: P, mask: &'a AlphaMask<T>) -> Self {
+        Self { pixf, mask }
+    }
+    /// Alpha Mask applied
+    pub fn mask(&self) -> &AlphaMask<T> {
+        self.mask
+    }
+}
+
+impl<P,T> Source for PixfmtAlphaMask<'_,P,T> where P: Source {
+    fn get(&self, id: (usize, usize)) -> Rgba8 {
+        self.pixf.get(id)
+    }
+}
+
+impl<P,T> Pixel for PixfmtAlphaMask<'_,P,T> where P: Pixel + Source, Pixfmt<T>: Pixel + Source {
+    fn cover_mask() -> u64 { P::cover_mask() }
+    fn bpp(&self) -> usize { self.pixf.bpp() }
+    fn as_bytes(&self) -> &[u8] {
+        self.pixf.as_bytes()
+    }
+    fn packed(&self) -> std::borrow::Cow<'_, [u8]> {
+        self.pixf.packed()
+    }
+    fn to_file<P2: AsRef<std::path::Path>>(&self, filename: P2) -> Result<(),image::ImageError> {
+        self.pixf.to_file(filename)
+    }
+    fn width(&self) -> usize {
+        self.pixf.width()
+    }
+    fn height(&self) -> usize {
+        self.pixf.height()
+    }
+    fn set<C: Color>(&mut self, id: (usize, usize), c: C) {
+        let m = self.mask.pixel(id.0 as i64, id.1 as i64);
+        if m == 255 {
+            self.pixf.set(id, c);
+        } else if m > 0 {
+            let p = self.pixf.get(id);
+            let c = Rgba8::new(lerp_u8(p.r, c.red8(),   m),
+                               lerp_u8(p.g, c.green8(), m),
+                               lerp_u8(p.b, c.blue8(),  m),
+                               lerp_u8(p.a, c.alpha8(), m));
+            self.pixf.set(id, c);
+        }
+    }
+    fn setn<C: Color>(&mut self, id: (usize, usize), n: usize, c: C) {
+        for i in 0 .. n {
+            self.set((id.0+i, id.1), c);
+        }
+    }
+    fn fill<C: Color>(&mut self, color: C) {
+        for y in 0 .. self.height() {
+            self.setn((0,y), self.width(), color);
+        }
+    }
+    fn blend_pix<C: Color>(&mut self, id: (usize, usize), c: C, cover: u64) {
+        let cover = self.mask.combine_pixel(id.0 as i64, id.1 as i64, cover);
+        if cover > 0 {
+            self.pixf.copy_or_blend_pix_with_cover(id, c, cover);
+        }
+    }
+}
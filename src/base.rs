@@ -4,26 +4,155 @@ use crate::color::*;
 use crate::Pixel;
 use crate::Color;
 use crate::Source;
+use crate::AggError;
+use crate::VertexSource;
+use crate::raster::RasterizerScanline;
+use crate::raster::FillingRule;
+use crate::scan::ScanlineU8;
+use crate::math::multiply_u8;
+use crate::comp_op::{CompOp,CompOpBlend};
 use std::cmp::min;
 use std::cmp::max;
 
 
 /// Rendering Base
 ///
+/// Clips all drawing to the image limits and, if set, to the active
+///   clip path
 #[derive(Debug)]
 pub struct RenderingBase<T> {
     /// Pixel Format
     pub pixf: T,
+    /// Active clip path coverage, one value per pixel
+    clip: Option<Vec<u8>>,
+    /// Saved clip paths
+    clip_stack: Vec<Option<Vec<u8>>>,
 }
 
 impl<T> RenderingBase<T> where T: Pixel {
     /// Create new Rendering Base from Pixel Format
     pub fn new(pixf: T) -> RenderingBase<T> {
-        RenderingBase { pixf }
+        RenderingBase { pixf, clip: None, clip_stack: vec![] }
     }
+    /// Intersect the active clip path with `path`
+    ///
+    /// The path is rasterized once using the `rule` filling rule and the
+    ///   resulting coverage is multiplied with all later drawing.  Nested
+    ///   calls narrow the clip region further; use [`save_clip`] and
+    ///   [`restore_clip`] to undo them.
+    ///
+    ///     use agg::{Pixfmt,Rgb8,Rgba8,RenderingBase,Source,FillingRule};
+    ///
+    ///     let pix = Pixfmt::<Rgb8>::new(20,20);
+    ///     let mut ren_base = RenderingBase::new(pix);
+    ///     ren_base.clear(Rgba8::white());
+    ///
+    ///     let mut clip = agg::Path::new();
+    ///     clip.move_to(0.0, 0.0);
+    ///     clip.line_to(10.0, 0.0);
+    ///     clip.line_to(10.0, 20.0);
+    ///     clip.line_to(0.0, 20.0);
+    ///     clip.close_polygon();
+    ///     ren_base.clip_path(&clip, FillingRule::NonZero);
+    ///
+    ///     ren_base.blend_hline(0, 5, 19, Rgba8::black(), 255);
+    ///     assert_eq!(ren_base.pixf.get((5,5)), Rgba8::black());
+    ///     assert_eq!(ren_base.pixf.get((15,5)), Rgba8::white());
+    ///
+    /// [`save_clip`]: #method.save_clip
+    /// [`restore_clip`]: #method.restore_clip
+    pub fn clip_path<VS: VertexSource>(&mut self, path: &VS, rule: FillingRule) {
+        let (w, h) = (self.pixf.width(), self.pixf.height());
+        let mut ras = RasterizerScanline::new();
+        ras.clip_box(0.0, 0.0, w as f64, h as f64);
+        ras.set_filling_rule(rule);
+        ras.add_path(path);
+
+        let mut mask = vec![0u8; w * h];
+        let mut sl = ScanlineU8::new();
+        if ras.rewind_scanlines() {
+            sl.reset(ras.min_x(), ras.max_x());
+            while ras.sweep_scanline(&mut sl) {
+                if sl.y < 0 || sl.y >= h as i64 {
+                    continue;
+                }
+                let row = sl.y as usize * w;
+                for span in &sl.spans {
+                    for (i, &cover) in span.covers.iter().enumerate() {
+                        let x = span.x + i as i64;
+                        if x >= 0 && x < w as i64 {
+                            mask[row + x as usize] = min(cover, 255) as u8;
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(ref cur) = self.clip {
+            for (m, &c) in mask.iter_mut().zip(cur.iter()) {
+                *m = multiply_u8(*m, c);
+            }
+        }
+        self.clip = Some(mask);
+    }
+    /// Remove the active clip path
+    ///
+    /// Saved clip paths are not affected
+    pub fn reset_clip(&mut self) {
+        self.clip = None;
+    }
+    /// Save the active clip path
+    ///
+    /// Restore with [`restore_clip`](#method.restore_clip)
+    pub fn save_clip(&mut self) {
+        self.clip_stack.push(self.clip.clone());
+    }
+    /// Restore the most recently saved clip path
+    ///
+    /// Without a saved clip path, the clip path is removed
+    pub fn restore_clip(&mut self) {
+        self.clip = self.clip_stack.pop().unwrap_or(None);
+    }
+    /// Return if a clip path is active
+    pub fn has_clip(&self) -> bool {
+        self.clip.is_some()
+    }
+    /// Clip path coverage at (`x`,`y`)
+    ///
+    /// Returns 255 (fully visible) without an active clip path
+    pub fn clip_cover(&self, x: i64, y: i64) -> u64 {
+        match self.clip {
+            None => 255,
+            Some(ref mask) => {
+                let (w, h) = (self.pixf.width() as i64, self.pixf.height() as i64);
+                if x < 0 || y < 0 || x >= w || y >= h {
+                    0
+                } else {
+                    u64::from(mask[(y * w + x) as usize])
+                }
+            }
+        }
+    }
+    /// Combine `covers` with the clip path along a line of pixels
+    ///
+    /// Pixels are located at (`x`+`dx`*i,`y`+`dy`*i)
+    fn clip_covers<F>(&self, x: i64, y: i64, dx: i64, dy: i64, len: i64, cover: F) -> Vec<u64>
+        where F: Fn(usize) -> u64
+    {
+        (0 .. len).map(|i| {
+            let c = self.clip_cover(x + dx * i, y + dy * i);
+            u64::from(multiply_u8(min(cover(i as usize), 255) as u8, c as u8))
+        }).collect()
+    }
+    /// Underlying storage of the Pixel Format, see [`Pixel::as_bytes`]
+    ///
+    /// [`Pixel::as_bytes`]: ../trait.Pixel.html#tymethod.as_bytes
     pub fn as_bytes(&self) -> &[u8] {
         self.pixf.as_bytes()
     }
+    /// Pixels of the image, top-down without padding
+    pub fn packed(&self) -> std::borrow::Cow<'_, [u8]> {
+        self.pixf.packed()
+    }
     pub fn to_file<P: AsRef<std::path::Path>>(&self, filename: P) -> Result<(),image::ImageError> {
         self.pixf.to_file(filename)
     }
@@ -46,6 +175,11 @@ impl<T> RenderingBase<T> where T: Pixel {
         }
         let x1 = max(x1, xmin);
         let x2 = min(x2, xmax);
+        if self.clip.is_some() {
+            let covers = self.clip_covers(x1, y, 1, 0, x2 - x1 + 1, |_| cover);
+            self.pixf.blend_solid_hspan(x1, y, x2 - x1 + 1, c, &covers);
+            return;
+        }
         self.pixf.blend_hline(x1, y, x2 - x1 + 1, c, cover);
     }
     /// Blend a color from (x,y) with variable covers
@@ -71,6 +205,11 @@ impl<T> RenderingBase<T> where T: Pixel {
         }
         let covers_win = &covers[off as usize .. (off+len) as usize];
         assert!(len as usize <= covers[off as usize ..].len());
+        if self.clip.is_some() {
+            let covers = self.clip_covers(x, y, 1, 0, len, |i| covers_win[i]);
+            self.pixf.blend_solid_hspan(x, y, len, c, &covers);
+            return;
+        }
         self.pixf.blend_solid_hspan(x, y, len, c, covers_win);
     }
     /// Blend a color from (x,y) with variable covers
@@ -96,6 +235,11 @@ impl<T> RenderingBase<T> where T: Pixel {
         }
         let covers_win = &covers[off as usize .. (off+len) as usize];
         assert!(len as usize <= covers[off as usize ..].len());
+        if self.clip.is_some() {
+            let covers = self.clip_covers(x, y, 0, 1, len, |i| covers_win[i]);
+            self.pixf.blend_solid_vspan(x, y, len, c, &covers);
+            return;
+        }
         self.pixf.blend_solid_vspan(x, y, len, c, covers_win);
     }
 
@@ -125,6 +269,13 @@ impl<T> RenderingBase<T> where T: Pixel {
             &covers[off as usize .. (off+len) as usize]
         };
         let colors_win = &colors[off as usize .. (off+len) as usize];
+        if self.clip.is_some() {
+            let covers = self.clip_covers(x, y, 0, 1, len, |i| {
+                if covers_win.is_empty() { cover } else { covers_win[i] }
+            });
+            self.pixf.blend_color_vspan(x, y, len, colors_win, &covers, cover);
+            return;
+        }
         self.pixf.blend_color_vspan(x, y, len, colors_win, covers_win, cover);
     }
     pub fn blend_color_hspan<C: Color>(&mut self, x: i64, y: i64, len: i64, colors: &[C], covers: &[u64], cover: u64) {
@@ -153,24 +304,55 @@ impl<T> RenderingBase<T> where T: Pixel {
             &covers[off as usize .. (off+len) as usize]
         };
         let colors_win = &colors[off as usize .. (off+len) as usize];
+        if self.clip.is_some() {
+            let covers = self.clip_covers(x, y, 1, 0, len, |i| {
+                if covers_win.is_empty() { cover } else { covers_win[i] }
+            });
+            self.pixf.blend_color_hspan(x, y, len, colors_win, &covers, cover);
+            return;
+        }
         self.pixf.blend_color_hspan(x, y, len, colors_win, covers_win, cover);
     }
     
     
     
+    /// Blend an image of the same size onto this one with an `opacity`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sizes differ, see [`try_blend_from`](#method.try_blend_from)
     pub fn blend_from<S: Pixel + Source>(&mut self, other: &S, opacity: f64) {
+        if let Err(err) = self.try_blend_from(other, opacity) {
+            panic!("{}", err);
+        }
+    }
+    /// Blend an image of the same size onto this one with an `opacity`
+    ///
+    /// Returns an error if the sizes differ
+    pub fn try_blend_from<S: Pixel + Source>(&mut self, other: &S, opacity: f64) -> Result<(), AggError> {
+        self.blend_from_with(other, opacity, |pixf, id, c, cover| pixf.blend_pix(id, c, cover))
+    }
+    /// Blend each pixel of `other` with `f`, considering `opacity` and
+    ///   the clip path
+    fn blend_from_with<S, F>(&mut self, other: &S, opacity: f64, mut f: F) -> Result<(), AggError>
+        where S: Pixel + Source, F: FnMut(&mut T, (usize,usize), Rgba8, u64)
+    {
         if self.pixf.width()!=other.width() || self.pixf.height() != other.height() {
-            panic!("wrong size");
+            return Err(AggError::SizeMismatch {
+                expected: (self.pixf.width(), self.pixf.height()),
+                found: (other.width(), other.height()),
+            });
         }
-        
-        
-        
         for x in 0..self.pixf.width() {
             for y in 0..self.pixf.height() {
                 let c = other.get((x,y));
-                self.pixf.blend_pix((x,y),c,(opacity*255.0) as u64);
+                let cover = (opacity*255.0) as u64;
+                let clip = self.clip_cover(x as i64, y as i64);
+                let cover = u64::from(multiply_u8(cover as u8, clip as u8));
+                f(&mut self.pixf, (x,y), c, cover);
             }
         }
+        Ok(())
     }
         
         
@@ -178,3 +360,33 @@ impl<T> RenderingBase<T> where T: Pixel {
     
 }
 
+impl<T> RenderingBase<T> where T: Pixel + CompOpBlend {
+    /// Blend an image of the same size onto this one using the compositing
+    ///   operator `op` with an `opacity`
+    ///
+    ///     use agg::{Pixfmt,Rgba8,RenderingBase,Pixel,Source,CompOp};
+    ///
+    ///     let mut layer = Pixfmt::<Rgba8>::new(2,2);
+    ///     layer.fill(Rgba8::new(128,128,128,255));
+    ///     let mut ren_base = RenderingBase::new(Pixfmt::<Rgba8>::new(2,2));
+    ///     ren_base.clear(Rgba8::new(255,0,0,255));
+    ///     ren_base.blend_from_op(&layer, CompOp::Luminosity, 1.0);
+    ///     let c = ren_base.pixf.get((0,0));
+    ///     assert!(c.r > c.g && c.g == c.b);
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sizes differ, see [`try_blend_from_op`](#method.try_blend_from_op)
+    pub fn blend_from_op<S: Pixel + Source>(&mut self, other: &S, op: CompOp, opacity: f64) {
+        if let Err(err) = self.try_blend_from_op(other, op, opacity) {
+            panic!("{}", err);
+        }
+    }
+    /// Blend an image of the same size onto this one using the compositing
+    ///   operator `op` with an `opacity`
+    ///
+    /// Returns an error if the sizes differ
+    pub fn try_blend_from_op<S: Pixel + Source>(&mut self, other: &S, op: CompOp, opacity: f64) -> Result<(), AggError> {
+        self.blend_from_with(other, opacity, |pixf, id, c, cover| pixf.blend_pix_op(id, c, op, cover))
+    }
+}
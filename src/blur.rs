@@ -0,0 +1,267 @@
+//! Blur
+//!
+//! Blurs an image, or a region of it, in place
+//!
+//! - [`stack_blur`] approximates a Gaussian with a triangular kernel
+//! - [`recursive_blur`] is a recursive Gaussian (Young / van Vliet),
+//!   with a constant cost for any radius
+//!
+//! Both operate on each 8-bit component independently, including alpha.
+//!   Formats with straight alpha are premultiplied while blurring, so
+//!   transparent pixels do not darken colors near them.  A radius of 0
+//!   leaves that direction unchanged
+//!
+//!     use agg::{Pixfmt,Rgba8,Pixel,Source,stack_blur};
+//!
+//!     let mut pix = Pixfmt::<Rgba8>::new(20,20);
+//!     pix.set((10,10), Rgba8::new(255,255,255,255));
+//!     stack_blur(&mut pix, 2, 2, None);
+//!     assert_eq!(pix.get((10,10)).a, 28); // 255 * 3/9 * 3/9
+//!
+//! [`stack_blur`]: fn.stack_blur.html
+//! [`recursive_blur`]: fn.recursive_blur.html
+
+use crate::color::*;
+use crate::pixfmt::Pixfmt;
+use crate::pixfmt::PixelData;
+use crate::clip::Rectangle;
+use crate::Pixel;
+
+/// Pixel formats with 8-bit components, which may be blurred
+pub trait Component8 {
+    /// Offset of a straight alpha component, premultiplied while blurring
+    ///
+    /// Gray values are blurred independent of alpha, as they are commonly
+    ///   used as coverage masks
+    const ALPHA: Option<usize>;
+}
+impl Component8 for Rgb8     { const ALPHA: Option<usize> = None; }
+impl Component8 for Rgba8    { const ALPHA: Option<usize> = Some(3); }
+impl Component8 for Rgba8pre { const ALPHA: Option<usize> = None; }
+impl Component8 for Gray8    { const ALPHA: Option<usize> = None; }
+impl Component8 for Bgr8     { const ALPHA: Option<usize> = None; }
+impl Component8 for Bgra8    { const ALPHA: Option<usize> = Some(3); }
+impl Component8 for Argb8    { const ALPHA: Option<usize> = Some(0); }
+impl Component8 for Abgr8    { const ALPHA: Option<usize> = Some(0); }
+impl Component8 for Bgra8pre { const ALPHA: Option<usize> = None; }
+impl Component8 for Argb8pre { const ALPHA: Option<usize> = None; }
+impl Component8 for Abgr8pre { const ALPHA: Option<usize> = None; }
+
+/// Region, inclusive, limited to the image
+fn region<T, B>(pix: &Pixfmt<T, B>, rect: Option<Rectangle<i64>>) -> Option<(usize,usize,usize,usize)>
+    where B: PixelData, Pixfmt<T, B>: Pixel
+{
+    let (w, h) = (pix.width() as i64, pix.height() as i64);
+    let (x1, y1, x2, y2) = match rect {
+        Some(r) => (r.x1().max(0), r.y1().max(0), r.x2().min(w-1), r.y2().min(h-1)),
+        None => (0, 0, w-1, h-1),
+    };
+    if x1 > x2 || y1 > y2 {
+        return None;
+    }
+    Some((x1 as usize, y1 as usize, x2 as usize, y2 as usize))
+}
+
+/// Apply `f` to each row, and then each column, within the region
+///
+/// `f` receives the components of a line, the number of components per
+///   pixel and whether the line is a row.  Components are widened to 16
+///   bits; with straight alpha, colors are premultiplied without rounding
+///   as `color * alpha`, alpha is scaled to match, and colors are divided
+///   by the blurred alpha afterwards
+fn blur_lines<T, B, F>(pix: &mut Pixfmt<T, B>, rect: Option<Rectangle<i64>>, mut f: F)
+    where T: Component8, B: PixelData, Pixfmt<T, B>: Pixel, F: FnMut(&mut [u16], usize, bool)
+{
+    let (x1, y1, x2, y2) = match region(pix, rect) {
+        Some(r) => r,
+        None => return,
+    };
+    let n = pix.bpp();
+    let (w, h) = (x2 - x1 + 1, y2 - y1 + 1);
+    let mut buf = Vec::with_capacity(w * h * n);
+    for y in y1 ..= y2 {
+        for x in x1 ..= x2 {
+            let p = pix.components((x,y));
+            buf.extend(p.iter().enumerate().map(|(i, &v)| match T::ALPHA {
+                Some(a) if a == i => u16::from(v) * 255,
+                Some(a) => u16::from(v) * u16::from(p[a]),
+                None => u16::from(v),
+            }));
+        }
+    }
+    for row in buf.chunks_mut(w * n) {
+        f(row, n, true);
+    }
+    let mut line = vec![0; h * n];
+    for x in 0 .. w {
+        for y in 0 .. h {
+            line[y*n..(y+1)*n].copy_from_slice(&buf[(y*w+x)*n..(y*w+x+1)*n]);
+        }
+        f(&mut line, n, false);
+        for y in 0 .. h {
+            buf[(y*w+x)*n..(y*w+x+1)*n].copy_from_slice(&line[y*n..(y+1)*n]);
+        }
+    }
+    for (y, row) in (y1 ..= y2).zip(buf.chunks(w * n)) {
+        for (x, v) in (x1 ..= x2).zip(row.chunks(n)) {
+            let p = pix.components_mut((x,y));
+            for (i, p) in p.iter_mut().enumerate() {
+                let vi = u32::from(v[i]);
+                *p = match T::ALPHA {
+                    Some(a) if a == i => ((vi + 127) / 255).min(255) as u8,
+                    Some(a) => match u32::from(v[a]) {
+                        0 => 0,
+                        av => ((vi * 255 + av / 2) / av).min(255) as u8,
+                    },
+                    None => vi.min(255) as u8,
+                };
+            }
+        }
+    }
+}
+
+/// Stack blur a line of pixels with `n` components and a radius `r`
+///
+/// Pixels beyond the ends of the line repeat the end pixels
+fn stack_blur_line(line: &mut [u16], n: usize, r: usize) {
+    let len = line.len() / n;
+    if r == 0 || len < 2 {
+        return;
+    }
+    let div = 2 * r + 1;
+    let den = ((r + 1) * (r + 1)) as u64;
+    let src = line.to_vec();
+    let px = |i: usize, c: usize| u64::from(src[i.min(len-1) * n + c]);
+    let mut stack = vec![0u64; div * n];
+    for c in 0 .. n {
+        let (mut sum, mut sum_in, mut sum_out) = (0, 0, 0);
+        for i in 0 ..= r {
+            let v = px(0, c);
+            stack[i * n + c] = v;
+            sum += v * (i as u64 + 1);
+            sum_out += v;
+        }
+        for i in 1 ..= r {
+            let v = px(i, c);
+            stack[(i + r) * n + c] = v;
+            sum += v * (r + 1 - i) as u64;
+            sum_in += v;
+        }
+        let mut sp = r;
+        let mut xp = r.min(len - 1);
+        for x in 0 .. len {
+            line[x * n + c] = ((sum + den / 2) / den) as u16;
+            sum -= sum_out;
+            let start = (sp + div - r) % div;
+            sum_out -= stack[start * n + c];
+            if xp < len - 1 {
+                xp += 1;
+            }
+            let v = px(xp, c);
+            stack[start * n + c] = v;
+            sum_in += v;
+            sum += sum_in;
+            sp = (sp + 1) % div;
+            let v = stack[sp * n + c];
+            sum_out += v;
+            sum_in -= v;
+        }
+    }
+}
+
+/// Blur with a triangular kernel of radius `rx` and `ry`, in pixels
+///
+/// Only pixels within `rect` (inclusive) are used and modified, if given
+pub fn stack_blur<T, B>(pix: &mut Pixfmt<T, B>, rx: usize, ry: usize, rect: Option<Rectangle<i64>>)
+    where T: Component8, B: PixelData, Pixfmt<T, B>: Pixel
+{
+    blur_lines(pix, rect, |line, n, row| {
+        stack_blur_line(line, n, if row { rx } else { ry })
+    });
+}
+
+/// Recursive Gaussian coefficients (b, b1, b2, b3) for a `radius`
+fn recursive_coefficients(radius: f64) -> (f64, f64, f64, f64) {
+    let s = radius * 0.5;
+    let q = if s < 2.5 {
+        3.97156 - 4.14554 * (1.0 - 0.26891 * s).sqrt()
+    } else {
+        0.98711 * s - 0.96330
+    };
+    let q2 = q * q;
+    let q3 = q2 * q;
+    let b0 = 1.0 / (1.578250 + 2.444130 * q + 1.428100 * q2 + 0.422205 * q3);
+    let b1 =  2.44413 * q + 2.85619 * q2 + 1.26661 * q3;
+    let b2 = -1.42810 * q2 - 1.26661 * q3;
+    let b3 =  0.422205 * q3;
+    let b = 1.0 - (b1 + b2 + b3) * b0;
+    (b, b1 * b0, b2 * b0, b3 * b0)
+}
+
+/// Recursive blur a line of pixels with `n` components and a `radius`
+fn recursive_blur_line(line: &mut [u16], n: usize, radius: f64) {
+    let len = line.len() / n;
+    if radius < 0.62 || len < 3 {
+        return;
+    }
+    let (b, b1, b2, b3) = recursive_coefficients(radius);
+    let calc = |c1: f64, c2: f64, c3: f64, c4: f64| b * c1 + b1 * c2 + b2 * c3 + b3 * c4;
+    let mut sum1 = vec![0.0; len];
+    let mut sum2 = vec![0.0; len];
+    for c in 0 .. n {
+        let v = |i: usize| f64::from(line[i * n + c]);
+        sum1[0] = calc(v(0), v(0), v(0), v(0));
+        sum1[1] = calc(v(1), sum1[0], sum1[0], sum1[0]);
+        sum1[2] = calc(v(2), sum1[1], sum1[0], sum1[0]);
+        for i in 3 .. len {
+            sum1[i] = calc(v(i), sum1[i-1], sum1[i-2], sum1[i-3]);
+        }
+        let e = len - 1;
+        sum2[e]   = calc(sum1[e],   sum1[e],   sum1[e],   sum1[e]);
+        sum2[e-1] = calc(sum1[e-1], sum2[e],   sum2[e],   sum2[e]);
+        sum2[e-2] = calc(sum1[e-2], sum2[e-1], sum2[e],   sum2[e]);
+        for i in (0 .. e-2).rev() {
+            sum2[i] = calc(sum1[i], sum2[i+1], sum2[i+2], sum2[i+3]);
+        }
+        for (i, s) in sum2.iter().enumerate() {
+            line[i * n + c] = s.round().clamp(0.0, 65535.0) as u16;
+        }
+    }
+}
+
+/// Blur with a recursive Gaussian of radius `rx` and `ry`, in pixels
+///
+/// Radii less than 0.62 leave that direction unchanged.  Only pixels
+///   within `rect` (inclusive) are used and modified, if given
+pub fn recursive_blur<T, B>(pix: &mut Pixfmt<T, B>, rx: f64, ry: f64, rect: Option<Rectangle<i64>>)
+    where T: Component8, B: PixelData, Pixfmt<T, B>: Pixel
+{
+    blur_lines(pix, rect, |line, n, row| {
+        recursive_blur_line(line, n, if row { rx } else { ry })
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn stack_blur_line_impulse() {
+        let mut line = vec![0, 0, 0, 90, 0, 0, 0];
+        stack_blur_line(&mut line, 1, 2);
+        assert_eq!(line, [0, 10, 20, 30, 20, 10, 0]);
+    }
+    #[test]
+    fn stack_blur_line_edges() {
+        // Edge pixels are repeated
+        let mut line = vec![90, 0, 0, 0, 0];
+        stack_blur_line(&mut line, 1, 1);
+        assert_eq!(line, [68, 23, 0, 0, 0]);
+    }
+    #[test]
+    fn recursive_coefficients_sum() {
+        for &r in &[1.0, 3.0, 10.0, 50.0] {
+            let (b, b1, b2, b3) = recursive_coefficients(r);
+            assert!((b + b1 + b2 + b3 - 1.0).abs() < 1e-9);
+        }
+    }
+}
@@ -1,63 +1,146 @@
 //! Rendering buffer
 
+use std::borrow::Cow;
+use std::convert::TryFrom;
+
 /// Rendering Buffer
 ///
 /// Data is stored as row-major order (C-format)
+///
+/// Rows are `stride` bytes apart, starting at `origin`.  A negative `stride`
+///   stores the rows bottom-up, with the first row at the end of `data`
 #[derive(Debug,Default)]
-pub(crate) struct RenderingBuffer {
+pub(crate) struct RenderingBuffer<B = Vec<u8>> {
     /// Pixel / Component level data of Image
-    pub data: Vec<u8>,
+    pub data: B,
     /// Image Width in pixels
     pub width: usize,
     /// Image Height in pixels
     pub height: usize,
-    /// Bytes per pixel or number of color components 
+    /// Bytes per pixel or number of color components
     pub bpp: usize,
+    /// Bytes from the start of one row to the start of the next
+    pub stride: i64,
+    /// Offset in bytes of the first row
+    pub origin: usize,
 }
 
 
 impl RenderingBuffer {
     /// Create a new buffer of width, height, and bpp
     ///
-    /// Data for the Image is allocated 
+    /// Data for the Image is allocated
     pub fn new(width: usize, height: usize, bpp: usize) -> Self {
-        RenderingBuffer {
-            width, height, bpp, data: vec![0u8; width * height * bpp]
+        Self::from_buf(vec![0u8; width * height * bpp], width, height, bpp)
+    }
+    pub fn from_buf(data: Vec<u8>, width: usize, height: usize, bpp: usize) -> Self {
+        assert_eq!(data.len(), width * height * bpp);
+        let stride = (width * bpp) as i64;
+        RenderingBuffer { width, height, bpp, data, stride, origin: 0 }
+    }
+}
+
+impl<B> RenderingBuffer<B> where B: AsRef<[u8]> {
+    /// Attach to existing data with rows `stride` bytes apart
+    ///
+    /// Returns None if the rows do not fit within `data` or their size overflows
+    pub fn attach(data: B, width: usize, height: usize, bpp: usize, stride: i64) -> Option<Self> {
+        let row = width.checked_mul(bpp)?;
+        let step = usize::try_from(stride.unsigned_abs()).ok()?;
+        if step < row {
+            return None;
         }
+        let span = step.checked_mul(height.checked_sub(1)?)?.checked_add(row)?;
+        if span > data.as_ref().len() {
+            return None;
+        }
+        let origin = if stride < 0 { span - row } else { 0 };
+        Some(RenderingBuffer { width, height, bpp, data, stride, origin })
     }
     /// Size of underlying Rendering Buffer
     pub fn len(&self) -> usize {
-        self.data.len()
+        self.data.as_ref().len()
+    }
+    /// Offset in bytes of row `y`
+    fn row_start(&self, y: usize) -> usize {
+        (self.origin as i64 + y as i64 * self.stride) as usize
+    }
+    /// Pixel data of row `y`
+    pub fn row(&self, y: usize) -> &[u8] {
+        let i = self.row_start(y);
+        &self.data.as_ref()[i .. i + self.width * self.bpp]
+    }
+    /// Pixel data with rows top-down and without padding
+    ///
+    /// Data is only copied if it is not already stored this way
+    pub fn packed(&self) -> Cow<'_, [u8]> {
+        let n = self.width * self.bpp;
+        if self.stride == n as i64 {
+            Cow::Borrowed(&self.data.as_ref()[self.origin .. self.origin + n * self.height])
+        } else {
+            Cow::Owned((0 .. self.height).flat_map(|y| self.row(y)).copied().collect())
+        }
+    }
+}
+
+impl<B> RenderingBuffer<B> where B: AsRef<[u8]> + AsMut<[u8]> {
+    /// Mutable pixel data of row `y`
+    pub fn row_mut(&mut self, y: usize) -> &mut [u8] {
+        let i = self.row_start(y);
+        let n = self.width * self.bpp;
+        &mut self.data.as_mut()[i .. i + n]
+    }
+    /// Copy the components of a single pixel, `pix`, to every pixel
+    pub fn fill(&mut self, pix: &[u8]) {
+        debug_assert_eq!(pix.len(), self.bpp);
+        for chunk in self.row_mut(0).chunks_exact_mut(pix.len()) {
+            chunk.copy_from_slice(pix);
+        }
+        let src = self.row_start(0);
+        let n = self.width * self.bpp;
+        for y in 1 .. self.height {
+            let dst = self.row_start(y);
+            self.data.as_mut().copy_within(src .. src + n, dst);
+        }
+    }
+    /// Region of `width` x `height` pixels starting at (`x`,`y`), sharing
+    ///   the underlying data
+    ///
+    /// The region must lie within the buffer
+    pub fn sub(&mut self, x: usize, y: usize, width: usize, height: usize) -> RenderingBuffer<&mut [u8]> {
+        assert!(width > 0 && height > 0);
+        assert!(x + width <= self.width && y + height <= self.height);
+        let first = self.row_start(y).min(self.row_start(y + height - 1));
+        let span = self.stride.unsigned_abs() as usize * (height - 1) + width * self.bpp;
+        let i = first + x * self.bpp;
+        let (bpp, stride) = (self.bpp, self.stride);
+        RenderingBuffer::attach(&mut self.data.as_mut()[i .. i + span], width, height, bpp, stride)
+            .expect("region within buffer")
     }
     /// Clear an image
     pub fn clear(&mut self) {
-        self.data.iter_mut().for_each(|v| *v = 255);
-    }
-    pub fn from_buf(data: Vec<u8>, width: usize, height: usize, bpp: usize) -> Self {
-        assert_eq!(data.len(), width * height * bpp);
-        RenderingBuffer { width, height, bpp, data }
+        for y in 0 .. self.height {
+            self.row_mut(y).iter_mut().for_each(|v| *v = 255);
+        }
     }
 }
 
 use std::ops::Index;
 use std::ops::IndexMut;
 
-impl Index<(usize,usize)> for RenderingBuffer {
+impl<B> Index<(usize,usize)> for RenderingBuffer<B> where B: AsRef<[u8]> {
     type Output = [u8];
     fn index(&self, index: (usize, usize)) -> &[u8] {
         debug_assert!(index.0 < self.width, "request {} >= {} width :: index", index.0, self.width);
         debug_assert!(index.1 < self.height, "request {} >= {} height :: index", index.1, self.height);
-        let i = ((index.1 * self.width) + index.0) * self.bpp;
-        debug_assert!(i < self.data.len());
-        &self.data[i..]
+        &self.row(index.1)[index.0 * self.bpp ..]
     }
 }
-impl IndexMut<(usize,usize)> for RenderingBuffer {
+impl<B> IndexMut<(usize,usize)> for RenderingBuffer<B> where B: AsRef<[u8]> + AsMut<[u8]> {
     fn index_mut(&mut self, index: (usize, usize)) -> &mut [u8] {
         debug_assert!(index.0 < self.width, "request {} >= {} width :: index_mut", index.0, self.width);
         debug_assert!(index.1 < self.height, "request {} >= {} height :: index_mut", index.1, self.height);
-        let i = ((index.1 * self.width) + index.0) * self.bpp;
-        debug_assert!(i < self.data.len());
-        &mut self.data[i..]
+        let bpp = self.bpp;
+        &mut self.row_mut(index.1)[index.0 * bpp ..]
     }
 }
@@ -260,6 +260,7 @@ impl RasterizerCell {
             let cy = (y1 + y2) / 2;
             self.line(x1, y1, cx, cy);
             self.line(cx, cy, x2, y2);
+            return;
         }
         let dy = y2-y1;
         // Downshift
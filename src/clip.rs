@@ -2,6 +2,11 @@
 
 //use crate::POLY_SUBPIXEL_SCALE;
 use crate::cell::RasterizerCell;
+use crate::paths::PathCommand;
+use crate::paths::Vertex;
+use crate::VertexSource;
+use crate::raster::RasConv;
+use crate::raster::RasConvInt;
 
 /// Rectangle
 #[derive(Debug,Copy,Clone)]
@@ -21,7 +26,7 @@ impl<T> Rectangle<T> where T: std::cmp::PartialOrd + Copy {
     /// Values are sorted before storing
     pub fn new(x1: T, y1: T, x2: T, y2: T) -> Self {
         let (x1, x2) = if x1 > x2 { (x2,x1) } else { (x1,x2) };
-        let (y1, y2) = if y1 > x2 { (y2,y1) } else { (y1,y2) };
+        let (y1, y2) = if y1 > y2 { (y2,y1) } else { (y1,y2) };
         Self { x1,y1,x2,y2 }
     }
     /// Get location of point relative to rectangle
@@ -106,34 +111,42 @@ fn clip_flags<T: std::cmp::PartialOrd>(x: &T, y: &T, x1: &T, y1: &T, x2: &T, y2:
 
 /// Clip Region
 ///
-/// Clipping for Rasterizers
+/// Clipping for Rasterizers, coordinates are converted with a [`RasConv`]
+///
+/// [`RasConv`]: ../raster/trait.RasConv.html
 #[derive(Debug)]
-pub struct Clip {
+pub struct Clip<C: RasConv = RasConvInt> {
     /// Current x Point
-    x1: i64,
+    x1: C::Coord,
     /// Current y Point
-    y1: i64,
+    y1: C::Coord,
     /// Rectangle to clip on
-    clip_box: Option<Rectangle<i64>>,
+    clip_box: Option<Rectangle<C::Coord>>,
     /// Current clip flag for point (x1,y1)
     clip_flag: u8,
 }
 
-fn mul_div(a: i64, b: i64, c: i64) -> i64 {
-    let (a,b,c) = (a as f64, b as f64, c as f64);
-    (a * b / c).round() as i64
+impl<C: RasConv> Default for Clip<C> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
-impl Clip {
+
+impl<C: RasConv> Clip<C> {
     /// Create new Clipping region
     pub fn new() -> Self {
-        Self {x1: 0, y1: 0,
+        Self {x1: C::upscale(0.0), y1: C::upscale(0.0),
               clip_box: None,
               clip_flag: INSIDE }
     }
+    /// Draw a line into the RasterizerCell, converting to subpixel scale
+    fn line(ras: &mut RasterizerCell, x1: C::Coord, y1: C::Coord, x2: C::Coord, y2: C::Coord) {
+        ras.line(C::xi(x1), C::yi(y1), C::xi(x2), C::yi(y2));
+    }
     /// Clip a line along the top and bottom of the regon
     fn line_clip_y(&self, ras: &mut RasterizerCell,
-                   x1: i64, y1: i64,
-                   x2: i64, y2: i64,
+                   x1: C::Coord, y1: C::Coord,
+                   x2: C::Coord, y2: C::Coord,
                    f1: u8, f2: u8) {
         let b = match self.clip_box {
             None => return,
@@ -143,7 +156,7 @@ impl Clip {
         let f2 = f2 & (TOP|BOTTOM);
         // Fully Visible in y
         if f1 == INSIDE && f2 == INSIDE {
-            ras.line(x1,y1,x2,y2);
+            Self::line(ras, x1,y1,x2,y2);
         } else {
             // Both points above or below clip box
             if f1 == f2 {
@@ -151,29 +164,29 @@ impl Clip {
             }
             let (mut tx1, mut ty1, mut tx2, mut ty2) = (x1,y1,x2,y2);
             if f1 == BOTTOM {
-                tx1 = x1 + mul_div(b.y1-y1, x2-x1, y2-y1);
+                tx1 = x1 + C::mul_div(b.y1-y1, x2-x1, y2-y1);
                 ty1 = b.y1;
             }
             if f1 == TOP {
-                tx1 = x1 + mul_div(b.y2-y1, x2-x1, y2-y1);
+                tx1 = x1 + C::mul_div(b.y2-y1, x2-x1, y2-y1);
                 ty1 = b.y2;
             }
             if f2 == BOTTOM {
-                tx2 = x1 + mul_div(b.y1-y1, x2-x1, y2-y1);
+                tx2 = x1 + C::mul_div(b.y1-y1, x2-x1, y2-y1);
                 ty2 = b.y1;
             }
             if f2 == TOP {
-                tx2 = x1 + mul_div(b.y2-y1, x2-x1, y2-y1);
+                tx2 = x1 + C::mul_div(b.y2-y1, x2-x1, y2-y1);
                 ty2 = b.y2;
             }
-            ras.line(tx1,tx2,ty1,ty2);
+            Self::line(ras, tx1,ty1,tx2,ty2);
         }
     }
 
     /// Draw a line from (x1,y1) to (x2,y2) into a RasterizerCell
     ///
     /// Final point (x2,y2) is saved internally as (x1,y1))
-    pub(crate) fn line_to(&mut self, ras: &mut RasterizerCell, x2: i64, y2: i64) {
+    pub(crate) fn line_to(&mut self, ras: &mut RasterizerCell, x2: C::Coord, y2: C::Coord) {
         if let Some(ref b) = self.clip_box {
             let f2 = b.clip_flags(x2,y2);
             // Both points above or below clip box
@@ -189,26 +202,26 @@ impl Clip {
             match (f1 & (LEFT|RIGHT), f2 & (LEFT|RIGHT)) {
                 (INSIDE,INSIDE) => self.line_clip_y(ras, x1,y1,x2,y2,f1,f2),
                 (INSIDE,RIGHT) => {
-                    let y3 = y1 + mul_div(b.x2-x1, y2-y1, x2-x1);
+                    let y3 = y1 + C::mul_div(b.x2-x1, y2-y1, x2-x1);
                     let f3 = b.clip_flags(b.x2, y3);
                     self.line_clip_y(ras, x1,   y1, b.x2, y3, f1, f3);
                     self.line_clip_y(ras, b.x2, y3, b.x2, y2, f3, f2);
                 },
                 (RIGHT,INSIDE) => {
-                    let y3 = y1 + mul_div(b.x2-x1, y2-y1, x2-x1);
+                    let y3 = y1 + C::mul_div(b.x2-x1, y2-y1, x2-x1);
                     let f3 = b.clip_flags(b.x2, y3);
                     self.line_clip_y(ras, b.x2, y1, b.x2, y3, f1, f3);
                     self.line_clip_y(ras, b.x2, y3,   x2, y2, f3, f2);
                 },
                 (INSIDE,LEFT) => {
-                    let y3 = y1 + mul_div(b.x1-x1, y2-y1, x2-x1);
+                    let y3 = y1 + C::mul_div(b.x1-x1, y2-y1, x2-x1);
                     let f3 = b.clip_flags(b.x1, y3);
                     self.line_clip_y(ras, x1,   y1, b.x1, y3, f1, f3);
                     self.line_clip_y(ras, b.x1, y3, b.x1, y2, f3, f2);
                 },
                 (RIGHT,LEFT) => {
-                    let y3 = y1 + mul_div(b.x2-x1, y2-y1, x2-x1);
-                    let y4 = y1 + mul_div(b.x1-x1, y2-y1, x2-x1);
+                    let y3 = y1 + C::mul_div(b.x2-x1, y2-y1, x2-x1);
+                    let y4 = y1 + C::mul_div(b.x1-x1, y2-y1, x2-x1);
                     let f3 = b.clip_flags(b.x2, y3);
                     let f4 = b.clip_flags(b.x1, y4);
                     self.line_clip_y(ras, b.x2, y1, b.x2, y3, f1, f3);
@@ -216,14 +229,14 @@ impl Clip {
                     self.line_clip_y(ras, b.x1, y4, b.x1, y2, f4, f2);
                 },
                 (LEFT,INSIDE) => {
-                    let y3 = y1 + mul_div(b.x1-x1, y2-y1, x2-x1);
+                    let y3 = y1 + C::mul_div(b.x1-x1, y2-y1, x2-x1);
                     let f3 = b.clip_flags(b.x1, y3);
                     self.line_clip_y(ras, b.x1, y1, b.x1, y3, f1, f3);
                     self.line_clip_y(ras, b.x1, y3,   x2, y2, f3, f2);
                 },
                 (LEFT,RIGHT) => {
-                    let y3 = y1 + mul_div(b.x1-x1, y2-y1, x2-x1);
-                    let y4 = y1 + mul_div(b.x2-x1, y2-y1, x2-x1);
+                    let y3 = y1 + C::mul_div(b.x1-x1, y2-y1, x2-x1);
+                    let y4 = y1 + C::mul_div(b.x2-x1, y2-y1, x2-x1);
                     let f3 = b.clip_flags(b.x1, y3);
                     let f4 = b.clip_flags(b.x2, y4);
                     self.line_clip_y(ras, b.x1, y1, b.x1, y3, f1, f3);
@@ -237,7 +250,7 @@ impl Clip {
             }
             self.clip_flag = f2;
         } else {
-            ras.line(self.x1, self.y1, x2, y2);
+            Self::line(ras, self.x1, self.y1, x2, y2);
         }
         self.x1 = x2;
         self.y1 = y2;
@@ -245,7 +258,7 @@ impl Clip {
     /// Move to point (x2,y2)
     ///
     /// Point is saved internally as (x1,y1)
-    pub(crate) fn move_to(&mut self, x2: i64, y2: i64) {
+    pub(crate) fn move_to(&mut self, x2: C::Coord, y2: C::Coord) {
         self.x1 = x2;
         self.y1 = y2;
         if let Some(ref b) = self.clip_box {
@@ -255,7 +268,484 @@ impl Clip {
         }
     }
     /// Define the clipping region
-    pub fn clip_box(&mut self, x1: i64, y1: i64, x2: i64, y2: i64) {
+    pub fn clip_box(&mut self, x1: C::Coord, y1: C::Coord, x2: C::Coord, y2: C::Coord) {
         self.clip_box = Some( Rectangle::new(x1, y1, x2, y2) );
     }
 }
+
+/// Clip a line segment to a rectangle, Liang-Barsky
+///
+/// Returns up to four points; corner points of the rectangle are included
+///   when the segment passes outside a corner so that clipped polygons stay
+///   closed along the rectangle boundary
+///
+/// See [Liang Barsky](https://en.wikipedia.org/wiki/Liang-Barsky_algorithm)
+fn clip_liang_barsky(x1: f64, y1: f64, x2: f64, y2: f64, b: &Rectangle<f64>) -> Vec<(f64,f64)> {
+    let nearzero = 1e-30;
+    let mut deltax = x2 - x1;
+    let mut deltay = y2 - y1;
+    let mut pts = Vec::with_capacity(4);
+
+    if deltax == 0.0 {
+        deltax = if x1 > b.x1 { -nearzero } else { nearzero };
+    }
+    if deltay == 0.0 {
+        deltay = if y1 > b.y1 { -nearzero } else { nearzero };
+    }
+    let (xin, xout) = if deltax > 0.0 { (b.x1, b.x2) } else { (b.x2, b.x1) };
+    let (yin, yout) = if deltay > 0.0 { (b.y1, b.y2) } else { (b.y2, b.y1) };
+
+    let tinx = (xin - x1) / deltax;
+    let tiny = (yin - y1) / deltay;
+    let (tin1, tin2) = if tinx < tiny { (tinx, tiny) } else { (tiny, tinx) };
+
+    if tin1 <= 1.0 {
+        if 0.0 < tin1 {
+            pts.push((xin, yin));
+        }
+        if tin2 <= 1.0 {
+            let toutx = (xout - x1) / deltax;
+            let touty = (yout - y1) / deltay;
+            let tout1 = if toutx < touty { toutx } else { touty };
+            if tin2 > 0.0 || tout1 > 0.0 {
+                if tin2 <= tout1 {
+                    if tin2 > 0.0 {
+                        if tinx > tiny {
+                            pts.push((xin, y1 + tinx * deltay));
+                        } else {
+                            pts.push((x1 + tiny * deltax, yin));
+                        }
+                    }
+                    if tout1 < 1.0 {
+                        if toutx < touty {
+                            pts.push((xout, y1 + toutx * deltay));
+                        } else {
+                            pts.push((x1 + touty * deltax, yout));
+                        }
+                    } else {
+                        pts.push((x2, y2));
+                    }
+                } else if tinx > tiny {
+                    pts.push((xin, yout));
+                } else {
+                    pts.push((xout, yin));
+                }
+            }
+        }
+    }
+    pts
+}
+
+/// Move a point outside the rectangle onto its boundary along a line
+fn clip_move_point(x1: f64, y1: f64, x2: f64, y2: f64, b: &Rectangle<f64>, p: (f64,f64), flags: u8) -> Option<(f64,f64)> {
+    let (mut x, mut y) = p;
+    if flags & (LEFT | RIGHT) != 0 {
+        if x1 == x2 {
+            return None;
+        }
+        let bound = if flags & LEFT != 0 { b.x1 } else { b.x2 };
+        y = (bound - x1) * (y2 - y1) / (x2 - x1) + y1;
+        x = bound;
+    }
+    let flags = b.clip_flags(x, y);
+    if flags & (TOP | BOTTOM) != 0 {
+        if y1 == y2 {
+            return None;
+        }
+        let bound = if flags & BOTTOM != 0 { b.y1 } else { b.y2 };
+        x = (bound - y1) * (x2 - x1) / (y2 - y1) + x1;
+        y = bound;
+    }
+    Some((x, y))
+}
+
+/// Clip a line segment to a rectangle
+///
+/// Returns the clipped segment, if any remains, and whether the first and
+///   second points were moved
+fn clip_line_segment(x1: f64, y1: f64, x2: f64, y2: f64, b: &Rectangle<f64>) -> Option<(f64,f64,f64,f64,bool,bool)> {
+    let f1 = b.clip_flags(x1, y1);
+    let f2 = b.clip_flags(x2, y2);
+    if f1 | f2 == INSIDE {
+        return Some((x1, y1, x2, y2, false, false));
+    }
+    let x_side = LEFT | RIGHT;
+    let y_side = TOP | BOTTOM;
+    if f1 & x_side != 0 && f1 & x_side == f2 & x_side {
+        return None;
+    }
+    if f1 & y_side != 0 && f1 & y_side == f2 & y_side {
+        return None;
+    }
+    let (mut tx1, mut ty1, mut tx2, mut ty2) = (x1, y1, x2, y2);
+    if f1 != INSIDE {
+        let (x, y) = clip_move_point(x1, y1, x2, y2, b, (x1, y1), f1)?;
+        tx1 = x;
+        ty1 = y;
+        if tx1 == tx2 && ty1 == ty2 {
+            return None;
+        }
+    }
+    if f2 != INSIDE {
+        let (x, y) = clip_move_point(x1, y1, x2, y2, b, (x2, y2), f2)?;
+        tx2 = x;
+        ty2 = y;
+        if tx1 == tx2 && ty1 == ty2 {
+            return None;
+        }
+    }
+    Some((tx1, ty1, tx2, ty2, f1 != INSIDE, f2 != INSIDE))
+}
+
+/// Clip Polygons to a Rectangle
+///
+/// Polygons are trimmed to the clip box before rasterizing, stroking or
+///   export; portions outside the box are replaced by the box boundary so
+///   the result remains a closed polygon.  Every sub-path is closed.
+///
+/// See agg_conv_clip_polygon.h and agg_vpgen_clip_polygon.h
+///
+///     use agg::{VertexSource,ClipPolygon,Path};
+///
+///     let mut path = Path::new();
+///     path.move_to(-10.0, -10.0);
+///     path.line_to(50.0, -10.0);
+///     path.line_to(50.0, 50.0);
+///     path.close_polygon();
+///
+///     let mut clip = ClipPolygon::new(path);
+///     clip.clip_box(0.0, 0.0, 20.0, 20.0);
+///     for v in clip.xconvert() {
+///         assert!(v.x >= 0.0 && v.x <= 20.0);
+///         assert!(v.y >= 0.0 && v.y <= 20.0);
+///     }
+#[derive(Debug)]
+pub struct ClipPolygon<T: VertexSource> {
+    /// Source of Vertices
+    source: T,
+    /// Rectangle to clip on
+    clip_box: Rectangle<f64>,
+}
+
+impl<T> ClipPolygon<T> where T: VertexSource {
+    /// Create a new Polygon Clipper
+    ///
+    /// The default clip box is (0,0) to (1,1)
+    pub fn new(source: T) -> Self {
+        Self { source, clip_box: Rectangle::new(0.0, 0.0, 1.0, 1.0) }
+    }
+    /// Set the clip box
+    pub fn clip_box(&mut self, x1: f64, y1: f64, x2: f64, y2: f64) {
+        self.clip_box = Rectangle::new(x1, y1, x2, y2);
+    }
+    /// Region of a point, as used by the polygon clipper
+    ///
+    /// Corner regions are unique so that moving between regions
+    ///   around a corner is detected
+    fn clipping_flags(&self, x: f64, y: f64) -> u8 {
+        let b = &self.clip_box;
+        if x < b.x1 {
+            if y > b.y2 { return 6; }
+            if y < b.y1 { return 12; }
+            return 4;
+        }
+        if x > b.x2 {
+            if y > b.y2 { return 3; }
+            if y < b.y1 { return 9; }
+            return 1;
+        }
+        if y > b.y2 { return 2; }
+        if y < b.y1 { return 8; }
+        0
+    }
+    /// Clip the Polygons
+    pub fn clip(&self) -> Vec<Vertex<f64>> {
+        let mut out = vec![];
+        let (mut x1, mut y1, mut flags) = (0.0, 0.0, 0);
+        let (mut x0, mut y0) = (0.0, 0.0);
+        let mut n = 0;
+        let mut first = 0;
+        let mut cmd = PathCommand::MoveTo;
+
+        let emit = |out: &mut Vec<Vertex<f64>>, cmd: &mut PathCommand, x: f64, y: f64| {
+            out.push(Vertex::new(x, y, *cmd));
+            *cmd = PathCommand::LineTo;
+        };
+
+        for v in self.source.xconvert() {
+            match v.cmd {
+                PathCommand::MoveTo => {
+                    self.close(&mut out, &mut cmd, &mut n, first, (x1, y1, flags), (x0, y0));
+                    first = out.len();
+                    cmd = PathCommand::MoveTo;
+                    flags = self.clipping_flags(v.x, v.y);
+                    if flags == 0 {
+                        emit(&mut out, &mut cmd, v.x, v.y);
+                    }
+                    x1 = v.x;
+                    y1 = v.y;
+                    x0 = v.x;
+                    y0 = v.y;
+                    n = 1;
+                },
+                PathCommand::LineTo => {
+                    if n == 0 {
+                        continue;
+                    }
+                    let f = self.clipping_flags(v.x, v.y);
+                    if f == flags {
+                        if f == 0 {
+                            emit(&mut out, &mut cmd, v.x, v.y);
+                        }
+                    } else {
+                        for (x, y) in clip_liang_barsky(x1, y1, v.x, v.y, &self.clip_box) {
+                            emit(&mut out, &mut cmd, x, y);
+                        }
+                    }
+                    flags = f;
+                    x1 = v.x;
+                    y1 = v.y;
+                    n += 1;
+                },
+                PathCommand::Close => {
+                    self.close(&mut out, &mut cmd, &mut n, first, (x1, y1, flags), (x0, y0));
+                    first = out.len();
+                },
+                PathCommand::Stop => break,
+            }
+        }
+        self.close(&mut out, &mut cmd, &mut n, first, (x1, y1, flags), (x0, y0));
+        out
+    }
+    /// Close the current polygon, back to its starting point (`x0`,`y0`)
+    fn close(&self, out: &mut Vec<Vertex<f64>>, cmd: &mut PathCommand,
+             n: &mut usize, first: usize,
+             (x1, y1, flags): (f64, f64, u8), (x0, y0): (f64, f64)) {
+        if *n > 2 {
+            let f = self.clipping_flags(x0, y0);
+            if f != flags {
+                for (x, y) in clip_liang_barsky(x1, y1, x0, y0, &self.clip_box) {
+                    out.push(Vertex::new(x, y, *cmd));
+                    *cmd = PathCommand::LineTo;
+                }
+            }
+        }
+        if out.len() > first {
+            let v = out[first];
+            out.push(Vertex::close_polygon(v.x, v.y));
+        }
+        *n = 0;
+    }
+}
+
+impl<T> VertexSource for ClipPolygon<T> where T: VertexSource {
+    fn xconvert(&self) -> Vec<Vertex<f64>> {
+        self.clip()
+    }
+}
+
+/// Clip Polylines to a Rectangle
+///
+/// Lines are trimmed to the clip box, portions outside the box are
+///   removed and the remaining pieces start new sub-paths.  Closed
+///   polygons are converted to polylines including the closing segment.
+///
+/// See agg_conv_clip_polyline.h and agg_vpgen_clip_polyline.h
+///
+///     use agg::{VertexSource,ClipPolyline,Path,PathCommand};
+///
+///     let mut path = Path::new();
+///     path.move_to(-10.0, 5.0);
+///     path.line_to(30.0, 5.0);
+///
+///     let mut clip = ClipPolyline::new(path);
+///     clip.clip_box(0.0, 0.0, 20.0, 20.0);
+///     let v = clip.xconvert();
+///     assert_eq!(v.len(), 2);
+///     assert_eq!((v[0].x, v[0].y, v[0].cmd), (0.0, 5.0, PathCommand::MoveTo));
+///     assert_eq!((v[1].x, v[1].y, v[1].cmd), (20.0, 5.0, PathCommand::LineTo));
+#[derive(Debug)]
+pub struct ClipPolyline<T: VertexSource> {
+    /// Source of Vertices
+    source: T,
+    /// Rectangle to clip on
+    clip_box: Rectangle<f64>,
+}
+
+impl<T> ClipPolyline<T> where T: VertexSource {
+    /// Create a new Polyline Clipper
+    ///
+    /// The default clip box is (0,0) to (1,1)
+    pub fn new(source: T) -> Self {
+        Self { source, clip_box: Rectangle::new(0.0, 0.0, 1.0, 1.0) }
+    }
+    /// Set the clip box
+    pub fn clip_box(&mut self, x1: f64, y1: f64, x2: f64, y2: f64) {
+        self.clip_box = Rectangle::new(x1, y1, x2, y2);
+    }
+    /// Clip the Polylines
+    pub fn clip(&self) -> Vec<Vertex<f64>> {
+        let mut out = vec![];
+        let (mut x1, mut y1) = (0.0, 0.0);
+        let (mut x0, mut y0) = (0.0, 0.0);
+        let mut move_to = true;
+        let mut started = false;
+
+        let line_to = |out: &mut Vec<Vertex<f64>>, move_to: &mut bool, x1: f64, y1: f64, x2: f64, y2: f64| {
+            if let Some((tx1, ty1, tx2, ty2, f1, f2)) = clip_line_segment(x1, y1, x2, y2, &self.clip_box) {
+                if f1 || *move_to {
+                    out.push(Vertex::move_to(tx1, ty1));
+                }
+                out.push(Vertex::line_to(tx2, ty2));
+                *move_to = f2;
+            }
+        };
+
+        for v in self.source.xconvert() {
+            match v.cmd {
+                PathCommand::MoveTo => {
+                    x1 = v.x;
+                    y1 = v.y;
+                    x0 = v.x;
+                    y0 = v.y;
+                    move_to = true;
+                    started = true;
+                },
+                PathCommand::LineTo => {
+                    if started {
+                        line_to(&mut out, &mut move_to, x1, y1, v.x, v.y);
+                    }
+                    x1 = v.x;
+                    y1 = v.y;
+                },
+                PathCommand::Close => {
+                    if started && (x1 != x0 || y1 != y0) {
+                        line_to(&mut out, &mut move_to, x1, y1, x0, y0);
+                    }
+                    x1 = x0;
+                    y1 = y0;
+                },
+                PathCommand::Stop => break,
+            }
+        }
+        out
+    }
+}
+
+impl<T> VertexSource for ClipPolyline<T> where T: VertexSource {
+    fn xconvert(&self) -> Vec<Vertex<f64>> {
+        self.clip()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::paths::Path;
+
+    fn xy(v: &[Vertex<f64>]) -> Vec<(f64,f64,PathCommand)> {
+        v.iter().map(|v| (v.x, v.y, v.cmd)).collect()
+    }
+    #[test]
+    fn rectangle_sorted() {
+        let r = Rectangle::new(10.0, 50.0, 0.0, 20.0);
+        assert_eq!((r.x1(), r.y1(), r.x2(), r.y2()), (0.0, 20.0, 10.0, 50.0));
+    }
+    #[test]
+    fn clip_polygon_inside() {
+        let mut path = Path::new();
+        path.move_to(1.0, 1.0);
+        path.line_to(5.0, 1.0);
+        path.line_to(5.0, 5.0);
+        path.close_polygon();
+        let mut clip = ClipPolygon::new(path);
+        clip.clip_box(0.0, 0.0, 10.0, 10.0);
+        assert_eq!(xy(&clip.xconvert()), vec![
+            (1.0, 1.0, PathCommand::MoveTo),
+            (5.0, 1.0, PathCommand::LineTo),
+            (5.0, 5.0, PathCommand::LineTo),
+            (1.0, 1.0, PathCommand::Close),
+        ]);
+    }
+    #[test]
+    fn clip_polygon_corner() {
+        // Triangle covering the upper left corner of the clip box
+        let mut path = Path::new();
+        path.move_to(-10.0, 5.0);
+        path.line_to(5.0, -10.0);
+        path.line_to(5.0, 5.0);
+        path.close_polygon();
+        let mut clip = ClipPolygon::new(path);
+        clip.clip_box(0.0, 0.0, 10.0, 10.0);
+        let v = clip.xconvert();
+        assert_eq!(v[0].cmd, PathCommand::MoveTo);
+        assert_eq!(v.last().unwrap().cmd, PathCommand::Close);
+        assert!(v.iter().any(|v| v.x == 0.0 && v.y == 0.0), "{:?}", v);
+        assert!(v.iter().any(|v| v.x == 5.0 && v.y == 5.0), "{:?}", v);
+        for v in &v {
+            assert!(v.x >= 0.0 && v.x <= 10.0 && v.y >= 0.0 && v.y <= 10.0);
+        }
+    }
+    #[test]
+    fn clip_polygon_enclosing() {
+        let mut path = Path::new();
+        path.move_to(-10.0, -10.0);
+        path.line_to(20.0, -10.0);
+        path.line_to(20.0, 20.0);
+        path.line_to(-10.0, 20.0);
+        path.close_polygon();
+        let mut clip = ClipPolygon::new(path);
+        clip.clip_box(0.0, 0.0, 10.0, 10.0);
+        let v = clip.xconvert();
+        for corner in &[(0.0,0.0), (10.0,0.0), (10.0,10.0), (0.0,10.0)] {
+            assert!(v.iter().any(|v| (v.x,v.y) == *corner), "{:?} {:?}", corner, v);
+        }
+        for v in &v {
+            assert!(v.x >= 0.0 && v.x <= 10.0 && v.y >= 0.0 && v.y <= 10.0);
+        }
+    }
+    #[test]
+    fn clip_polygon_outside() {
+        let mut path = Path::new();
+        path.move_to(20.0, 20.0);
+        path.line_to(30.0, 20.0);
+        path.line_to(30.0, 30.0);
+        path.close_polygon();
+        let mut clip = ClipPolygon::new(path);
+        clip.clip_box(0.0, 0.0, 10.0, 10.0);
+        assert!(clip.xconvert().is_empty());
+    }
+    #[test]
+    fn clip_polyline() {
+        // Leaves and re-enters the clip box
+        let mut path = Path::new();
+        path.move_to(2.0, 2.0);
+        path.line_to(2.0, 20.0);
+        path.line_to(8.0, 20.0);
+        path.line_to(8.0, 2.0);
+        let mut clip = ClipPolyline::new(path);
+        clip.clip_box(0.0, 0.0, 10.0, 10.0);
+        assert_eq!(xy(&clip.xconvert()), vec![
+            (2.0, 2.0, PathCommand::MoveTo),
+            (2.0, 10.0, PathCommand::LineTo),
+            (8.0, 10.0, PathCommand::MoveTo),
+            (8.0, 2.0, PathCommand::LineTo),
+        ]);
+    }
+    #[test]
+    fn clip_polyline_closed() {
+        let mut path = Path::new();
+        path.move_to(2.0, 2.0);
+        path.line_to(8.0, 2.0);
+        path.line_to(8.0, 8.0);
+        path.close_polygon();
+        let mut clip = ClipPolyline::new(path);
+        clip.clip_box(0.0, 0.0, 10.0, 10.0);
+        assert_eq!(xy(&clip.xconvert()), vec![
+            (2.0, 2.0, PathCommand::MoveTo),
+            (8.0, 2.0, PathCommand::LineTo),
+            (8.0, 8.0, PathCommand::LineTo),
+            (2.0, 2.0, PathCommand::LineTo),
+        ]);
+    }
+}
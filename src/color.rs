@@ -4,12 +4,12 @@ use crate::Color;
 use crate::math::multiply_u8;
 
 /// Convert an f64 [0,1] component to a u8 [0,255] component
-fn cu8(v: f64) -> u8 {
+pub(crate) fn cu8(v: f64) -> u8 {
     (v * 255.0).round() as u8
 }
 
 /// Convert from sRGB to RGB for a single component
-fn srgb_to_rgb(x: f64) -> f64 {
+pub(crate) fn srgb_to_rgb(x: f64) -> f64 {
     if x <= 0.04045 {
         x / 12.92
     } else {
@@ -17,7 +17,7 @@ fn srgb_to_rgb(x: f64) -> f64 {
     }
 }
 /// Convert from RGB to sRGB for a single component
-fn rgb_to_srgb(x: f64) -> f64 {
+pub(crate) fn rgb_to_srgb(x: f64) -> f64 {
     if x <= 0.003_130_8 {
         x * 12.92
     } else {
@@ -25,6 +25,37 @@ fn rgb_to_srgb(x: f64) -> f64 {
     }
 }
 
+/// Lookup tables between 8-bit sRGB and 16-bit linear components
+struct SrgbLut {
+    /// Linear value of each sRGB value
+    linear: [u16; 256],
+    /// Smallest linear value which converts to each sRGB value
+    threshold: [u16; 256],
+}
+
+fn srgb_lut() -> &'static SrgbLut {
+    static LUT: std::sync::OnceLock<SrgbLut> = std::sync::OnceLock::new();
+    LUT.get_or_init(|| {
+        let mut lut = SrgbLut { linear: [0; 256], threshold: [0; 256] };
+        for (i, v) in lut.linear.iter_mut().enumerate() {
+            *v = cu16(srgb_to_rgb(i as f64 / 255.0));
+        }
+        for (i, v) in lut.threshold.iter_mut().enumerate().skip(1) {
+            *v = cu16(srgb_to_rgb((i as f64 - 0.5) / 255.0));
+        }
+        lut
+    })
+}
+/// Convert an 8-bit sRGB component to a 16-bit linear component
+pub(crate) fn srgb8_to_linear16(v: u8) -> u16 {
+    srgb_lut().linear[usize::from(v)]
+}
+/// Convert a 16-bit linear component to an 8-bit sRGB component
+pub(crate) fn linear16_to_srgb8(v: u16) -> u8 {
+    let n = srgb_lut().threshold.partition_point(|&t| t <= v);
+    (n - 1) as u8
+}
+
 
 /// Color as Red, Green, Blue, and Alpha
 #[derive(Debug,Default,Copy,Clone,PartialEq)]
@@ -134,6 +165,10 @@ pub fn lightness(red: f64, green: f64, blue: f64) -> f64 {
 
     (cmax + cmin) / 2.0
 }
+/// Saturation max(R, G, B) - min(R, G, B)
+pub fn saturation(red: f64, green: f64, blue: f64) -> f64 {
+    red.max(green).max(blue) - red.min(green).min(blue)
+}
 /// Average 
 pub fn average(red: f64, green: f64, blue: f64) -> f64 {
     (red + green + blue) / 3.0
@@ -226,6 +261,249 @@ impl Rgba8pre {
     }
 }
 
+/// Color as Red, Green, Blue, stored in Blue, Green, Red order
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct Bgr8 {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+/// Color as Red, Green, Blue, and Alpha, stored in Blue, Green, Red, Alpha order
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct Bgra8 {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+/// Color as Red, Green, Blue, and Alpha, stored in Alpha, Red, Green, Blue order
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct Argb8 {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+/// Color as Red, Green, Blue, and Alpha, stored in Alpha, Blue, Green, Red order
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct Abgr8 {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+/// Color as Red, Green, Blue, and Alpha, Premultiplied, stored in Blue, Green, Red, Alpha order
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct Bgra8pre {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+/// Color as Red, Green, Blue, and Alpha, Premultiplied, stored in Alpha, Red, Green, Blue order
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct Argb8pre {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+/// Color as Red, Green, Blue, and Alpha, Premultiplied, stored in Alpha, Blue, Green, Red order
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct Abgr8pre {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Bgr8 {
+    pub fn from_trait<C: Color>(c: C) -> Self {
+        Self::new(c.red8(), c.green8(), c.blue8())
+    }
+    /// Create new color from red, green and blue
+    pub fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+}
+
+/// Constructors for colors with alpha in any component order
+///
+/// `$pre` is true if the components are premultiplied
+macro_rules! impl_rgba_order {
+    ($t:ident, $pre:expr) => {
+        impl $t {
+            pub fn from_trait<C: Color>(c: C) -> Self {
+                Self::new(c.red8(), c.green8(), c.blue8(), c.alpha8())
+            }
+            /// Create new color from red, green, blue and alpha
+            pub fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+                Self { r, g, b, a }
+            }
+        }
+        impl Color for $t {
+            fn   red(&self)  -> f64 { color_u8_to_f64(self.r) }
+            fn green(&self)  -> f64 { color_u8_to_f64(self.g) }
+            fn  blue(&self)  -> f64 { color_u8_to_f64(self.b) }
+            fn alpha(&self)  -> f64 { color_u8_to_f64(self.a) }
+            fn alpha8(&self) -> u8  { self.a }
+            fn red8(&self)   -> u8  { self.r }
+            fn green8(&self) -> u8  { self.g }
+            fn blue8(&self)  -> u8  { self.b }
+            fn is_premultiplied(&self) -> bool { $pre }
+        }
+    }
+}
+impl_rgba_order!(Bgra8, false);
+impl_rgba_order!(Argb8, false);
+impl_rgba_order!(Abgr8, false);
+impl_rgba_order!(Bgra8pre, true);
+impl_rgba_order!(Argb8pre, true);
+impl_rgba_order!(Abgr8pre, true);
+
+/// Convert an f64 [0,1] component to a u16 [0,65535] component
+pub(crate) fn cu16(v: f64) -> u16 {
+    (v.clamp(0.0, 1.0) * 65535.0).round() as u16
+}
+
+fn color_u16_to_f64(x: u16) -> f64 {
+    f64::from(x) / 65535.0
+}
+
+/// Gray scale, 16-bit
+#[derive(Debug,Copy,Clone,Default,PartialEq)]
+pub struct Gray16 {
+    pub value: u16,
+    pub alpha: u16,
+}
+impl Gray16 {
+    pub fn from_trait<C: Color>(c: C) -> Self {
+        let lum = luminance(c.red(), c.green(), c.blue());
+        Self::new_with_alpha(cu16(lum), cu16(c.alpha()))
+    }
+    /// Create a new gray scale value
+    pub fn new(value: u16) -> Self {
+        Self { value, alpha: 65535 }
+    }
+    pub fn new_with_alpha(value: u16, alpha: u16) -> Self {
+        Self { value, alpha }
+    }
+}
+
+/// Color as Red, Green, Blue, 16-bit
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct Rgb48 {
+    pub r: u16,
+    pub g: u16,
+    pub b: u16,
+}
+impl Rgb48 {
+    pub fn from_trait<C: Color>(c: C) -> Self {
+        Self::new(cu16(c.red()), cu16(c.green()), cu16(c.blue()))
+    }
+    /// Create new color
+    pub fn new(r: u16, g: u16, b: u16) -> Self {
+        Self { r, g, b }
+    }
+}
+
+/// Color as Red, Green, Blue, and Alpha, 16-bit
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct Rgba64 {
+    pub r: u16,
+    pub g: u16,
+    pub b: u16,
+    pub a: u16,
+}
+/// Color as Red, Green, Blue, and Alpha with pre-multiplied components, 16-bit
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct Rgba64pre {
+    pub r: u16,
+    pub g: u16,
+    pub b: u16,
+    pub a: u16,
+}
+impl Rgba64 {
+    pub fn from_trait<C: Color>(c: C) -> Self {
+        Self::new(cu16(c.red()), cu16(c.green()), cu16(c.blue()), cu16(c.alpha()))
+    }
+    /// Create new color
+    pub fn new(r: u16, g: u16, b: u16, a: u16) -> Self {
+        Self { r, g, b, a }
+    }
+}
+impl Rgba64pre {
+    pub fn from_trait<C: Color>(c: C) -> Self {
+        Self::new(cu16(c.red()), cu16(c.green()), cu16(c.blue()), cu16(c.alpha()))
+    }
+    /// Create new color
+    pub fn new(r: u16, g: u16, b: u16, a: u16) -> Self {
+        Self { r, g, b, a }
+    }
+}
+
+/// Color as Red, Green, Blue packed into 16 bits, 5-6-5 bits
+///
+/// Red is in the high bits
+#[derive(Debug,Default,Copy,Clone,PartialEq,Eq)]
+pub struct Rgb565(pub u16);
+
+/// Color as Red, Green, Blue packed into 16 bits, 5-5-5 bits
+///
+/// Red is in the high bits, the top bit is unused
+#[derive(Debug,Default,Copy,Clone,PartialEq,Eq)]
+pub struct Rgb555(pub u16);
+
+/// Reduce an 8-bit component to `bits`, rounding
+fn pack_bits(v: u8, bits: u32) -> u16 {
+    let max = (1u32 << bits) - 1;
+    ((u32::from(v) * max + 127) / 255) as u16
+}
+/// Expand a component of `bits` to 8-bits, 0 and the maximum are preserved
+fn unpack_bits(v: u16, bits: u32) -> u8 {
+    let v = v & ((1 << bits) - 1);
+    ((v << (8 - bits)) | (v >> (2 * bits - 8))) as u8
+}
+
+impl Rgb565 {
+    pub fn from_trait<C: Color>(c: C) -> Self {
+        Self::new(c.red8(), c.green8(), c.blue8())
+    }
+    /// Pack 8-bit red, green and blue components
+    pub fn new(r: u8, g: u8, b: u8) -> Self {
+        Rgb565(pack_bits(r, 5) << 11 | pack_bits(g, 6) << 5 | pack_bits(b, 5))
+    }
+}
+impl Rgb555 {
+    pub fn from_trait<C: Color>(c: C) -> Self {
+        Self::new(c.red8(), c.green8(), c.blue8())
+    }
+    /// Pack 8-bit red, green and blue components
+    pub fn new(r: u8, g: u8, b: u8) -> Self {
+        Rgb555(pack_bits(r, 5) << 10 | pack_bits(g, 5) << 5 | pack_bits(b, 5))
+    }
+}
+impl From<Rgba8> for Rgb565 {
+    fn from(c: Rgba8) -> Self {
+        Self::from_trait(c)
+    }
+}
+impl From<Rgb565> for Rgba8 {
+    fn from(c: Rgb565) -> Self {
+        Self::from_trait(c)
+    }
+}
+impl From<Rgba8> for Rgb555 {
+    fn from(c: Rgba8) -> Self {
+        Self::from_trait(c)
+    }
+}
+impl From<Rgb555> for Rgba8 {
+    fn from(c: Rgb555) -> Self {
+        Self::from_trait(c)
+    }
+}
+
 /// Color as standard Red, Green, Blue, Alpha
 ///
 /// See <https://en.wikipedia.org/wiki/SRGB>
@@ -253,6 +531,73 @@ impl Srgba8 {
     pub fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
         Self { r, g, b, a }
     }
+    /// Encoded components, [r, g, b, a]
+    pub fn into_slice(&self) -> [u8; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+}
+
+/// Color as standard Red, Green, Blue
+///
+/// See <https://en.wikipedia.org/wiki/SRGB>
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct Srgb8 {
+    /// Red
+    r: u8,
+    /// Green
+    g: u8,
+    /// Blue
+    b: u8,
+}
+
+impl Srgb8 {
+    pub fn from_rgb<C: Color>(c: C) -> Self {
+        let r = cu8(rgb_to_srgb(c.red()));
+        let g = cu8(rgb_to_srgb(c.green()));
+        let b = cu8(rgb_to_srgb(c.blue()));
+        Self::new(r,g,b)
+    }
+    /// Create a new Srgb8 color
+    pub fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+    /// Encoded components, [r, g, b]
+    pub fn into_slice(&self) -> [u8; 3] {
+        [self.r, self.g, self.b]
+    }
+}
+
+/// Color as standard Red, Green, Blue, Alpha with pre-multiplied components
+///
+/// Components are premultiplied in linear light before encoding
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct Srgba8pre {
+    /// Red
+    r: u8,
+    /// Green
+    g: u8,
+    /// Blue
+    b: u8,
+    /// Alpha
+    a: u8,
+}
+
+impl Srgba8pre {
+    /// Encode components of `c`, which are assumed to be premultiplied
+    pub fn from_rgb<C: Color>(c: C) -> Self {
+        let r = cu8(rgb_to_srgb(c.red()));
+        let g = cu8(rgb_to_srgb(c.green()));
+        let b = cu8(rgb_to_srgb(c.blue()));
+        Self::new(r,g,b,cu8(c.alpha()))
+    }
+    /// Create a new Srgba8pre color
+    pub fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+    /// Encoded components, [r, g, b, a]
+    pub fn into_slice(&self) -> [u8; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
 }
 
 #[derive(Debug,Default,Copy,Clone,PartialEq)]
@@ -285,6 +630,21 @@ impl Rgba32 {
             Rgba32::new(r, g, b, self.a)
         }
     }
+    /// Divide the color components by alpha
+    ///
+    /// Fully transparent colors become transparent black
+    pub fn demultiply(&self) -> Self {
+        if (self.a - 1.0).abs() <= f32::EPSILON {
+            Rgba32::new(self.r, self.g, self.b, self.a)
+        } else if self.a == 0.0 {
+            Rgba32::new(0., 0., 0., self.a)
+        } else {
+            let r = self.r / self.a;
+            let g = self.g / self.a;
+            let b = self.b / self.a;
+            Rgba32::new(r, g, b, self.a)
+        }
+    }
 }
 
 impl Color for Rgba8 {
@@ -309,6 +669,83 @@ impl Color for Rgb8 {
     fn blue8(&self) -> u8  { self.b }
     fn is_premultiplied(&self) -> bool { false }
 }
+impl Color for Bgr8 {
+    fn   red(&self) -> f64 { color_u8_to_f64(self.r) }
+    fn green(&self) -> f64 { color_u8_to_f64(self.g) }
+    fn  blue(&self) -> f64 { color_u8_to_f64(self.b) }
+    fn alpha(&self) -> f64 { 1.0 }
+    fn alpha8(&self) -> u8 { 255 }
+    fn red8(&self) -> u8   { self.r }
+    fn green8(&self) -> u8 { self.g }
+    fn blue8(&self) -> u8  { self.b }
+    fn is_premultiplied(&self) -> bool { false }
+}
+impl Color for Gray16 {
+    fn   red(&self)  -> f64 { color_u16_to_f64(self.value) }
+    fn green(&self)  -> f64 { color_u16_to_f64(self.value) }
+    fn  blue(&self)  -> f64 { color_u16_to_f64(self.value) }
+    fn alpha(&self)  -> f64 { color_u16_to_f64(self.alpha) }
+    fn alpha8(&self) -> u8  { cu8(self.alpha()) }
+    fn red8(&self)   -> u8  { cu8(self.red()) }
+    fn green8(&self) -> u8  { cu8(self.green()) }
+    fn blue8(&self)  -> u8  { cu8(self.blue()) }
+    fn is_premultiplied(&self) -> bool { false }
+}
+impl Color for Rgb48 {
+    fn   red(&self)  -> f64 { color_u16_to_f64(self.r) }
+    fn green(&self)  -> f64 { color_u16_to_f64(self.g) }
+    fn  blue(&self)  -> f64 { color_u16_to_f64(self.b) }
+    fn alpha(&self)  -> f64 { 1.0 }
+    fn alpha8(&self) -> u8  { 255 }
+    fn red8(&self)   -> u8  { cu8(self.red()) }
+    fn green8(&self) -> u8  { cu8(self.green()) }
+    fn blue8(&self)  -> u8  { cu8(self.blue()) }
+    fn is_premultiplied(&self) -> bool { false }
+}
+impl Color for Rgba64 {
+    fn   red(&self)  -> f64 { color_u16_to_f64(self.r) }
+    fn green(&self)  -> f64 { color_u16_to_f64(self.g) }
+    fn  blue(&self)  -> f64 { color_u16_to_f64(self.b) }
+    fn alpha(&self)  -> f64 { color_u16_to_f64(self.a) }
+    fn alpha8(&self) -> u8  { cu8(self.alpha()) }
+    fn red8(&self)   -> u8  { cu8(self.red()) }
+    fn green8(&self) -> u8  { cu8(self.green()) }
+    fn blue8(&self)  -> u8  { cu8(self.blue()) }
+    fn is_premultiplied(&self) -> bool { false }
+}
+impl Color for Rgba64pre {
+    fn   red(&self)  -> f64 { color_u16_to_f64(self.r) }
+    fn green(&self)  -> f64 { color_u16_to_f64(self.g) }
+    fn  blue(&self)  -> f64 { color_u16_to_f64(self.b) }
+    fn alpha(&self)  -> f64 { color_u16_to_f64(self.a) }
+    fn alpha8(&self) -> u8  { cu8(self.alpha()) }
+    fn red8(&self)   -> u8  { cu8(self.red()) }
+    fn green8(&self) -> u8  { cu8(self.green()) }
+    fn blue8(&self)  -> u8  { cu8(self.blue()) }
+    fn is_premultiplied(&self) -> bool { true }
+}
+impl Color for Rgb565 {
+    fn   red(&self)  -> f64 { color_u8_to_f64(self.red8()) }
+    fn green(&self)  -> f64 { color_u8_to_f64(self.green8()) }
+    fn  blue(&self)  -> f64 { color_u8_to_f64(self.blue8()) }
+    fn alpha(&self)  -> f64 { 1.0 }
+    fn alpha8(&self) -> u8  { 255 }
+    fn red8(&self)   -> u8  { unpack_bits(self.0 >> 11, 5) }
+    fn green8(&self) -> u8  { unpack_bits(self.0 >> 5, 6) }
+    fn blue8(&self)  -> u8  { unpack_bits(self.0, 5) }
+    fn is_premultiplied(&self) -> bool { false }
+}
+impl Color for Rgb555 {
+    fn   red(&self)  -> f64 { color_u8_to_f64(self.red8()) }
+    fn green(&self)  -> f64 { color_u8_to_f64(self.green8()) }
+    fn  blue(&self)  -> f64 { color_u8_to_f64(self.blue8()) }
+    fn alpha(&self)  -> f64 { 1.0 }
+    fn alpha8(&self) -> u8  { 255 }
+    fn red8(&self)   -> u8  { unpack_bits(self.0 >> 10, 5) }
+    fn green8(&self) -> u8  { unpack_bits(self.0 >> 5, 5) }
+    fn blue8(&self)  -> u8  { unpack_bits(self.0, 5) }
+    fn is_premultiplied(&self) -> bool { false }
+}
 impl Color for Rgba8pre {
     fn   red(&self) -> f64 { color_u8_to_f64(self.r) }
     fn green(&self) -> f64 { color_u8_to_f64(self.g) }
@@ -334,6 +771,28 @@ impl Color for Srgba8 {
     fn blue8(&self)  -> u8  { cu8(self.blue()) }
     fn is_premultiplied(&self) -> bool { false }
 }
+impl Color for Srgb8 {
+    fn   red(&self)  -> f64 { srgb_to_rgb(color_u8_to_f64(self.r)) }
+    fn green(&self)  -> f64 { srgb_to_rgb(color_u8_to_f64(self.g)) }
+    fn  blue(&self)  -> f64 { srgb_to_rgb(color_u8_to_f64(self.b)) }
+    fn alpha(&self)  -> f64 { 1.0 }
+    fn alpha8(&self) -> u8  { 255 }
+    fn red8(&self)   -> u8  { cu8(self.red()) }
+    fn green8(&self) -> u8  { cu8(self.green()) }
+    fn blue8(&self)  -> u8  { cu8(self.blue()) }
+    fn is_premultiplied(&self) -> bool { false }
+}
+impl Color for Srgba8pre {
+    fn   red(&self)  -> f64 { srgb_to_rgb(color_u8_to_f64(self.r)) }
+    fn green(&self)  -> f64 { srgb_to_rgb(color_u8_to_f64(self.g)) }
+    fn  blue(&self)  -> f64 { srgb_to_rgb(color_u8_to_f64(self.b)) }
+    fn alpha(&self)  -> f64 { color_u8_to_f64(self.a) }
+    fn alpha8(&self) -> u8  { self.a }
+    fn red8(&self)   -> u8  { cu8(self.red()) }
+    fn green8(&self) -> u8  { cu8(self.green()) }
+    fn blue8(&self)  -> u8  { cu8(self.blue()) }
+    fn is_premultiplied(&self) -> bool { true }
+}
 impl Color for Rgba32 {
     fn   red(&self)  -> f64 { f64::from(self.r) }
     fn green(&self)  -> f64 { f64::from(self.g) }
@@ -427,6 +886,28 @@ mod tests {
         assert_eq!(p, Rgba8pre { r: 128, g: 128, b: 128, a: 128 } )
     }
     #[test]
+    fn rgba32_demultiply_test() {
+        use super::Rgba32;
+        let c = Rgba32::new(1.0, 0.5, 0.0, 0.5);
+        assert_eq!(c.premultiply(), Rgba32::new(0.5, 0.25, 0.0, 0.5));
+        assert_eq!(c.premultiply().demultiply(), c);
+        let c = Rgba32::new(0.3, 0.2, 0.1, 0.0);
+        assert_eq!(c.demultiply(), Rgba32::new(0.0, 0.0, 0.0, 0.0));
+    }
+    #[test]
+    fn srgb_lut_test() {
+        use super::{srgb8_to_linear16, linear16_to_srgb8, srgb_to_rgb, cu16};
+        assert_eq!(srgb8_to_linear16(0), 0);
+        assert_eq!(srgb8_to_linear16(255), 65535);
+        assert_eq!(srgb8_to_linear16(188), cu16(srgb_to_rgb(188.0/255.0)));
+        for v in 0 ..= 255 {
+            assert_eq!(linear16_to_srgb8(srgb8_to_linear16(v)), v);
+        }
+        assert_eq!(linear16_to_srgb8(32768), 188);
+        assert_eq!(linear16_to_srgb8(65535), 255);
+        assert_eq!(linear16_to_srgb8(1), 0);
+    }
+    #[test]
     fn srgb_test() {
         let s = Srgba8::new(50,150,250,128);
         assert_eq!(s, Srgba8{r:50,g:150,b:250,a:128});
@@ -0,0 +1,167 @@
+//! Pixel Format Conversion
+//!
+//! Convert an image between any two pixel formats with [`convert`] or
+//!   [`convert_with`].  Colors are exchanged as [`Rgba32`] with components
+//!   that are not premultiplied, so premultiplied formats are demultiplied
+//!   when read and premultiplied when written.  Formats without alpha drop
+//!   the alpha channel.
+//!
+//!     use agg::{Pixfmt,Pixel,Source,Rgba8,Rgba8pre,Gray8,GrayConv};
+//!
+//!     let mut pix = Pixfmt::<Rgba8>::new(1,1);
+//!     pix.set((0,0), Rgba8::new(255,0,0,128));
+//!     let pre : Pixfmt<Rgba8pre> = agg::convert(&pix);
+//!     assert_eq!(pre.get((0,0)), Rgba8::new(128,0,0,128));
+//!
+//!     let gray : Pixfmt<Gray8> = agg::convert_with(&pix, GrayConv::Lightness);
+//!     assert_eq!(gray.raw((0,0)), Gray8::new_with_alpha(128,128));
+//!
+//! [`convert`]: fn.convert.html
+//! [`convert_with`]: fn.convert_with.html
+//! [`Rgba32`]: ../color/struct.Rgba32.html
+
+use crate::color::*;
+use crate::pixfmt::Pixfmt;
+use crate::pixfmt::PixelData;
+use crate::pixfmt::PixelSize;
+
+use crate::Color;
+use crate::Pixel;
+use crate::Source;
+
+/// Conversion of red, green and blue components to gray
+#[derive(Debug,Default,Copy,Clone,PartialEq,Eq)]
+pub enum GrayConv {
+    /// Weighted sum, see [`luminance`](../color/fn.luminance.html)
+    #[default]
+    Luminance,
+    /// (max + min) / 2, see [`lightness`](../color/fn.lightness.html)
+    Lightness,
+    /// (red + green + blue) / 3, see [`average`](../color/fn.average.html)
+    Average,
+}
+
+impl GrayConv {
+    /// Gray value of a color
+    pub fn gray<C: Color>(&self, c: C) -> f64 {
+        let (r, g, b) = (c.red(), c.green(), c.blue());
+        match self {
+            GrayConv::Luminance => luminance(r, g, b),
+            GrayConv::Lightness => lightness(r, g, b),
+            GrayConv::Average   => average(r, g, b),
+        }
+    }
+}
+
+/// Pixel Formats supported by [`convert`](fn.convert.html)
+pub trait PixfmtConv: Pixel + PixelSize {
+    /// Color of the pixel at `id`, not premultiplied
+    fn read(&self, id: (usize, usize)) -> Rgba32;
+    /// Set the pixel at `id` to `c`, which is not premultiplied
+    ///
+    /// Gray formats combine the color components with `gray`
+    fn write(&mut self, id: (usize, usize), c: Rgba32, gray: GrayConv);
+}
+
+/// Formats which store colors that are not premultiplied
+macro_rules! impl_conv {
+    ($t:ident, |$p:ident, $id:ident| $read:expr) => {
+        impl<B: PixelData> PixfmtConv for Pixfmt<$t, B> {
+            fn read(&self, $id: (usize, usize)) -> Rgba32 {
+                let $p = self;
+                $read
+            }
+            fn write(&mut self, id: (usize, usize), c: Rgba32, _gray: GrayConv) {
+                self.set(id, c);
+            }
+        }
+    }
+}
+/// Formats which store premultiplied colors
+macro_rules! impl_conv_pre {
+    ($t:ident, |$p:ident, $id:ident| $read:expr) => {
+        impl<B: PixelData> PixfmtConv for Pixfmt<$t, B> {
+            fn read(&self, $id: (usize, usize)) -> Rgba32 {
+                let $p = self;
+                let c : Rgba32 = $read;
+                c.demultiply()
+            }
+            fn write(&mut self, id: (usize, usize), c: Rgba32, _gray: GrayConv) {
+                self.set(id, c.premultiply());
+            }
+        }
+    }
+}
+
+impl_conv!(Rgb8,   |p, id| Rgba32::from_trait(p.get(id)));
+impl_conv!(Rgba8,  |p, id| Rgba32::from_trait(p.get(id)));
+impl_conv!(Bgr8,   |p, id| Rgba32::from_trait(p.get(id)));
+impl_conv!(Bgra8,  |p, id| Rgba32::from_trait(p.get(id)));
+impl_conv!(Argb8,  |p, id| Rgba32::from_trait(p.get(id)));
+impl_conv!(Abgr8,  |p, id| Rgba32::from_trait(p.get(id)));
+impl_conv!(Rgb565, |p, id| Rgba32::from_trait(p.raw(id)));
+impl_conv!(Rgb555, |p, id| Rgba32::from_trait(p.raw(id)));
+impl_conv!(Rgb48,  |p, id| Rgba32::from_trait(p.raw(id)));
+impl_conv!(Rgba64, |p, id| Rgba32::from_trait(p.raw(id)));
+impl_conv!(Rgba32, |p, id| p.raw(id));
+impl_conv!(Srgb8,  |p, id| {
+    let [r, g, b] = p.raw(id);
+    Rgba32::from_trait(Srgb8::new(r, g, b))
+});
+impl_conv!(Srgba8, |p, id| {
+    let [r, g, b, a] = p.raw(id);
+    Rgba32::from_trait(Srgba8::new(r, g, b, a))
+});
+
+impl_conv_pre!(Rgba8pre,  |p, id| Rgba32::from_trait(p.get(id)));
+impl_conv_pre!(Bgra8pre,  |p, id| Rgba32::from_trait(p.get(id)));
+impl_conv_pre!(Argb8pre,  |p, id| Rgba32::from_trait(p.get(id)));
+impl_conv_pre!(Abgr8pre,  |p, id| Rgba32::from_trait(p.get(id)));
+impl_conv_pre!(Rgba64pre, |p, id| Rgba32::from_trait(p.raw(id)));
+impl_conv_pre!(Srgba8pre, |p, id| {
+    let [r, g, b, a] = p.raw(id);
+    Rgba32::from_trait(Srgba8pre::new(r, g, b, a))
+});
+
+impl<B: PixelData> PixfmtConv for Pixfmt<Gray8, B> {
+    fn read(&self, id: (usize, usize)) -> Rgba32 {
+        Rgba32::from_trait(self.raw(id))
+    }
+    fn write(&mut self, id: (usize, usize), c: Rgba32, gray: GrayConv) {
+        self.set(id, Gray8::new_with_alpha(cu8(gray.gray(c)), c.alpha8()));
+    }
+}
+impl<B: PixelData> PixfmtConv for Pixfmt<Gray16, B> {
+    fn read(&self, id: (usize, usize)) -> Rgba32 {
+        Rgba32::from_trait(self.raw(id))
+    }
+    fn write(&mut self, id: (usize, usize), c: Rgba32, gray: GrayConv) {
+        self.set(id, Gray16::new_with_alpha(cu16(gray.gray(c)), cu16(c.alpha())));
+    }
+}
+
+/// Convert an image to another pixel format
+///
+/// Gray formats use [`GrayConv::Luminance`], see [`convert_with`]
+///
+/// [`GrayConv::Luminance`]: enum.GrayConv.html#variant.Luminance
+/// [`convert_with`]: fn.convert_with.html
+pub fn convert<A, B, S>(src: &Pixfmt<A, S>) -> Pixfmt<B>
+    where Pixfmt<A, S>: PixfmtConv, Pixfmt<B>: PixfmtConv
+{
+    convert_with(src, GrayConv::default())
+}
+
+/// Convert an image to another pixel format, with `gray` used for
+///   conversions to gray
+pub fn convert_with<A, B, S>(src: &Pixfmt<A, S>, gray: GrayConv) -> Pixfmt<B>
+    where Pixfmt<A, S>: PixfmtConv, Pixfmt<B>: PixfmtConv
+{
+    let mut dst = Pixfmt::<B>::new(src.width(), src.height());
+    for y in 0 .. src.height() {
+        for x in 0 .. src.width() {
+            dst.write((x,y), src.read((x,y)), gray);
+        }
+    }
+    dst
+}
@@ -0,0 +1,478 @@
+//! Compositing Operators
+//!
+//! Porter-Duff operators and the SVG / PDF blend modes, applied by wrapping
+//!   an RGBA [`Pixfmt`] in a [`PixfmtCompOp`] or directly with [`CompOpBlend`]
+//!
+//!     use agg::{Pixfmt,Rgba8,Pixel,Source,CompOp,PixfmtCompOp};
+//!
+//!     let mut pix = Pixfmt::<Rgba8>::new(1,1);
+//!     pix.fill(Rgba8::new(255,128,0,255));
+//!     let mut pix = PixfmtCompOp::new(pix, CompOp::Multiply);
+//!     pix.blend_pix((0,0), Rgba8::new(128,128,128,255), 255);
+//!     assert_eq!(pix.get((0,0)), Rgba8::new(128,64,0,255));
+//!
+//! Colors are composited with premultiplied components; coverage less
+//!   than full interpolates between the original pixel and the result
+//!
+//! See [Compositing and Blending](https://www.w3.org/TR/compositing-1/)
+//!
+//! [`Pixfmt`]: ../pixfmt/struct.Pixfmt.html
+//! [`PixfmtCompOp`]: struct.PixfmtCompOp.html
+//! [`CompOpBlend`]: trait.CompOpBlend.html
+
+use crate::color::*;
+use crate::pixfmt::Pixfmt;
+use crate::pixfmt::PixelData;
+
+use crate::Color;
+use crate::Pixel;
+use crate::Source;
+
+/// Compositing Operator
+#[derive(Debug,Default,Copy,Clone,PartialEq,Eq)]
+pub enum CompOp {
+    /// Neither source nor destination
+    Clear,
+    /// Source only
+    Src,
+    /// Destination only
+    Dst,
+    /// Source over destination
+    #[default]
+    SrcOver,
+    /// Destination over source
+    DstOver,
+    /// Source where the destination is
+    SrcIn,
+    /// Destination where the source is
+    DstIn,
+    /// Source where the destination is not
+    SrcOut,
+    /// Destination where the source is not
+    DstOut,
+    /// Source where the destination is, destination elsewhere
+    SrcAtop,
+    /// Destination where the source is, source elsewhere
+    DstAtop,
+    /// Source and destination where the other is not
+    Xor,
+    /// Sum of source and destination
+    Plus,
+    /// Product of source and destination
+    Multiply,
+    /// Complement of the product of the complements
+    Screen,
+    /// Multiply or screen, depending on the destination
+    Overlay,
+    /// Minimum of source and destination
+    Darken,
+    /// Maximum of source and destination
+    Lighten,
+    /// Brighten the destination to reflect the source
+    ColorDodge,
+    /// Darken the destination to reflect the source
+    ColorBurn,
+    /// Multiply or screen, depending on the source
+    HardLight,
+    /// Darken or lighten, depending on the source
+    SoftLight,
+    /// Absolute difference of source and destination
+    Difference,
+    /// Similar to difference, with lower contrast
+    Exclusion,
+    /// Hue of the source with the saturation and luminosity of the destination
+    Hue,
+    /// Saturation of the source with the hue and luminosity of the destination
+    Saturation,
+    /// Hue and saturation of the source with the luminosity of the destination
+    Color,
+    /// Luminosity of the source with the hue and saturation of the destination
+    Luminosity,
+}
+
+/// Separable blend function B(Cb, Cs) of non-premultiplied components
+fn blend_channel(op: CompOp, cb: f64, cs: f64) -> f64 {
+    use CompOp::*;
+    match op {
+        Multiply => cb * cs,
+        Screen => cb + cs - cb * cs,
+        Overlay => blend_channel(HardLight, cs, cb),
+        Darken => cb.min(cs),
+        Lighten => cb.max(cs),
+        ColorDodge => {
+            if cb <= 0.0 {
+                0.0
+            } else if cs >= 1.0 {
+                1.0
+            } else {
+                (cb / (1.0 - cs)).min(1.0)
+            }
+        },
+        ColorBurn => {
+            if cb >= 1.0 {
+                1.0
+            } else if cs <= 0.0 {
+                0.0
+            } else {
+                1.0 - ((1.0 - cb) / cs).min(1.0)
+            }
+        },
+        HardLight => {
+            if cs <= 0.5 {
+                cb * 2.0 * cs
+            } else {
+                blend_channel(Screen, cb, 2.0 * cs - 1.0)
+            }
+        },
+        SoftLight => {
+            if cs <= 0.5 {
+                cb - (1.0 - 2.0 * cs) * cb * (1.0 - cb)
+            } else {
+                let d = if cb <= 0.25 {
+                    ((16.0 * cb - 12.0) * cb + 4.0) * cb
+                } else {
+                    cb.sqrt()
+                };
+                cb + (2.0 * cs - 1.0) * (d - cb)
+            }
+        },
+        Difference => (cb - cs).abs(),
+        Exclusion => cb + cs - 2.0 * cb * cs,
+        _ => cs,
+    }
+}
+
+/// Luminosity of a color, with the weights of the Compositing specification
+///
+/// These differ from [`luminance`](../color/fn.luminance.html), which uses
+///   Rec. 709 weights
+fn lum(c: [f64;3]) -> f64 {
+    0.3 * c[0] + 0.59 * c[1] + 0.11 * c[2]
+}
+
+/// Color with the luminosity `l`, keeping components within [0,1]
+fn set_lum(c: [f64;3], l: f64) -> [f64;3] {
+    let d = l - lum(c);
+    let c = [c[0] + d, c[1] + d, c[2] + d];
+    let l = lum(c);
+    let n = c[0].min(c[1]).min(c[2]);
+    let x = c[0].max(c[1]).max(c[2]);
+    let mut out = c;
+    for v in out.iter_mut() {
+        if n < 0.0 {
+            *v = l + (*v - l) * l / (l - n);
+        }
+        if x > 1.0 {
+            *v = l + (*v - l) * (1.0 - l) / (x - l);
+        }
+    }
+    out
+}
+
+/// Color with the saturation `s`, keeping the hue
+fn set_sat(c: [f64;3], s: f64) -> [f64;3] {
+    let n = c[0].min(c[1]).min(c[2]);
+    let x = c[0].max(c[1]).max(c[2]);
+    if x > n {
+        [(c[0] - n) * s / (x - n), (c[1] - n) * s / (x - n), (c[2] - n) * s / (x - n)]
+    } else {
+        [0.0; 3]
+    }
+}
+
+/// Blend function B(Cb, Cs) of non-premultiplied colors
+fn blend_color(op: CompOp, cb: [f64;3], cs: [f64;3]) -> [f64;3] {
+    use CompOp::*;
+    let sat = |c: [f64;3]| saturation(c[0], c[1], c[2]);
+    match op {
+        Hue        => set_lum(set_sat(cs, sat(cb)), lum(cb)),
+        Saturation => set_lum(set_sat(cb, sat(cs)), lum(cb)),
+        Color      => set_lum(cs, lum(cb)),
+        Luminosity => set_lum(cb, lum(cs)),
+        _ => [blend_channel(op, cb[0], cs[0]),
+              blend_channel(op, cb[1], cs[1]),
+              blend_channel(op, cb[2], cs[2])],
+    }
+}
+
+impl CompOp {
+    /// Composite premultiplied source `s` onto premultiplied destination `d`
+    ///
+    /// Components are [red, green, blue, alpha] in the range [0,1]
+    pub fn composite(self, d: [f64;4], s: [f64;4]) -> [f64;4] {
+        use CompOp::*;
+        let (sa, da) = (s[3], d[3]);
+        let each = |f: &dyn Fn(f64,f64) -> f64, a: f64| {
+            [f(s[0],d[0]), f(s[1],d[1]), f(s[2],d[2]), a]
+        };
+        match self {
+            Clear    => [0.0; 4],
+            Src      => s,
+            Dst      => d,
+            SrcOver  => each(&|s,d| s + d * (1.0 - sa), sa + da - sa * da),
+            DstOver  => each(&|s,d| d + s * (1.0 - da), sa + da - sa * da),
+            SrcIn    => each(&|s,_| s * da, sa * da),
+            DstIn    => each(&|_,d| d * sa, sa * da),
+            SrcOut   => each(&|s,_| s * (1.0 - da), sa * (1.0 - da)),
+            DstOut   => each(&|_,d| d * (1.0 - sa), da * (1.0 - sa)),
+            SrcAtop  => each(&|s,d| s * da + d * (1.0 - sa), da),
+            DstAtop  => each(&|s,d| d * sa + s * (1.0 - da), sa),
+            Xor      => each(&|s,d| s * (1.0 - da) + d * (1.0 - sa),
+                             sa + da - 2.0 * sa * da),
+            Plus     => each(&|s,d| (s + d).min(1.0), (sa + da).min(1.0)),
+            _ => {
+                let cs = unpremultiply(&s[..3], sa);
+                let cb = unpremultiply(&d[..3], da);
+                let b = blend_color(self, cb, cs);
+                let mut out = [0.0, 0.0, 0.0, sa + da - sa * da];
+                for (i, o) in out.iter_mut().take(3).enumerate() {
+                    *o = s[i] * (1.0 - da) + d[i] * (1.0 - sa) + sa * da * b[i];
+                }
+                out
+            },
+        }
+    }
+}
+
+/// Non-premultiplied color components
+fn unpremultiply(c: &[f64], a: f64) -> [f64;3] {
+    if a <= 0.0 {
+        [0.0; 3]
+    } else {
+        [(c[0] / a).min(1.0), (c[1] / a).min(1.0), (c[2] / a).min(1.0)]
+    }
+}
+
+fn to_f64(v: u8) -> f64 {
+    f64::from(v) / 255.0
+}
+fn to_u8(v: f64) -> u8 {
+    (v.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Premultiplied components of a color
+fn color_premultiplied<C: Color>(c: C) -> [f64;4] {
+    let v = [to_f64(c.red8()), to_f64(c.green8()), to_f64(c.blue8()), to_f64(c.alpha8())];
+    if c.is_premultiplied() {
+        v
+    } else {
+        [v[0] * v[3], v[1] * v[3], v[2] * v[3], v[3]]
+    }
+}
+
+/// Pixel Formats able to composite with any [`CompOp`]
+///
+///     use agg::{Pixfmt,Rgba8,Pixel,Source,CompOp,CompOpBlend};
+///
+///     let mut pix = Pixfmt::<Rgba8>::new(1,1);
+///     pix.fill(Rgba8::new(255,0,0,255));
+///     pix.blend_pix_op((0,0), Rgba8::new(0,0,255,255), CompOp::Hue, 255);
+///     assert_eq!(pix.get((0,0)), Rgba8::new(54,54,255,255));
+///
+/// [`CompOp`]: enum.CompOp.html
+pub trait CompOpBlend {
+    /// Composite `c` onto the pixel at `id` using `op` with `cover`
+    fn blend_pix_op<C: Color>(&mut self, id: (usize, usize), c: C, op: CompOp, cover: u64);
+}
+
+macro_rules! impl_comp_op_blend {
+    ($t:ty, $premultiplied:expr) => {
+        impl<B: PixelData> CompOpBlend for Pixfmt<$t, B> {
+            fn blend_pix_op<C: Color>(&mut self, id: (usize, usize), c: C, op: CompOp, cover: u64) {
+                if cover == 0 {
+                    return;
+                }
+                let p = self.get(id);
+                let mut d = [to_f64(p.r), to_f64(p.g), to_f64(p.b), to_f64(p.a)];
+                if ! $premultiplied {
+                    d = [d[0] * d[3], d[1] * d[3], d[2] * d[3], d[3]];
+                }
+                let r = op.composite(d, color_premultiplied(c));
+                let k = cover.min(255) as f64 / 255.0;
+                let mut v = [0.0; 4];
+                for (v, (d, r)) in v.iter_mut().zip(d.iter().zip(r.iter())) {
+                    *v = d + (r - d) * k;
+                }
+                if $premultiplied {
+                    self.set(id, Rgba8pre::new(to_u8(v[0]), to_u8(v[1]), to_u8(v[2]), to_u8(v[3])));
+                } else {
+                    let c = unpremultiply(&v[..3], v[3]);
+                    self.set(id, Rgba8::new(to_u8(c[0]), to_u8(c[1]), to_u8(c[2]), to_u8(v[3])));
+                }
+            }
+        }
+    }
+}
+
+impl_comp_op_blend!(Rgba8, false);
+impl_comp_op_blend!(Rgba8pre, true);
+
+/// Pixel Format Adaptor compositing with a [`CompOp`]
+///
+/// Every blending call is composited using the operator, regardless of the
+///   color's opacity.  `set`, `setn` and `fill` copy colors directly
+///
+/// [`CompOp`]: enum.CompOp.html
+#[derive(Debug)]
+pub struct PixfmtCompOp<T, B = Vec<u8>> {
+    /// Underlying Pixel Format
+    pub pixf: Pixfmt<T, B>,
+    op: CompOp,
+}
+
+impl<T, B> PixfmtCompOp<T, B> {
+    /// Create a new adaptor compositing onto `pixf` with `op`
+    pub fn new(pixf: Pixfmt<T, B>, op: CompOp) -> Self {
+        Self { pixf, op }
+    }
+    /// Current compositing operator
+    pub fn comp_op(&self) -> CompOp {
+        self.op
+    }
+    /// Set the compositing operator
+    pub fn set_comp_op(&mut self, op: CompOp) {
+        self.op = op;
+    }
+}
+
+impl<T, B> Source for PixfmtCompOp<T, B> where Pixfmt<T, B>: Source {
+    fn get(&self, id: (usize, usize)) -> Rgba8 {
+        self.pixf.get(id)
+    }
+}
+
+impl<T, B> CompOpBlend for PixfmtCompOp<T, B> where Pixfmt<T, B>: CompOpBlend {
+    fn blend_pix_op<C: Color>(&mut self, id: (usize, usize), c: C, op: CompOp, cover: u64) {
+        self.pixf.blend_pix_op(id, c, op, cover);
+    }
+}
+
+impl<T, B> Pixel for PixfmtCompOp<T, B> where Pixfmt<T, B>: Pixel + CompOpBlend {
+    fn cover_mask() -> u64 { Pixfmt::<T, B>::cover_mask() }
+    fn bpp(&self) -> usize { self.pixf.bpp() }
+    fn as_bytes(&self) -> &[u8] {
+        self.pixf.as_bytes()
+    }
+    fn packed(&self) -> std::borrow::Cow<'_, [u8]> {
+        self.pixf.packed()
+    }
+    fn to_file<P: AsRef<std::path::Path>>(&self, filename: P) -> Result<(),image::ImageError> {
+        self.pixf.to_file(filename)
+    }
+    fn width(&self) -> usize {
+        self.pixf.width()
+    }
+    fn height(&self) -> usize {
+        self.pixf.height()
+    }
+    fn set<C: Color>(&mut self, id: (usize, usize), c: C) {
+        self.pixf.set(id, c);
+    }
+    fn setn<C: Color>(&mut self, id: (usize, usize), n: usize, c: C) {
+        self.pixf.setn(id, n, c);
+    }
+    fn fill<C: Color>(&mut self, color: C) {
+        self.pixf.fill(color);
+    }
+    /// Composite `c` onto the pixel at `id` with `cover`
+    fn blend_pix<C: Color>(&mut self, id: (usize, usize), c: C, cover: u64) {
+        self.pixf.blend_pix_op(id, c, self.op, cover);
+    }
+    fn copy_or_blend_pix<C: Color>(&mut self, id: (usize,usize), color: C) {
+        self.blend_pix(id, color, 255);
+    }
+    fn copy_or_blend_pix_with_cover<C: Color>(&mut self, id: (usize,usize), color: C, cover: u64) {
+        self.blend_pix(id, color, cover);
+    }
+    fn blend_hline<C: Color>(&mut self, x: i64, y: i64, len: i64, color: C, cover: u64) {
+        let (x,y,len) = (x as usize, y as usize, len as usize);
+        for i in 0 .. len {
+            self.blend_pix((x+i,y), color, cover);
+        }
+    }
+    fn blend_vline<C: Color>(&mut self, x: i64, y: i64, len: i64, c: C, cover: u64) {
+        let (x,y,len) = (x as usize, y as usize, len as usize);
+        for i in 0 .. len {
+            self.blend_pix((x,y+i), c, cover);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn close(a: [f64;4], b: [f64;4]) -> bool {
+        a.iter().zip(b.iter()).all(|(a,b)| (a - b).abs() < 1e-9)
+    }
+    fn close3(a: [f64;3], b: [f64;3]) -> bool {
+        close([a[0], a[1], a[2], 0.0], [b[0], b[1], b[2], 0.0])
+    }
+    #[test]
+    fn porter_duff() {
+        let s = [0.5, 0.0, 0.0, 0.5];
+        let d = [0.0, 0.0, 1.0, 1.0];
+        assert!(close(CompOp::Clear.composite(d, s), [0.0; 4]));
+        assert!(close(CompOp::Src.composite(d, s), s));
+        assert!(close(CompOp::Dst.composite(d, s), d));
+        assert!(close(CompOp::SrcOver.composite(d, s), [0.5, 0.0, 0.5, 1.0]));
+        assert!(close(CompOp::DstOver.composite(d, s), d));
+        assert!(close(CompOp::SrcIn.composite(d, s), s));
+        assert!(close(CompOp::DstIn.composite(d, s), [0.0, 0.0, 0.5, 0.5]));
+        assert!(close(CompOp::SrcOut.composite(d, s), [0.0; 4]));
+        assert!(close(CompOp::DstOut.composite(d, s), [0.0, 0.0, 0.5, 0.5]));
+        assert!(close(CompOp::SrcAtop.composite(d, s), [0.5, 0.0, 0.5, 1.0]));
+        assert!(close(CompOp::DstAtop.composite(d, s), [0.0, 0.0, 0.5, 0.5]));
+        assert!(close(CompOp::Xor.composite(d, s), [0.0, 0.0, 0.5, 0.5]));
+        assert!(close(CompOp::Plus.composite(d, s), [0.5, 0.0, 1.0, 1.0]));
+    }
+    #[test]
+    fn blend_modes_opaque() {
+        let (cb, cs) = (0.25, 0.75);
+        let d = [cb, cb, cb, 1.0];
+        let s = [cs, cs, cs, 1.0];
+        let gray = |v: f64| [v, v, v, 1.0];
+        assert!(close(CompOp::Multiply.composite(d, s), gray(0.1875)));
+        assert!(close(CompOp::Screen.composite(d, s), gray(0.8125)));
+        assert!(close(CompOp::Overlay.composite(d, s), gray(0.375)));
+        assert!(close(CompOp::Darken.composite(d, s), gray(0.25)));
+        assert!(close(CompOp::Lighten.composite(d, s), gray(0.75)));
+        assert!(close(CompOp::ColorDodge.composite(d, s), gray(1.0)));
+        assert!(close(CompOp::ColorBurn.composite(d, s), gray(0.0)));
+        assert!(close(CompOp::HardLight.composite(d, s), gray(0.625)));
+        assert!(close(CompOp::SoftLight.composite(d, s), gray(0.375)));
+        assert!(close(CompOp::Difference.composite(d, s), gray(0.5)));
+        assert!(close(CompOp::Exclusion.composite(d, s), gray(0.625)));
+    }
+    #[test]
+    fn non_separable() {
+        // Luminosity is kept within [0,1] by scaling towards the luminosity
+        let c = set_lum([1.0, 0.0, 0.0], 0.9);
+        assert!((lum(c) - 0.9).abs() < 1e-9);
+        assert!(c.iter().all(|&v| (-1e-9 ..= 1.0 + 1e-9).contains(&v)));
+        let c = set_lum([0.0, 1.0, 1.0], 0.1);
+        assert!((lum(c) - 0.1).abs() < 1e-9);
+        assert!(c.iter().all(|&v| (-1e-9 ..= 1.0 + 1e-9).contains(&v)));
+        // Saturation keeps the order of the components
+        assert!(close3(set_sat([0.2, 0.6, 0.4], 0.5), [0.0, 0.5, 0.25]));
+        assert_eq!(set_sat([0.3, 0.3, 0.3], 0.5), [0.0; 3]);
+
+        let red = [1.0, 0.0, 0.0, 1.0];
+        let gray = [0.5, 0.5, 0.5, 1.0];
+        let l = lum([1.0, 0.0, 0.0]);
+        assert!((l - 0.3).abs() < 1e-9);
+        assert!(close(CompOp::Saturation.composite(red, gray), [l, l, l, 1.0]));
+        assert!(close(CompOp::Hue.composite(gray, gray), gray));
+        assert!(close(CompOp::Color.composite(red, red), red));
+        assert!(close(CompOp::Luminosity.composite(red, red), red));
+    }
+    #[test]
+    fn blend_modes_transparent() {
+        // Blend modes reduce to source-over with a transparent backdrop
+        let s = [0.25, 0.5, 0.0, 0.5];
+        for &op in &[CompOp::Multiply, CompOp::Screen, CompOp::Darken, CompOp::SoftLight,
+                    CompOp::Hue, CompOp::Luminosity] {
+            assert!(close(op.composite([0.0; 4], s), s), "{:?}", op);
+            assert!(close(op.composite([0.2, 0.2, 0.2, 1.0], [0.0; 4]), [0.2, 0.2, 0.2, 1.0]));
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! Errors
+
+use crate::ft;
+
+/// Errors from drawing, image and font operations
+#[derive(Debug)]
+pub enum AggError {
+    /// Image has a zero width or height
+    ZeroSize {
+        /// Requested width
+        width: usize,
+        /// Requested height
+        height: usize,
+    },
+    /// Images do not have the same size
+    SizeMismatch {
+        /// Expected size, (width, height)
+        expected: (usize, usize),
+        /// Found size, (width, height)
+        found: (usize, usize),
+    },
+    /// Freetype Error
+    Ft(ft::error::Error),
+    /// Image reading or writing Error
+    Image(image::ImageError),
+    /// Other Input / Output Error
+    Io(String),
+    /// Unknown pixel format name
+    UnknownFormat(String),
+    /// Buffer is too small for the image or the stride is smaller than a row
+    BufferSize {
+        /// Image width in pixels
+        width: usize,
+        /// Image height in pixels
+        height: usize,
+        /// Bytes between rows
+        stride: i64,
+        /// Length of the buffer in bytes
+        len: usize,
+    },
+}
+
+impl std::fmt::Display for AggError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            AggError::ZeroSize { width, height } =>
+                write!(f, "Cannot create pixfmt with 0 width or height: {}x{}", width, height),
+            AggError::SizeMismatch { expected, found } =>
+                write!(f, "wrong size: expected {}x{}, found {}x{}",
+                       expected.0, expected.1, found.0, found.1),
+            AggError::Ft(err) => write!(f, "freetype: {}", err),
+            AggError::Image(err) => write!(f, "image: {}", err),
+            AggError::Io(err) => write!(f, "{}", err),
+            AggError::UnknownFormat(name) => write!(f, "unknown pixel format: {}", name),
+            AggError::BufferSize { width, height, stride, len } =>
+                write!(f, "buffer of {} bytes cannot hold {}x{} image with stride {}",
+                       len, width, height, stride),
+        }
+    }
+}
+
+impl std::error::Error for AggError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AggError::Ft(err) => Some(err),
+            AggError::Image(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ft::error::Error> for AggError {
+    fn from(err: ft::error::Error) -> Self {
+        AggError::Ft(err)
+    }
+}
+impl From<image::ImageError> for AggError {
+    fn from(err: image::ImageError) -> Self {
+        AggError::Image(err)
+    }
+}
+impl From<String> for AggError {
+    fn from(err: String) -> Self {
+        AggError::Io(err)
+    }
+}
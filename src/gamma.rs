@@ -0,0 +1,347 @@
+//! Gamma Functions
+//!
+//! Gamma functions map a coverage value in the range [0,1] to a new value
+//!   in the range [0,1].  They are used by [`RasterizerScanline`] and
+//!   [`RendererOutlineAA`] to adjust anti-aliased edges, with `set_gamma()`.
+//!   Any closure `Fn(f64) -> f64` is also a gamma function, and may be
+//!   passed to `gamma()`.
+//!
+//!     use agg::{RasterizerScanline,GammaPower};
+//!     let mut ras = RasterizerScanline::new();
+//!     ras.set_gamma(GammaPower::new(2.0));
+//!     ras.gamma(|v| v.powf(2.0));
+//!
+//! [`RasterizerScanline`]: ../raster/struct.RasterizerScanline.html
+//! [`RendererOutlineAA`]: ../outline_aa/struct.RendererOutlineAA.html
+
+/// Gamma Function
+pub trait Gamma {
+    /// Apply gamma function to value `x`, in the range [0,1]
+    fn apply(&self, x: f64) -> f64;
+}
+
+impl<F> Gamma for F where F: Fn(f64) -> f64 {
+    fn apply(&self, x: f64) -> f64 {
+        self(x)
+    }
+}
+
+/// No Gamma correction, values are unchanged
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct GammaNone {}
+
+impl GammaNone {
+    /// Create new Gamma function
+    pub fn new() -> Self {
+        Self { }
+    }
+}
+impl Gamma for GammaNone {
+    fn apply(&self, x: f64) -> f64 {
+        x
+    }
+}
+
+/// Power Gamma, x^gamma
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub struct GammaPower {
+    gamma: f64,
+}
+
+impl GammaPower {
+    /// Create new Gamma function
+    pub fn new(gamma: f64) -> Self {
+        Self { gamma }
+    }
+    /// Set gamma value
+    pub fn set_gamma(&mut self, gamma: f64) {
+        self.gamma = gamma;
+    }
+    /// Get gamma value
+    pub fn gamma(&self) -> f64 {
+        self.gamma
+    }
+}
+impl Default for GammaPower {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+impl Gamma for GammaPower {
+    fn apply(&self, x: f64) -> f64 {
+        x.powf(self.gamma)
+    }
+}
+
+/// Threshold Gamma, 0 below threshold, 1 otherwise
+///
+/// Turns anti-aliasing off
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub struct GammaThreshold {
+    threshold: f64,
+}
+
+impl GammaThreshold {
+    /// Create new Gamma function
+    pub fn new(threshold: f64) -> Self {
+        Self { threshold }
+    }
+    /// Set threshold value
+    pub fn set_threshold(&mut self, threshold: f64) {
+        self.threshold = threshold;
+    }
+    /// Get threshold value
+    pub fn threshold(&self) -> f64 {
+        self.threshold
+    }
+}
+impl Default for GammaThreshold {
+    fn default() -> Self {
+        Self::new(0.5)
+    }
+}
+impl Gamma for GammaThreshold {
+    fn apply(&self, x: f64) -> f64 {
+        if x < self.threshold { 0.0 } else { 1.0 }
+    }
+}
+
+/// Linear Gamma, 0 below start, 1 above end and linear in between
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub struct GammaLinear {
+    start: f64,
+    end: f64,
+}
+
+impl GammaLinear {
+    /// Create new Gamma function
+    pub fn new(start: f64, end: f64) -> Self {
+        Self { start, end }
+    }
+    /// Set start and end values
+    pub fn set(&mut self, start: f64, end: f64) {
+        self.start = start;
+        self.end = end;
+    }
+    /// Get start value
+    pub fn start(&self) -> f64 {
+        self.start
+    }
+    /// Get end value
+    pub fn end(&self) -> f64 {
+        self.end
+    }
+}
+impl Default for GammaLinear {
+    fn default() -> Self {
+        Self::new(0.0, 1.0)
+    }
+}
+impl Gamma for GammaLinear {
+    fn apply(&self, x: f64) -> f64 {
+        if x < self.start {
+            0.0
+        } else if x > self.end {
+            1.0
+        } else {
+            (x - self.start) / (self.end - self.start)
+        }
+    }
+}
+
+/// Multiply Gamma, x * multiplier limited to 1
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub struct GammaMultiply {
+    mul: f64,
+}
+
+impl GammaMultiply {
+    /// Create new Gamma function
+    pub fn new(mul: f64) -> Self {
+        Self { mul }
+    }
+    /// Set multiplier value
+    pub fn set_value(&mut self, mul: f64) {
+        self.mul = mul;
+    }
+    /// Get multiplier value
+    pub fn value(&self) -> f64 {
+        self.mul
+    }
+}
+impl Default for GammaMultiply {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+impl Gamma for GammaMultiply {
+    fn apply(&self, x: f64) -> f64 {
+        let y = x * self.mul;
+        if y > 1.0 { 1.0 } else { y }
+    }
+}
+
+/// Spline Gamma
+///
+/// Smooth gamma curve through (0,0), (1,1) and two control points
+///   defined by (kx1,ky1) and (kx2,ky2).  Control values are in the
+///   range [0.001, 1.999], a value of 1.0 for all produces a nearly
+///   linear curve
+#[derive(Debug,Clone,PartialEq)]
+pub struct GammaSpline {
+    spline: Bspline,
+}
+
+impl GammaSpline {
+    /// Create new Gamma function
+    pub fn new(kx1: f64, ky1: f64, kx2: f64, ky2: f64) -> Self {
+        let mut g = Self { spline: Bspline::default() };
+        g.values(kx1, ky1, kx2, ky2);
+        g
+    }
+    /// Set control values
+    pub fn values(&mut self, kx1: f64, ky1: f64, kx2: f64, ky2: f64) {
+        let clamp = |v: f64| v.clamp(0.001, 1.999);
+        let (kx1, ky1) = (clamp(kx1), clamp(ky1));
+        let (kx2, ky2) = (clamp(kx2), clamp(ky2));
+        let x = [0.0, kx1 * 0.25, 1.0 - kx2 * 0.25, 1.0];
+        let y = [0.0, ky1 * 0.25, 1.0 - ky2 * 0.25, 1.0];
+        self.spline = Bspline::new(&x, &y);
+    }
+    /// Gamma table with 256 values
+    pub fn gamma(&self) -> Vec<u8> {
+        (0..256)
+            .map(|i| (self.apply(f64::from(i) / 255.0) * 255.0) as u8)
+            .collect()
+    }
+}
+impl Default for GammaSpline {
+    fn default() -> Self {
+        Self::new(1.0, 1.0, 1.0, 1.0)
+    }
+}
+impl Gamma for GammaSpline {
+    fn apply(&self, x: f64) -> f64 {
+        let x = x.clamp(0.0, 1.0);
+        self.spline.get(x).clamp(0.0, 1.0)
+    }
+}
+
+/// Interpolating Cubic Spline
+#[derive(Debug,Default,Clone,PartialEq)]
+struct Bspline {
+    x: Vec<f64>,
+    y: Vec<f64>,
+    am: Vec<f64>,
+}
+
+impl Bspline {
+    /// Create a new Spline through points (x,y)
+    ///
+    /// x values must be increasing, at least 3 points are required
+    fn new(x: &[f64], y: &[f64]) -> Self {
+        let n = x.len();
+        let mut am = vec![0.0; n];
+        if n > 2 {
+            let mut al = vec![0.0; n];
+            let mut r = vec![0.0; n];
+            let mut s = vec![0.0; n];
+            let n1 = n - 1;
+            let mut d = x[1] - x[0];
+            let mut e = (y[1] - y[0]) / d;
+            for k in 1 .. n1 {
+                let h = d;
+                d = x[k+1] - x[k];
+                let f = e;
+                e = (y[k+1] - y[k]) / d;
+                al[k] = d / (d + h);
+                r[k] = 1.0 - al[k];
+                s[k] = 6.0 * (e - f) / (h + d);
+            }
+            for k in 1 .. n1 {
+                let p = 1.0 / (r[k] * al[k-1] + 2.0);
+                al[k] *= -p;
+                s[k] = (s[k] - r[k] * s[k-1]) * p;
+            }
+            am[n1] = 0.0;
+            al[n1-1] = s[n1-1];
+            am[n1-1] = al[n1-1];
+            for k in (0 .. n1-1).rev() {
+                al[k] = al[k] * al[k+1] + s[k];
+                am[k] = al[k];
+            }
+        }
+        Self { x: x.to_vec(), y: y.to_vec(), am }
+    }
+    /// Value of the spline at x
+    fn get(&self, x: f64) -> f64 {
+        let n = self.x.len();
+        if n <= 2 {
+            return 0.0;
+        }
+        if x < self.x[0] {
+            let d = self.x[1] - self.x[0];
+            return (-d * self.am[1] / 6.0 + (self.y[1] - self.y[0]) / d) *
+                (x - self.x[0]) + self.y[0];
+        }
+        if x >= self.x[n-1] {
+            let d = self.x[n-1] - self.x[n-2];
+            return (-d * self.am[n-2] / 6.0 + (self.y[n-1] - self.y[n-2]) / d) *
+                (x - self.x[n-1]) + self.y[n-1];
+        }
+        // Binary search for x[i] <= x < x[i+1]
+        let (mut i, mut j) = (0, n - 1);
+        while j - i > 1 {
+            let k = (i + j) >> 1;
+            if x < self.x[k] {
+                j = k;
+            } else {
+                i = k;
+            }
+        }
+        let j = i + 1;
+        let d = self.x[i] - self.x[j];
+        let h = x - self.x[j];
+        let r = self.x[i] - x;
+        let p = d * d / 6.0;
+        (self.am[j] * r * r * r + self.am[i] * h * h * h) / 6.0 / d +
+            ((self.y[j] - self.am[j] * p) * r + (self.y[i] - self.am[i] * p) * h) / d
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn gamma_functions() {
+        assert_eq!(GammaNone::new().apply(0.3), 0.3);
+        assert_eq!(GammaPower::new(2.0).apply(0.5), 0.25);
+        assert_eq!(GammaThreshold::new(0.5).apply(0.49), 0.0);
+        assert_eq!(GammaThreshold::new(0.5).apply(0.5), 1.0);
+        let g = GammaLinear::new(0.25, 0.75);
+        assert_eq!(g.apply(0.1), 0.0);
+        assert_eq!(g.apply(0.5), 0.5);
+        assert_eq!(g.apply(0.9), 1.0);
+        assert_eq!(GammaMultiply::new(2.0).apply(0.25), 0.5);
+        assert_eq!(GammaMultiply::new(2.0).apply(0.75), 1.0);
+        assert_eq!((|x: f64| 1.0 - x).apply(0.25), 0.75);
+    }
+    #[test]
+    fn gamma_spline() {
+        let g = GammaSpline::default();
+        assert_eq!(g.apply(0.0), 0.0);
+        assert_eq!(g.apply(1.0), 1.0);
+        for i in 0 .. 256 {
+            let x = f64::from(i) / 255.0;
+            assert!((g.apply(x) - x).abs() < 1e-9, "{} {}", x, g.apply(x));
+        }
+        // Control points are on the curve
+        let g = GammaSpline::new(1.6, 0.4, 0.4, 1.6);
+        assert!((g.apply(0.4) - 0.1).abs() < 1e-9);
+        assert!((g.apply(0.9) - 0.6).abs() < 1e-9);
+        let table = g.gamma();
+        assert_eq!(table.len(), 256);
+        assert_eq!(table[0], 0);
+        assert_eq!(table[255], 255);
+    }
+}
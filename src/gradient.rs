@@ -0,0 +1,388 @@
+//! Gradient Functions
+//!
+//! Gradient functions map a point (x,y) to a distance used by
+//!   [`SpanGradient`] to select a color.  Coordinates and distances are in
+//!   gradient subpixel units, 1 << [`GRADIENT_SUBPIXEL_SHIFT`] per pixel,
+//!   `d` is the distance at which the gradient ends.  Any closure
+//!   `Fn(i64, i64, i64) -> i64` is also a gradient function.
+//!
+//!     use agg::{Transform,SpanGradient,GradientRadial,Rgb8};
+//!
+//!     let colors : Vec<_> = (0..=255).map(|v| Rgb8::new(v,v,v)).collect();
+//!     // Radial gradient centered at (50,50) with a radius of 40
+//!     let mtx = Transform::new_translate(-50.0, -50.0);
+//!     let span = SpanGradient::new(mtx, GradientRadial{}, &colors, 0.0, 40.0);
+//!
+//! [`SpanGradient`]: ../render/struct.SpanGradient.html
+//! [`GRADIENT_SUBPIXEL_SHIFT`]: constant.GRADIENT_SUBPIXEL_SHIFT.html
+
+use crate::Color;
+use crate::color::Rgba8;
+use crate::color::{cu8, srgb_to_rgb, rgb_to_srgb};
+
+use std::f64::consts::PI;
+
+/// Gradient subpixel shift, coordinates are scaled by 1 << 4
+pub const GRADIENT_SUBPIXEL_SHIFT : i64 = 4;
+/// Gradient subpixel scale
+pub const GRADIENT_SUBPIXEL_SCALE : i64 = 1 << GRADIENT_SUBPIXEL_SHIFT;
+
+/// Gradient Function
+pub trait GradientFunction {
+    /// Distance of point (`x`,`y`) with gradient length `d`
+    fn calculate(&self, x: i64, y: i64, d: i64) -> i64;
+}
+
+impl<F> GradientFunction for F where F: Fn(i64, i64, i64) -> i64 {
+    fn calculate(&self, x: i64, y: i64, d: i64) -> i64 {
+        self(x, y, d)
+    }
+}
+
+/// Linear gradient along x
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct GradientX {}
+impl GradientFunction for GradientX {
+    fn calculate(&self, x: i64, _: i64, _: i64) -> i64 {
+        x
+    }
+}
+
+/// Linear gradient along y
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct GradientY {}
+impl GradientFunction for GradientY {
+    fn calculate(&self, _: i64, y: i64, _: i64) -> i64 {
+        y
+    }
+}
+
+/// Radial gradient, distance from the origin
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct GradientRadial {}
+impl GradientFunction for GradientRadial {
+    fn calculate(&self, x: i64, y: i64, _: i64) -> i64 {
+        (x as f64).hypot(y as f64).round() as i64
+    }
+}
+
+/// Radial gradient with a focal point
+///
+/// The gradient is a circle of radius `r` centered at the origin with
+///   the focal point (`fx`,`fy`) inside it
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub struct GradientRadialFocus {
+    r: i64,
+    fx: i64,
+    fy: i64,
+    r2: f64,
+    fx2: f64,
+    fy2: f64,
+    mul: f64,
+}
+
+impl GradientRadialFocus {
+    /// Create new Radial Focus gradient, values are in pixels
+    pub fn new(r: f64, fx: f64, fy: f64) -> Self {
+        let mut g = Self { r: 0, fx: 0, fy: 0,
+                           r2: 0.0, fx2: 0.0, fy2: 0.0, mul: 0.0 };
+        g.init(r, fx, fy);
+        g
+    }
+    /// Set radius and focal point, values are in pixels
+    pub fn init(&mut self, r: f64, fx: f64, fy: f64) {
+        let scale = GRADIENT_SUBPIXEL_SCALE as f64;
+        self.r  = (r  * scale).round() as i64;
+        self.fx = (fx * scale).round() as i64;
+        self.fy = (fy * scale).round() as i64;
+        self.update_values();
+    }
+    /// Radius in pixels
+    pub fn radius(&self) -> f64 {
+        self.r as f64 / GRADIENT_SUBPIXEL_SCALE as f64
+    }
+    /// Focal point x in pixels
+    pub fn focus_x(&self) -> f64 {
+        self.fx as f64 / GRADIENT_SUBPIXEL_SCALE as f64
+    }
+    /// Focal point y in pixels
+    pub fn focus_y(&self) -> f64 {
+        self.fy as f64 / GRADIENT_SUBPIXEL_SCALE as f64
+    }
+    fn update_values(&mut self) {
+        self.r2  = self.r as f64 * self.r as f64;
+        self.fx2 = self.fx as f64 * self.fx as f64;
+        self.fy2 = self.fy as f64 * self.fy as f64;
+        let mut d = self.r2 - (self.fx2 + self.fy2);
+        // Focal point on the circle, move it slightly inside
+        if d == 0.0 {
+            self.fx -= self.fx.signum();
+            self.fy -= self.fy.signum();
+            self.fx2 = self.fx as f64 * self.fx as f64;
+            self.fy2 = self.fy as f64 * self.fy as f64;
+            d = self.r2 - (self.fx2 + self.fy2);
+        }
+        self.mul = self.r as f64 / d;
+    }
+}
+impl Default for GradientRadialFocus {
+    fn default() -> Self {
+        Self::new(100.0, 0.0, 0.0)
+    }
+}
+impl GradientFunction for GradientRadialFocus {
+    fn calculate(&self, x: i64, y: i64, _: i64) -> i64 {
+        let dx = (x - self.fx) as f64;
+        let dy = (y - self.fy) as f64;
+        let d2 = dx * self.fy as f64 - dy * self.fx as f64;
+        let d3 = self.r2 * (dx * dx + dy * dy) - d2 * d2;
+        ((dx * self.fx as f64 + dy * self.fy as f64 + d3.abs().sqrt())
+         * self.mul).round() as i64
+    }
+}
+
+/// Diamond gradient, maximum of |x| and |y|
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct GradientDiamond {}
+impl GradientFunction for GradientDiamond {
+    fn calculate(&self, x: i64, y: i64, _: i64) -> i64 {
+        x.abs().max(y.abs())
+    }
+}
+
+/// Conic gradient, angle about the origin
+///
+/// The angle is mirrored about the x-axis, 0 at +x and `d` at -x
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct GradientConic {}
+impl GradientFunction for GradientConic {
+    fn calculate(&self, x: i64, y: i64, d: i64) -> i64 {
+        ((y as f64).atan2(x as f64).abs() * d as f64 / PI).round() as i64
+    }
+}
+
+/// XY gradient, |x| * |y| / d
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct GradientXY {}
+impl GradientFunction for GradientXY {
+    fn calculate(&self, x: i64, y: i64, d: i64) -> i64 {
+        if d == 0 {
+            return 0;
+        }
+        x.abs() * y.abs() / d
+    }
+}
+
+/// Square root XY gradient, sqrt(|x| * |y|)
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct GradientSqrtXY {}
+impl GradientFunction for GradientSqrtXY {
+    fn calculate(&self, x: i64, y: i64, _: i64) -> i64 {
+        ((x.abs() as f64) * (y.abs() as f64)).sqrt() as i64
+    }
+}
+
+/// Color space used to interpolate between color stops
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub enum ColorInterpolation {
+    /// Interpolate linear RGB components
+    #[default]
+    Linear,
+    /// Interpolate sRGB encoded components, as in CSS and SVG
+    Srgb,
+}
+
+/// Gradient behavior outside of the range [0,1]
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub enum Spread {
+    /// Use the first or last color
+    #[default]
+    Pad,
+    /// Repeat the gradient
+    Repeat,
+    /// Repeat the gradient, mirrored every other time
+    Reflect,
+}
+
+/// Gradient Color Lookup Table
+///
+/// Colors are interpolated between stops at offsets in [0,1].  Colors
+///   before the first stop and after the last stop are constant.
+///
+///     use agg::{GradientLut,ColorInterpolation,Spread,Rgba8};
+///
+///     let mut lut = GradientLut::new();
+///     lut.add_stop(0.0, Rgba8::new(255,0,0,255));
+///     lut.add_stop(1.0, Rgba8::new(0,0,255,0));
+///     lut.interpolation(ColorInterpolation::Srgb);
+///     lut.spread(Spread::Reflect);
+///     assert_eq!(lut.get(0), Rgba8::new(255,0,0,255));
+///     assert_eq!(lut.get(255), Rgba8::new(0,0,255,0));
+///     assert_eq!(lut.get(256), Rgba8::new(0,0,255,0));
+#[derive(Debug,Clone,PartialEq)]
+pub struct GradientLut {
+    /// Color stops, offset and linear RGBA components
+    stops: Vec<(f64, [f64;4])>,
+    interpolation: ColorInterpolation,
+    spread: Spread,
+    lut: Vec<Rgba8>,
+}
+
+impl GradientLut {
+    /// Create a new, empty, Lookup Table with 256 colors
+    pub fn new() -> Self {
+        Self::with_size(256)
+    }
+    /// Create a new, empty, Lookup Table with `n` colors
+    pub fn with_size(n: usize) -> Self {
+        Self { stops: vec![],
+               interpolation: ColorInterpolation::default(),
+               spread: Spread::default(),
+               lut: vec![Rgba8::default(); n.max(1)] }
+    }
+    /// Add color stop at `offset` in [0,1]
+    ///
+    /// Stops with equal offsets are kept in order, producing a sharp
+    ///   transition
+    pub fn add_stop<C: Color>(&mut self, offset: f64, color: C) {
+        let offset = offset.clamp(0.0, 1.0);
+        let c = [color.red(), color.green(), color.blue(), color.alpha()];
+        let i = self.stops.iter().take_while(|s| s.0 <= offset).count();
+        self.stops.insert(i, (offset, c));
+        self.build_lut();
+    }
+    /// Remove all color stops
+    pub fn remove_all(&mut self) {
+        self.stops.clear();
+        self.build_lut();
+    }
+    /// Set color space used for interpolation
+    pub fn interpolation(&mut self, interp: ColorInterpolation) {
+        self.interpolation = interp;
+        self.build_lut();
+    }
+    /// Set spread mode
+    pub fn spread(&mut self, spread: Spread) {
+        self.spread = spread;
+    }
+    /// Number of colors in the table
+    pub fn len(&self) -> usize {
+        self.lut.len()
+    }
+    /// Table is empty, always false
+    pub fn is_empty(&self) -> bool {
+        self.lut.is_empty()
+    }
+    /// Colors of the table
+    pub fn colors(&self) -> &[Rgba8] {
+        &self.lut
+    }
+    /// Color at index `i`, indices outside the table use the spread mode
+    pub fn get(&self, i: i64) -> Rgba8 {
+        let n = self.lut.len() as i64;
+        let i = match self.spread {
+            Spread::Pad => i.clamp(0, n - 1),
+            Spread::Repeat => i.rem_euclid(n),
+            Spread::Reflect => {
+                let i = i.rem_euclid(2 * n);
+                if i < n { i } else { 2 * n - 1 - i }
+            },
+        };
+        self.lut[i as usize]
+    }
+    fn build_lut(&mut self) {
+        let srgb = self.interpolation == ColorInterpolation::Srgb;
+        let encode = |c: [f64;4]| if srgb {
+            [rgb_to_srgb(c[0]), rgb_to_srgb(c[1]), rgb_to_srgb(c[2]), c[3]]
+        } else {
+            c
+        };
+        let n = self.lut.len();
+        for (i, out) in self.lut.iter_mut().enumerate() {
+            let t = if n > 1 { i as f64 / (n - 1) as f64 } else { 0.0 };
+            let j = self.stops.iter().take_while(|s| s.0 <= t).count();
+            let c = if self.stops.is_empty() {
+                [0.0; 4]
+            } else if j == 0 {
+                encode(self.stops[0].1)
+            } else if j == self.stops.len() {
+                encode(self.stops[j-1].1)
+            } else {
+                let (t0, c0) = self.stops[j-1];
+                let (t1, c1) = self.stops[j];
+                let (c0, c1) = (encode(c0), encode(c1));
+                let k = (t - t0) / (t1 - t0);
+                [c0[0] + (c1[0] - c0[0]) * k,
+                 c0[1] + (c1[1] - c0[1]) * k,
+                 c0[2] + (c1[2] - c0[2]) * k,
+                 c0[3] + (c1[3] - c0[3]) * k]
+            };
+            let c = if srgb {
+                [srgb_to_rgb(c[0]), srgb_to_rgb(c[1]), srgb_to_rgb(c[2]), c[3]]
+            } else {
+                c
+            };
+            *out = Rgba8::new(cu8(c[0]), cu8(c[1]), cu8(c[2]), cu8(c[3]));
+        }
+    }
+}
+impl Default for GradientLut {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Lookup Table using the colors as is
+impl<C: Color> From<&[C]> for GradientLut {
+    fn from(colors: &[C]) -> Self {
+        let mut lut = Self::with_size(0);
+        if !colors.is_empty() {
+            lut.lut = colors.iter().map(|&c| Rgba8::from_trait(c)).collect();
+        }
+        lut
+    }
+}
+/// Lookup Table using the colors as is
+impl<C: Color> From<&Vec<C>> for GradientLut {
+    fn from(colors: &Vec<C>) -> Self {
+        Self::from(colors.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn gradient_functions() {
+        let d = 100;
+        assert_eq!(GradientX{}.calculate(3, 4, d), 3);
+        assert_eq!(GradientY{}.calculate(3, 4, d), 4);
+        assert_eq!(GradientRadial{}.calculate(3, 4, d), 5);
+        assert_eq!(GradientDiamond{}.calculate(-7, 4, d), 7);
+        assert_eq!(GradientXY{}.calculate(-20, 10, d), 2);
+        assert_eq!(GradientSqrtXY{}.calculate(-9, 4, d), 6);
+        assert_eq!(GradientConic{}.calculate(10, 0, d), 0);
+        assert_eq!(GradientConic{}.calculate(0, 10, d), 50);
+        assert_eq!(GradientConic{}.calculate(0, -10, d), 50);
+        assert_eq!(GradientConic{}.calculate(-10, 0, d), 100);
+        assert_eq!((|x: i64, y: i64, _| x + y).calculate(3, 4, d), 7);
+    }
+    #[test]
+    fn gradient_radial_focus() {
+        // Centered focus is identical to the radial gradient
+        let g = GradientRadialFocus::new(10.0, 0.0, 0.0);
+        for &(x,y) in &[(0,0), (16,0), (30,-40), (-100,60)] {
+            assert_eq!(g.calculate(x, y, 160), GradientRadial{}.calculate(x, y, 160));
+        }
+        // Focal point maps to 0, circle maps to the radius
+        let g = GradientRadialFocus::new(10.0, 5.0, 0.0);
+        assert_eq!(g.calculate(80, 0, 160), 0);
+        assert_eq!(g.calculate(160, 0, 160), 160);
+        assert_eq!(g.calculate(-160, 0, 160), 160);
+        assert_eq!(g.calculate(0, 160, 160), 160);
+        // Focal point on the circle
+        let g = GradientRadialFocus::new(10.0, 10.0, 0.0);
+        assert!(g.focus_x() < 10.0);
+        assert!(g.mul.is_finite());
+    }
+}
@@ -0,0 +1,153 @@
+//! Image Accessors
+//!
+//! Image accessors define how pixels outside of an image are sampled by the
+//!   image span generators and line patterns.  Each direction has its own
+//!   [`Wrap`] mode:
+//!
+//!   - Clip:    Pixels outside of the image are a background color
+//!   - Clone:   Pixels are repeated from the nearest edge
+//!   - Repeat:  Image is tiled
+//!   - Reflect: Image is tiled, with every other tile mirrored
+//!
+//! Images may also be used directly, pixels outside of the image are
+//!   then transparent
+//!
+//!     use agg::{Pixfmt,Rgba8,Pixel,ImageSource,ImageAccessor,Wrap};
+//!
+//!     let mut pix = Pixfmt::<Rgba8>::new(2,2);
+//!     pix.set((0,0), Rgba8::new(255,0,0,255));
+//!     let img = ImageAccessor::new(&pix, Wrap::Repeat, Wrap::Reflect);
+//!     assert_eq!(img.pixel(2, 0), Rgba8::new(255,0,0,255));
+//!     assert_eq!(img.pixel(0, -1), Rgba8::new(255,0,0,255));
+//!     assert_eq!(pix.pixel(-1, 0), Rgba8::new(0,0,0,0));
+//!
+//! [`Wrap`]: enum.Wrap.html
+
+use crate::color::Rgba8;
+use crate::pixfmt::Pixfmt;
+use crate::pixfmt::PixelData;
+use crate::Source;
+use crate::Pixel;
+
+/// Image Source with pixels at any location
+pub trait ImageSource {
+    /// Pixel at (`x`,`y`), which may be outside of the image
+    fn pixel(&self, x: i64, y: i64) -> Rgba8;
+}
+
+/// Images are transparent outside of their bounds
+impl<T, B: PixelData> ImageSource for Pixfmt<T, B> where Pixfmt<T, B>: Source + Pixel {
+    fn pixel(&self, x: i64, y: i64) -> Rgba8 {
+        if x < 0 || y < 0 || x >= self.width() as i64 || y >= self.height() as i64 {
+            return Rgba8::new(0,0,0,0);
+        }
+        self.get((x as usize, y as usize))
+    }
+}
+
+impl<T> ImageSource for &T where T: ImageSource + ?Sized {
+    fn pixel(&self, x: i64, y: i64) -> Rgba8 {
+        (**self).pixel(x, y)
+    }
+}
+
+/// Wrap mode for pixels outside of an image
+#[derive(Debug,Default,Copy,Clone,PartialEq,Eq)]
+pub enum Wrap {
+    /// Background color
+    #[default]
+    Clip,
+    /// Nearest edge pixel
+    Clone,
+    /// Tiled image
+    Repeat,
+    /// Tiled image, mirrored on every other tile
+    Reflect,
+}
+
+impl Wrap {
+    /// Map coordinate `v` into [0,`n`), None if outside and clipped
+    pub fn apply(&self, v: i64, n: i64) -> Option<i64> {
+        if n <= 0 {
+            return None;
+        }
+        match self {
+            Wrap::Clip    => if v >= 0 && v < n { Some(v) } else { None },
+            Wrap::Clone   => Some(v.clamp(0, n - 1)),
+            Wrap::Repeat  => Some(v.rem_euclid(n)),
+            Wrap::Reflect => {
+                let v = v.rem_euclid(2 * n);
+                Some(if v >= n { 2 * n - 1 - v } else { v })
+            }
+        }
+    }
+}
+
+/// Image Accessor
+///
+/// Access an image with a [`Wrap`] mode in each direction
+///
+/// [`Wrap`]: enum.Wrap.html
+#[derive(Debug)]
+pub struct ImageAccessor<'a,S> {
+    src: &'a S,
+    wrap_x: Wrap,
+    wrap_y: Wrap,
+    background: Rgba8,
+}
+
+impl<'a,S> ImageAccessor<'a,S> where S: Source + Pixel {
+    /// Create new image accessor with wrap modes for x and y
+    ///
+    /// Background color for clipped pixels is transparent
+    pub fn new(src: &'a S, wrap_x: Wrap, wrap_y: Wrap) -> Self {
+        Self { src, wrap_x, wrap_y, background: Rgba8::new(0,0,0,0) }
+    }
+    /// Create new image accessor, clipped to `background`
+    pub fn clip(src: &'a S, background: Rgba8) -> Self {
+        Self { src, wrap_x: Wrap::Clip, wrap_y: Wrap::Clip, background }
+    }
+    /// Set the background color for clipped pixels
+    pub fn background(&mut self, background: Rgba8) {
+        self.background = background;
+    }
+    /// Set the wrap modes for x and y
+    pub fn wrap(&mut self, wrap_x: Wrap, wrap_y: Wrap) {
+        self.wrap_x = wrap_x;
+        self.wrap_y = wrap_y;
+    }
+    /// Source image
+    pub fn source(&self) -> &S {
+        self.src
+    }
+}
+
+impl<S> ImageSource for ImageAccessor<'_,S> where S: Source + Pixel {
+    fn pixel(&self, x: i64, y: i64) -> Rgba8 {
+        let x = self.wrap_x.apply(x, self.src.width() as i64);
+        let y = self.wrap_y.apply(y, self.src.height() as i64);
+        match (x, y) {
+            (Some(x), Some(y)) => self.src.get((x as usize, y as usize)),
+            _ => self.background,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn wrap_modes() {
+        let n = 3;
+        let v : Vec<_> = (-4..7).map(|v| Wrap::Clip.apply(v, n)).collect();
+        assert_eq!(v, [None, None, None, None, Some(0), Some(1), Some(2),
+                       None, None, None, None]);
+        let v : Vec<_> = (-4..7).filter_map(|v| Wrap::Clone.apply(v, n)).collect();
+        assert_eq!(v, [0, 0, 0, 0, 0, 1, 2, 2, 2, 2, 2]);
+        let v : Vec<_> = (-4..7).filter_map(|v| Wrap::Repeat.apply(v, n)).collect();
+        assert_eq!(v, [2, 0, 1, 2, 0, 1, 2, 0, 1, 2, 0]);
+        let v : Vec<_> = (-4..7).filter_map(|v| Wrap::Reflect.apply(v, n)).collect();
+        assert_eq!(v, [2, 2, 1, 0, 0, 1, 2, 2, 1, 0, 0]);
+        assert_eq!(Wrap::Repeat.apply(5, 0), None);
+    }
+}
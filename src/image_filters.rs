@@ -0,0 +1,436 @@
+//! Image Filters
+//!
+//! Filters used to resample images by the image span generators.  A filter
+//!   is a weighting function of the distance from the sample point, out to
+//!   its `radius`.  Weights are precomputed into an [`ImageFilterLut`]
+//!
+//!     use agg::{ImageFilterLut,ImageFilterSpline36};
+//!
+//!     let lut = ImageFilterLut::new(&ImageFilterSpline36{});
+//!     assert_eq!(lut.diameter(), 6);
+//!
+//! [`ImageFilterLut`]: struct.ImageFilterLut.html
+
+use std::f64::consts::PI;
+
+/// Image subpixel shift, coordinates are scaled by 1 << 8
+pub const IMAGE_SUBPIXEL_SHIFT : i64 = 8;
+/// Image subpixel scale
+pub const IMAGE_SUBPIXEL_SCALE : i64 = 1 << IMAGE_SUBPIXEL_SHIFT;
+/// Image subpixel mask
+pub const IMAGE_SUBPIXEL_MASK  : i64 = IMAGE_SUBPIXEL_SCALE - 1;
+/// Image filter weight shift, weights are scaled by 1 << 14
+pub const IMAGE_FILTER_SHIFT : i64 = 14;
+/// Image filter weight scale
+pub const IMAGE_FILTER_SCALE : i64 = 1 << IMAGE_FILTER_SHIFT;
+
+/// Image Filter Function
+pub trait ImageFilter {
+    /// Radius of the filter in pixels
+    fn radius(&self) -> f64;
+    /// Weight at distance `x`, in the range [0, radius]
+    fn calc_weight(&self, x: f64) -> f64;
+}
+
+/// Image Filter Lookup Table
+///
+/// Weights of a filter at each subpixel distance, normalized so the
+///   weights of all pixels covered by the filter sum to 1
+///
+/// Weights are stored from a distance of `-diameter/2` to `diameter/2`
+///   in steps of 1/256 of a pixel.  For a sample point with a subpixel
+///   offset `f` from pixel `x`, the weight of pixel `x + start + i` is
+///   at index `256 - f + 256 * i`
+#[derive(Debug,Clone,PartialEq)]
+pub struct ImageFilterLut {
+    radius: f64,
+    diameter: usize,
+    start: i64,
+    weights: Vec<i64>,
+}
+
+impl ImageFilterLut {
+    /// Create a new, normalized, Lookup Table from filter `f`
+    pub fn new<F: ImageFilter>(f: &F) -> Self {
+        Self::new_with_normalization(f, true)
+    }
+    /// Create a new Lookup Table from filter `f`
+    pub fn new_with_normalization<F: ImageFilter>(f: &F, normalize: bool) -> Self {
+        let mut lut = Self { radius: 0.0, diameter: 0, start: 0, weights: vec![] };
+        lut.calculate(f, normalize);
+        lut
+    }
+    /// Recompute the Lookup Table from filter `f`
+    pub fn calculate<F: ImageFilter>(&mut self, f: &F, normalize: bool) {
+        self.radius = f.radius();
+        self.diameter = self.radius.ceil().max(1.0) as usize * 2;
+        self.start = -(self.diameter as i64 / 2 - 1);
+        self.weights = vec![0; (self.diameter << IMAGE_SUBPIXEL_SHIFT) + 1];
+
+        let pivot = self.diameter << (IMAGE_SUBPIXEL_SHIFT - 1);
+        for i in 0 ..= pivot {
+            let x = i as f64 / IMAGE_SUBPIXEL_SCALE as f64;
+            let y = f.calc_weight(x);
+            let w = (y * IMAGE_FILTER_SCALE as f64).round() as i64;
+            self.weights[pivot + i] = w;
+            self.weights[pivot - i] = w;
+        }
+        if normalize {
+            self.normalize();
+        }
+    }
+    /// Radius of the filter
+    pub fn radius(&self) -> f64 {
+        self.radius
+    }
+    /// Number of pixels covered by the filter in each direction
+    pub fn diameter(&self) -> usize {
+        self.diameter
+    }
+    /// Offset of the first pixel covered by the filter
+    pub fn start(&self) -> i64 {
+        self.start
+    }
+    /// Weights, scaled by 1 << 14
+    pub fn weights(&self) -> &[i64] {
+        &self.weights
+    }
+    /// Weight of pixel `i` for subpixel offset `f`
+    #[inline]
+    pub(crate) fn weight(&self, f: i64, i: i64) -> i64 {
+        self.weights[(IMAGE_SUBPIXEL_SCALE - f + i * IMAGE_SUBPIXEL_SCALE) as usize]
+    }
+    /// Normalize weights so the sum at each subpixel offset is 1
+    ///
+    /// Rounding errors are distributed from the center outward
+    fn normalize(&mut self) {
+        let scale = IMAGE_SUBPIXEL_SCALE as usize;
+        let d = self.diameter;
+        let mut flip = 1;
+        for f in 0 .. scale {
+            let k0 = scale - f;
+            loop {
+                let sum : i64 = (0..d).map(|j| self.weights[k0 + j * scale]).sum();
+                if sum == IMAGE_FILTER_SCALE || sum == 0 {
+                    break;
+                }
+                let k = IMAGE_FILTER_SCALE as f64 / sum as f64;
+                let mut sum = 0;
+                for j in 0 .. d {
+                    let w = &mut self.weights[k0 + j * scale];
+                    *w = (*w as f64 * k).round() as i64;
+                    sum += *w;
+                }
+                sum -= IMAGE_FILTER_SCALE;
+                let inc = if sum > 0 { -1 } else { 1 };
+                for j in 0 .. d {
+                    if sum == 0 {
+                        break;
+                    }
+                    flip ^= 1;
+                    let idx = if flip != 0 { d / 2 + j / 2 } else { d / 2 - j / 2 };
+                    let idx = idx.min(d - 1);
+                    let w = &mut self.weights[k0 + idx * scale];
+                    if *w < IMAGE_FILTER_SCALE {
+                        *w += inc;
+                        sum += inc;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Bilinear filter
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct ImageFilterBilinear {}
+impl ImageFilter for ImageFilterBilinear {
+    fn radius(&self) -> f64 { 1.0 }
+    fn calc_weight(&self, x: f64) -> f64 {
+        1.0 - x
+    }
+}
+
+/// Hanning filter
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct ImageFilterHanning {}
+impl ImageFilter for ImageFilterHanning {
+    fn radius(&self) -> f64 { 1.0 }
+    fn calc_weight(&self, x: f64) -> f64 {
+        0.5 + 0.5 * (PI * x).cos()
+    }
+}
+
+/// Hamming filter
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct ImageFilterHamming {}
+impl ImageFilter for ImageFilterHamming {
+    fn radius(&self) -> f64 { 1.0 }
+    fn calc_weight(&self, x: f64) -> f64 {
+        0.54 + 0.46 * (PI * x).cos()
+    }
+}
+
+/// Hermite filter
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct ImageFilterHermite {}
+impl ImageFilter for ImageFilterHermite {
+    fn radius(&self) -> f64 { 1.0 }
+    fn calc_weight(&self, x: f64) -> f64 {
+        (2.0 * x - 3.0) * x * x + 1.0
+    }
+}
+
+/// Quadric filter
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct ImageFilterQuadric {}
+impl ImageFilter for ImageFilterQuadric {
+    fn radius(&self) -> f64 { 1.5 }
+    fn calc_weight(&self, x: f64) -> f64 {
+        if x < 0.5 {
+            0.75 - x * x
+        } else if x < 1.5 {
+            let t = x - 1.5;
+            0.5 * t * t
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Bicubic filter
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct ImageFilterBicubic {}
+impl ImageFilter for ImageFilterBicubic {
+    fn radius(&self) -> f64 { 2.0 }
+    fn calc_weight(&self, x: f64) -> f64 {
+        let pow3 = |x: f64| if x <= 0.0 { 0.0 } else { x * x * x };
+        (1.0/6.0) * (pow3(x + 2.0) - 4.0 * pow3(x + 1.0) + 6.0 * pow3(x) - 4.0 * pow3(x - 1.0))
+    }
+}
+
+/// Catmull-Rom filter
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct ImageFilterCatrom {}
+impl ImageFilter for ImageFilterCatrom {
+    fn radius(&self) -> f64 { 2.0 }
+    fn calc_weight(&self, x: f64) -> f64 {
+        if x < 1.0 {
+            0.5 * (2.0 + x * x * (-5.0 + x * 3.0))
+        } else if x < 2.0 {
+            0.5 * (4.0 + x * (-8.0 + x * (5.0 - x)))
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Mitchell-Netravali filter with parameters `b` and `c`
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub struct ImageFilterMitchell {
+    p0: f64, p2: f64, p3: f64,
+    q0: f64, q1: f64, q2: f64, q3: f64,
+}
+impl ImageFilterMitchell {
+    /// Create new Mitchell filter
+    pub fn new(b: f64, c: f64) -> Self {
+        Self {
+            p0: (6.0 - 2.0 * b) / 6.0,
+            p2: (-18.0 + 12.0 * b + 6.0 * c) / 6.0,
+            p3: (12.0 - 9.0 * b - 6.0 * c) / 6.0,
+            q0: (8.0 * b + 24.0 * c) / 6.0,
+            q1: (-12.0 * b - 48.0 * c) / 6.0,
+            q2: (6.0 * b + 30.0 * c) / 6.0,
+            q3: (-b - 6.0 * c) / 6.0,
+        }
+    }
+}
+impl Default for ImageFilterMitchell {
+    /// Mitchell filter with b = c = 1/3
+    fn default() -> Self {
+        Self::new(1.0/3.0, 1.0/3.0)
+    }
+}
+impl ImageFilter for ImageFilterMitchell {
+    fn radius(&self) -> f64 { 2.0 }
+    fn calc_weight(&self, x: f64) -> f64 {
+        if x < 1.0 {
+            self.p0 + x * x * (self.p2 + x * self.p3)
+        } else if x < 2.0 {
+            self.q0 + x * (self.q1 + x * (self.q2 + x * self.q3))
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Spline16 filter
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct ImageFilterSpline16 {}
+impl ImageFilter for ImageFilterSpline16 {
+    fn radius(&self) -> f64 { 2.0 }
+    fn calc_weight(&self, x: f64) -> f64 {
+        if x < 1.0 {
+            ((x - 9.0/5.0) * x - 1.0/5.0) * x + 1.0
+        } else {
+            let x = x - 1.0;
+            ((-1.0/3.0 * x + 4.0/5.0) * x - 7.0/15.0) * x
+        }
+    }
+}
+
+/// Spline36 filter
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct ImageFilterSpline36 {}
+impl ImageFilter for ImageFilterSpline36 {
+    fn radius(&self) -> f64 { 3.0 }
+    fn calc_weight(&self, x: f64) -> f64 {
+        if x < 1.0 {
+            ((13.0/11.0 * x - 453.0/209.0) * x - 3.0/209.0) * x + 1.0
+        } else if x < 2.0 {
+            let x = x - 1.0;
+            ((-6.0/11.0 * x + 270.0/209.0) * x - 156.0/209.0) * x
+        } else {
+            let x = x - 2.0;
+            ((1.0/11.0 * x - 45.0/209.0) * x + 26.0/209.0) * x
+        }
+    }
+}
+
+/// Gaussian filter
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+pub struct ImageFilterGaussian {}
+impl ImageFilter for ImageFilterGaussian {
+    fn radius(&self) -> f64 { 2.0 }
+    fn calc_weight(&self, x: f64) -> f64 {
+        (-2.0 * x * x).exp() * (2.0 / PI).sqrt()
+    }
+}
+
+/// Sinc filter with radius `r`, at least 2
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub struct ImageFilterSinc {
+    radius: f64,
+}
+impl ImageFilterSinc {
+    /// Create new Sinc filter
+    pub fn new(r: f64) -> Self {
+        Self { radius: r.max(2.0) }
+    }
+}
+impl ImageFilter for ImageFilterSinc {
+    fn radius(&self) -> f64 { self.radius }
+    fn calc_weight(&self, x: f64) -> f64 {
+        if x == 0.0 {
+            return 1.0;
+        }
+        let x = x * PI;
+        x.sin() / x
+    }
+}
+
+/// Lanczos filter with radius `r`, at least 2
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub struct ImageFilterLanczos {
+    radius: f64,
+}
+impl ImageFilterLanczos {
+    /// Create new Lanczos filter
+    pub fn new(r: f64) -> Self {
+        Self { radius: r.max(2.0) }
+    }
+}
+impl ImageFilter for ImageFilterLanczos {
+    fn radius(&self) -> f64 { self.radius }
+    fn calc_weight(&self, x: f64) -> f64 {
+        if x == 0.0 {
+            return 1.0;
+        }
+        if x > self.radius {
+            return 0.0;
+        }
+        let x = x * PI;
+        let xr = x / self.radius;
+        (x.sin() / x) * (xr.sin() / xr)
+    }
+}
+
+/// Blackman filter with radius `r`, at least 2
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub struct ImageFilterBlackman {
+    radius: f64,
+}
+impl ImageFilterBlackman {
+    /// Create new Blackman filter
+    pub fn new(r: f64) -> Self {
+        Self { radius: r.max(2.0) }
+    }
+}
+impl ImageFilter for ImageFilterBlackman {
+    fn radius(&self) -> f64 { self.radius }
+    fn calc_weight(&self, x: f64) -> f64 {
+        if x == 0.0 {
+            return 1.0;
+        }
+        if x > self.radius {
+            return 0.0;
+        }
+        let x = x * PI;
+        let xr = x / self.radius;
+        (x.sin() / x) * (0.42 + 0.5 * xr.cos() + 0.08 * (2.0 * xr).cos())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn check_normalized(lut: &ImageFilterLut) {
+        for f in 0 .. IMAGE_SUBPIXEL_SCALE {
+            let sum : i64 = (0 .. lut.diameter() as i64)
+                .map(|i| lut.weight(f, i))
+                .sum();
+            assert_eq!(sum, IMAGE_FILTER_SCALE, "offset {}", f);
+        }
+    }
+    #[test]
+    fn filter_lut() {
+        let lut = ImageFilterLut::new(&ImageFilterBilinear{});
+        assert_eq!(lut.diameter(), 2);
+        assert_eq!(lut.start(), 0);
+        assert_eq!(lut.weights().len(), 513);
+        // Pixel center has all the weight
+        assert_eq!(lut.weight(0, 0), IMAGE_FILTER_SCALE);
+        assert_eq!(lut.weight(0, 1), 0);
+        assert_eq!(lut.weight(128, 0), IMAGE_FILTER_SCALE / 2);
+        assert_eq!(lut.weight(128, 1), IMAGE_FILTER_SCALE / 2);
+        assert_eq!(lut.weight(64, 1), IMAGE_FILTER_SCALE / 4);
+        check_normalized(&lut);
+
+        let lut = ImageFilterLut::new(&ImageFilterSpline36{});
+        assert_eq!(lut.diameter(), 6);
+        assert_eq!(lut.start(), -2);
+        check_normalized(&lut);
+        check_normalized(&ImageFilterLut::new(&ImageFilterBicubic{}));
+        check_normalized(&ImageFilterLut::new(&ImageFilterSpline16{}));
+        check_normalized(&ImageFilterLut::new(&ImageFilterGaussian{}));
+        check_normalized(&ImageFilterLut::new(&ImageFilterMitchell::default()));
+        check_normalized(&ImageFilterLut::new(&ImageFilterLanczos::new(3.0)));
+        check_normalized(&ImageFilterLut::new(&ImageFilterQuadric{}));
+    }
+    #[test]
+    fn filter_weights() {
+        // Interpolating filters are 1 at 0 and 0 at each integer
+        for f in &[&ImageFilterSpline16{} as &dyn ImageFilter,
+                   &ImageFilterSpline36{},
+                   &ImageFilterCatrom{},
+                   &ImageFilterLanczos::new(3.0),
+                   &ImageFilterSinc::new(3.0)] {
+            assert!((f.calc_weight(0.0) - 1.0).abs() < 1e-12);
+            for x in 1 .. f.radius() as i64 {
+                assert!(f.calc_weight(x as f64).abs() < 1e-12);
+            }
+        }
+        assert!(ImageFilterBicubic{}.calc_weight(2.0).abs() < 1e-12);
+        assert!(ImageFilterMitchell::default().calc_weight(2.0).abs() < 1e-12);
+    }
+}
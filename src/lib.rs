@@ -134,6 +134,7 @@ pub mod stroke;
 pub mod transform;
 pub mod color;
 pub mod pixfmt;
+pub mod pixfmt_dyn;
 pub mod base;
 pub mod clip;
 pub mod raster;
@@ -144,6 +145,19 @@ pub mod text;
 pub mod outline;
 pub mod outline_aa;
 pub mod line_interp;
+pub mod error;
+pub mod gamma;
+pub mod span;
+pub mod gradient;
+pub mod span_gouraud;
+pub mod image_filters;
+pub mod image_accessors;
+pub mod span_image;
+pub mod span_pattern;
+pub mod blur;
+pub mod shadow;
+pub mod comp_op;
+pub mod color_conv;
 
 pub mod math;
 pub(crate) mod scan;
@@ -164,6 +178,8 @@ pub use crate::color::*;
 #[doc(hidden)]
 pub use crate::pixfmt::*;
 #[doc(hidden)]
+pub use crate::pixfmt_dyn::*;
+#[doc(hidden)]
 pub use crate::base::*;
 #[doc(hidden)]
 pub use crate::clip::*;
@@ -181,12 +197,54 @@ pub use crate::line_interp::*;
 pub use crate::outline::*;
 #[doc(hidden)]
 pub use crate::outline_aa::*;
+#[doc(hidden)]
+pub use crate::error::*;
+#[doc(hidden)]
+pub use crate::gamma::*;
+#[doc(hidden)]
+pub use crate::span::*;
+#[doc(hidden)]
+pub use crate::gradient::*;
+#[doc(hidden)]
+pub use crate::span_gouraud::*;
+#[doc(hidden)]
+pub use crate::image_filters::*;
+#[doc(hidden)]
+pub use crate::image_accessors::*;
+#[doc(hidden)]
+pub use crate::span_image::*;
+#[doc(hidden)]
+pub use crate::span_pattern::*;
+#[doc(hidden)]
+pub use crate::blur::*;
+#[doc(hidden)]
+pub use crate::shadow::*;
+#[doc(hidden)]
+pub use crate::comp_op::*;
+#[doc(hidden)]
+pub use crate::color_conv::*;
 
 const POLY_SUBPIXEL_SHIFT : i64 = 8;
 const POLY_SUBPIXEL_SCALE : i64 = 1<<POLY_SUBPIXEL_SHIFT;
 const POLY_SUBPIXEL_MASK  : i64 = POLY_SUBPIXEL_SCALE - 1;
 const POLY_MR_SUBPIXEL_SHIFT : i64 = 4;
 const MAX_HALF_WIDTH : usize = 64;
+/// Maximum absolute coordinate at subpixel scale for the outline renderers
+const LINE_MAX_COORD : i64 = (1 << 28) - 1;
+
+/// Convert a value to subpixel scale for the outline renderers
+///
+/// Values are saturated to +/- LINE_MAX_COORD, NaN is converted to 0
+pub(crate) fn line_coord_sat(c: f64) -> i64 {
+    let v = (c * POLY_SUBPIXEL_SCALE as f64).round();
+    if v < -LINE_MAX_COORD as f64 {
+        -LINE_MAX_COORD
+    } else if v > LINE_MAX_COORD as f64 {
+        LINE_MAX_COORD
+    } else {
+        v as i64
+    }
+}
 
 
 /// Source of vertex points
@@ -231,7 +289,23 @@ pub trait Render {
     /// Set the Color of the Renderer
     fn color<C: Color>(&mut self, color: C);
     /// Prepare the Renderer
-    fn prepare(&self) { }
+    fn prepare(&mut self) { }
+}
+
+/// Generate colors for a span (horizontal run of pixels)
+///
+/// Used by [`RenderingScanlineAA`] and [`render_scanlines_aa`] to fill shapes
+///   with gradients, images, patterns or any user-written shader
+///
+/// [`RenderingScanlineAA`]: render/struct.RenderingScanlineAA.html
+/// [`render_scanlines_aa`]: render/fn.render_scanlines_aa.html
+pub trait SpanGenerator {
+    /// Color type of the generated span
+    type Output: Color + Default;
+    /// Prepare the generator, called once before rendering the scanlines
+    fn prepare(&mut self) { }
+    /// Generate colors for `span.len()` pixels starting at (`x`,`y`)
+    fn generate(&mut self, span: &mut [Self::Output], x: i64, y: i64);
 }
 /*
 /// Rasterize lines, path, and other things to scanlines
@@ -259,8 +333,20 @@ pub trait Source {
 /// Drawing and pixel related routines
 pub trait Pixel {
     fn cover_mask() -> u64;
-    fn bpp() -> usize;
+    /// Bytes per pixel
+    fn bpp(&self) -> usize;
+    /// Underlying storage, as is
+    ///
+    /// Includes any padding at the end of rows, has rows in storage order
+    ///   (bottom-up for a negative stride) and, for a view, spans the
+    ///   parent image.  Use [`packed`] for only the pixels of the image
+    ///
+    /// [`packed`]: #tymethod.packed
     fn as_bytes(&self) -> &[u8];
+    /// Pixels of the image, with rows top-down and without padding
+    ///
+    /// Data is only copied if it is not already stored this way
+    fn packed(&self) -> std::borrow::Cow<'_, [u8]>;
     fn to_file<P: AsRef<std::path::Path>>(&self, filename: P) -> Result<(),image::ImageError>;
     fn width(&self) -> usize;
     fn height(&self) -> usize;
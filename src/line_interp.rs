@@ -696,6 +696,7 @@ impl AA3 {
                 npix += 1;
             }
             dy += 1;
+            dist = self.li.dist[dy] + s1;
         }
         ren.blend_solid_vspan(self.li.x,
                               self.li.y - dy as i64 + 1,
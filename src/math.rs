@@ -37,12 +37,54 @@ pub fn multiply_u8(a: u8, b: u8) -> u8 {
 }
 
 
+/// Interpolate a 16-bit value between two end points using fixed point math
+///
+/// 16-bit version of [`lerp_u8`](fn.lerp_u8.html)
+pub fn lerp_u16(p: u16, q: u16, a: u16) -> u16 {
+    let base_shift = 16;
+    let base_msb = 1 << (base_shift - 1);
+    let v = if p > q { 1 } else { 0 };
+    let (q,p,a) = (i64::from(q), i64::from(p), i64::from(a));
+    let t0 : i64 = (q - p) * a + base_msb - v;
+    let t1 : i64 = ((t0>>base_shift) + t0) >> base_shift;
+    (p + t1) as u16
+}
+
+/// Interpolate a 16-bit value between two end points pre-calculated by alpha
+///
+/// p + q - (p*a)
+pub fn prelerp_u16(p: u16, q: u16, a: u16) -> u16 {
+    p.wrapping_add(q).wrapping_sub(multiply_u16(p,a))
+}
+
+/// Multiply two u16 values using fixed point math
+///
+/// 16-bit version of [`multiply_u8`](fn.multiply_u8.html)
+pub fn multiply_u16(a: u16, b: u16) -> u16 {
+    let base_shift = 16;
+    let base_msb = 1 << (base_shift - 1);
+    let (a,b) = (u64::from(a), u64::from(b));
+    let t : u64  = a * b + base_msb;
+    let tt : u64 = ((t >> base_shift) + t) >> base_shift;
+    tt as u16
+}
+
+/// Expand an 8-bit cover, in the range [0,255], to 16 bits
+///
+/// Covers arrive from the rasterizers with gamma already applied;
+///   255 maps exactly to 65535
+pub fn cover_u16(cover: u64) -> u16 {
+    cover.min(255) as u16 * 257
+}
+
+
 
 #[cfg(test)]
 mod tests {
     use super::multiply_u8;
     use super::lerp_u8;
     use super::prelerp_u8;
+    use super::{lerp_u16, prelerp_u16, multiply_u16, cover_u16};
 
     fn mu864(i: u8, j: u8) -> u8 {
         let i = i as f64 / 255.0;
@@ -103,4 +145,47 @@ mod tests {
             }
         }
     }
+    /// Sample values across the 16-bit range
+    fn samples16() -> Vec<u16> {
+        (0 ..= 65535).step_by(257 * 13).chain(vec![1, 2, 254, 255, 256, 32767, 32768, 65534, 65535]).collect()
+    }
+    #[test]
+    fn lerp_u16_test() {
+        for &p in &samples16() {
+            for &q in &samples16() {
+                for &a in &samples16() {
+                    let (pf, qf, af) = (f64::from(p), f64::from(q), f64::from(a) / 65535.0);
+                    let v = (af * (qf - pf) + pf).round();
+                    let d = (f64::from(lerp_u16(p,q,a)) - v).abs();
+                    assert!(d <= 1.0, "lerp({},{},{}) = {}", p, q, a, v);
+                }
+                assert_eq!(lerp_u16(p,q,0), p);
+                assert_eq!(lerp_u16(p,q,65535), q);
+            }
+        }
+    }
+    #[test]
+    fn prelerp_u16_test() {
+        for &p in &samples16() {
+            for &a in &samples16() {
+                // Source over with a premultiplied source of alpha a
+                let (pf, af) = (f64::from(p), f64::from(a) / 65535.0);
+                let v = (pf + f64::from(a) - af * pf).round();
+                let d = (f64::from(prelerp_u16(p,a,a)) - v).abs();
+                assert!(d <= 1.0, "prelerp({},{},{}) = {}", p, a, a, v);
+            }
+        }
+    }
+    #[test]
+    fn multiply_u16_test() {
+        for &a in &samples16() {
+            for &b in &samples16() {
+                let v = (f64::from(a) * f64::from(b) / 65535.0).round() as u16;
+                assert_eq!(multiply_u16(a,b), v, "{} * {} = {}", a, b, v);
+            }
+        }
+        assert_eq!(cover_u16(0), 0);
+        assert_eq!(cover_u16(128), 32896);
+        assert_eq!(cover_u16(255), 65535);
+    }
 }
@@ -30,7 +30,7 @@ use crate::color::Rgba8;
 use crate::render::BresehamInterpolator;
 use crate::base::RenderingBase;
 use crate::Color;
-use crate::POLY_SUBPIXEL_SCALE;
+use crate::line_coord_sat;
 
 #[derive(Debug,Copy,Clone,PartialEq,Default)]
 pub(crate) struct Subpixel(i64);
@@ -55,27 +55,31 @@ impl From<Subpixel> for i64 {
 /// Rasterizer for Outlined Shapes
 ///
 /// The rendering is directly attached and drawing is done immediately.
+///
+/// Vertices with a NaN or infinite coordinate are skipped.  If a move_to_d()
+///   is skipped, the next valid line_to_d() starts the new sub-path.
 pub struct RasterizerOutline<'a,T> where T: Pixel {
     ren: &'a mut RendererPrimatives<'a,T>,
     start_x: Subpixel,
     start_y: Subpixel,
     vertices: usize,
+    move_pending: bool,
 }
 impl<'a,T> RasterizerOutline<'a,T> where T: Pixel {
     /// Create a new RasterizerOutline with a Renderer
     pub fn with_primative(ren: &'a mut RendererPrimatives<'a,T>) -> Self {
         Self { start_x: Subpixel::from(0),
                start_y: Subpixel::from(0),
-               vertices: 0, ren}
+               vertices: 0, move_pending: false, ren}
     }
-    /// Add a path and render
+    /// Add a path and render; a PathCommand::Stop ends the path
     pub fn add_path<VS: VertexSource>(&mut self, path: &VS) {
         for v in path.xconvert().iter() {
             match v.cmd {
                 PathCommand::MoveTo => self.move_to_d(v.x, v.y),
                 PathCommand::LineTo => self.line_to_d(v.x, v.y),
                 PathCommand::Close => self.close(),
-                PathCommand::Stop => unimplemented!("stop encountered"),
+                PathCommand::Stop => break,
             }
         }
     }
@@ -89,12 +93,24 @@ impl<'a,T> RasterizerOutline<'a,T> where T: Pixel {
     }
     /// Move to position (`x`,`y`)
     pub fn move_to_d(&mut self, x: f64, y: f64) {
+        if ! x.is_finite() || ! y.is_finite() {
+            self.move_pending = true;
+            return;
+        }
+        self.move_pending = false;
         let x = self.ren.coord(x);
         let y = self.ren.coord(y);
         self.move_to( x, y );
     }
     /// Draw a line from the current position to position (`x`,`y`)
     pub fn line_to_d(&mut self, x: f64, y: f64) {
+        if ! x.is_finite() || ! y.is_finite() {
+            return;
+        }
+        if self.move_pending {
+            self.move_to_d(x, y);
+            return;
+        }
         let x = self.ren.coord(x);
         let y = self.ren.coord(y);
         self.line_to( x, y );
@@ -141,7 +157,7 @@ impl<'a,T> RendererPrimatives<'a,T> where T: Pixel {
         self.fill_color = Rgba8::from_trait(fill_color);
     }
     pub(crate) fn coord(&self, c: f64) -> Subpixel {
-        Subpixel::from( (c * POLY_SUBPIXEL_SCALE as f64).round() as i64 )
+        Subpixel::from( line_coord_sat(c) )
     }
     pub(crate) fn move_to(&mut self, x: Subpixel, y: Subpixel) {
         self.x = x;
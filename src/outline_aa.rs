@@ -49,6 +49,8 @@ use crate::Color;
 use crate::RenderOutline;
 use crate::render::LINE_MAX_LENGTH;
 use crate::MAX_HALF_WIDTH;
+use crate::gamma::Gamma;
+use crate::line_coord_sat;
 use crate::POLY_SUBPIXEL_SHIFT;
 use crate::POLY_SUBPIXEL_MASK;
 
@@ -65,6 +67,7 @@ pub struct RasterizerOutlineAA<'a,T> where T: DrawOutline {
     vertices: Vec<Vertex<i64>>,
     round_cap: bool,
     line_join: LineJoin,
+    move_pending: bool,
 }
 
 impl<'a,T> RasterizerOutlineAA<'a, T> where T: DrawOutline {
@@ -76,35 +79,52 @@ impl<'a,T> RasterizerOutlineAA<'a, T> where T: DrawOutline {
             LineJoin::Round
         };
         Self { ren, start_x: 0, start_y: 0, vertices: vec![],
-               round_cap: false, line_join }
+               round_cap: false, line_join, move_pending: false }
     }
     /// Set Rounded End Caps
     pub fn round_cap(&mut self, on: bool) {
         self.round_cap = on;
     }
-    /// Add and Render a path
+    /// Add and Render a path; a PathCommand::Stop ends the path
     pub fn add_path<VS: VertexSource>(&mut self, path: &VS) {
         for v in path.xconvert().iter() {
             match v.cmd {
                 PathCommand::MoveTo => self.move_to_d(v.x, v.y),
                 PathCommand::LineTo => self.line_to_d(v.x, v.y),
                 PathCommand::Close => self.close_path(),
-                PathCommand::Stop => unimplemented!("stop encountered"),
+                PathCommand::Stop => break,
             }
         }
         self.render(false);
     }
     fn conv(&self, v: f64) -> i64 {
-        (v * POLY_SUBPIXEL_SCALE as f64).round() as i64
+        line_coord_sat(v)
     }
     /// Move the current point to (`x`,`y`)
+    ///
+    /// If x or y is NaN or infinite, the point is skipped and the next
+    ///   valid line_to_d() point is used as the current point
     pub fn move_to_d(&mut self, x: f64, y: f64) {
+        if ! x.is_finite() || ! y.is_finite() {
+            self.move_pending = true;
+            return;
+        }
+        self.move_pending = false;
         let x = self.conv(x);
         let y = self.conv(y);
         self.move_to( x, y );
     }
     /// Draw a line from the current point to (`x`,`y`)
+    ///
+    /// If x or y is NaN or infinite, the point is skipped
     pub fn line_to_d(&mut self, x: f64, y: f64) {
+        if ! x.is_finite() || ! y.is_finite() {
+            return;
+        }
+        if self.move_pending {
+            self.move_to_d(x, y);
+            return;
+        }
         let x = self.conv(x);
         let y = self.conv(y);
         self.line_to( x, y );
@@ -431,6 +451,22 @@ impl<'a,T> RendererOutlineAA<'a,T> where T: Pixel {
     pub fn width(&mut self, width: f64) {
         self.profile.width(width);
     }
+    /// Set the gamma function of the line profile
+    ///
+    /// See [`set_gamma`](#method.set_gamma) for [`Gamma`] functions
+    ///
+    /// [`Gamma`]: ../gamma/trait.Gamma.html
+    pub fn gamma<F>(&mut self, gfunc: F) where F: Fn(f64) -> f64 {
+        self.profile.gamma(gfunc);
+    }
+    /// Set the gamma function of the line profile from a [`Gamma`]
+    ///   function, e.g. [`GammaPower`]
+    ///
+    /// [`Gamma`]: ../gamma/trait.Gamma.html
+    /// [`GammaPower`]: ../gamma/struct.GammaPower.html
+    pub fn set_gamma<G: Gamma>(&mut self, gfunc: G) {
+        self.profile.gamma(gfunc);
+    }
     /// Set minimum with of the line
     ///
     /// Use [`width`](#method.width) for this to take effect
@@ -815,6 +851,7 @@ impl<T> DrawOutline for RendererOutlineAA<'_, T> where T: Pixel {
 #[derive(Debug,Default)]
 /// Profile of a Line
 struct LineProfileAA {
+    width: f64,
     min_width: f64,
     smoother_width: f64,
     subpixel_width: i64,
@@ -828,7 +865,7 @@ impl LineProfileAA {
     /// Width is initialized to 0.0
     pub fn new() -> Self {
         let gamma : Vec<_> = (0..POLY_SUBPIXEL_SCALE).map(|x| x as u8).collect();
-        let mut s = Self { min_width: 1.0,
+        let mut s = Self { width: 0.0, min_width: 1.0,
                            smoother_width: 1.0,
                            subpixel_width: 0,
                            profile: vec![], gamma };
@@ -847,6 +884,19 @@ impl LineProfileAA {
     pub fn smoother_width(&mut self, width: f64) {
         self.smoother_width = width;
     }
+    /// Set the gamma function
+    ///
+    /// Values are set as gamma = gfunc( v / mask ) * mask, where v = 0 to 255,
+    ///   and the line profile is rebuilt with the current width
+    pub fn gamma<G: Gamma>(&mut self, gfunc: G) {
+        let aa_mask = (POLY_SUBPIXEL_SCALE - 1) as f64;
+        self.gamma = (0..POLY_SUBPIXEL_SCALE)
+            .map(|i| gfunc.apply(i as f64 / aa_mask))
+            .map(|v| (v * aa_mask).round().clamp(0.0, aa_mask) as u8)
+            .collect();
+        let w = self.width;
+        self.width(w);
+    }
     /// Set width
     ///
     /// Negative widths are set to 0.0
@@ -857,6 +907,7 @@ impl LineProfileAA {
     ///
     /// The line profile is then constructed and saved to `profile`
     pub fn width(&mut self, w: f64) {
+        self.width = w;
         let mut w = w;
         if w < 0.0 {
             w = 0.0;
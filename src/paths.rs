@@ -156,6 +156,41 @@ pub fn split(path: &[Vertex<f64>]) -> Vec<(usize, usize)> {
     pairs
 }
 
+/// Remove vertices with a NaN or infinite coordinate
+///
+/// If a MoveTo is removed, the next valid LineTo becomes a MoveTo and any
+///   Close of the removed sub-path is dropped
+pub(crate) fn finite_vertices(path: &[Vertex<f64>]) -> Vec<Vertex<f64>> {
+    let mut out = Vec::with_capacity(path.len());
+    let mut move_pending = false;
+    for v in path {
+        let finite = v.x.is_finite() && v.y.is_finite();
+        match v.cmd {
+            PathCommand::MoveTo => {
+                move_pending = ! finite;
+                if finite {
+                    out.push(*v);
+                }
+            },
+            PathCommand::LineTo => {
+                if finite && move_pending {
+                    out.push(Vertex::move_to(v.x, v.y));
+                    move_pending = false;
+                } else if finite {
+                    out.push(*v);
+                }
+            },
+            PathCommand::Close => {
+                if ! move_pending {
+                    out.push(*v);
+                }
+            },
+            PathCommand::Stop => out.push(*v),
+        }
+    }
+    out
+}
+
 fn arrange_orientations(path: &mut Path, dir: PathOrientation) {
     let pairs = split(&path.vertices);
     for (s,e) in pairs {
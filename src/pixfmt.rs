@@ -7,6 +7,7 @@ use crate::math::*;
 use crate::Color;
 use crate::Source;
 use crate::Pixel;
+use crate::AggError;
 
 use std::marker::PhantomData;
 
@@ -14,23 +15,143 @@ use std::path::Path;
 
 /// Pixel Format Wrapper around raw pixel component data
 ///
+/// Pixel data is owned by default, see [`attach`](#method.attach) for
+///   rendering into an external buffer
 #[derive(Debug)]
-pub struct Pixfmt<T> {
-    rbuf: RenderingBuffer,
+pub struct Pixfmt<T, B = Vec<u8>> {
+    rbuf: RenderingBuffer<B>,
     phantom: PhantomData<T>,
 }
 
-impl<T> Pixfmt<T> where Pixfmt<T>: Pixel {
+/// Storage for the pixel data of a [`Pixfmt`]
+///
+/// Implemented for anything viewable as a mutable slice of bytes, including
+///   `Vec<u8>` and `&mut [u8]`
+pub trait PixelData: AsRef<[u8]> + AsMut<[u8]> {}
+
+impl<B> PixelData for B where B: AsRef<[u8]> + AsMut<[u8]> {}
+
+/// Pixel Formats with a fixed number of bytes per pixel
+///
+/// Used to allocate or attach pixel data, see [`Pixel::bpp`] for the size
+///   of an existing image
+///
+/// [`Pixel::bpp`]: ../trait.Pixel.html#tymethod.bpp
+pub trait PixelSize {
+    /// Bytes per pixel
+    const BPP: usize;
+}
+
+impl<T> Pixfmt<T> where Pixfmt<T>: Pixel + PixelSize {
     /// Create new Pixel Format of width * height * bpp
     ///
     /// Allocates memory of width * height * bpp
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` or `height` is 0, see [`try_new`](#method.try_new)
     pub fn new(width: usize, height: usize) -> Self {
+        match Self::try_new(width, height) {
+            Ok(pix) => pix,
+            Err(err) => panic!("{}", err),
+        }
+    }
+    /// Create new Pixel Format of width * height * bpp
+    ///
+    /// Returns an error if `width` or `height` is 0
+    ///
+    ///     use agg::{Pixfmt,Rgb8};
+    ///
+    ///     assert!(Pixfmt::<Rgb8>::try_new(0, 10).is_err());
+    ///     assert!(Pixfmt::<Rgb8>::try_new(10, 10).is_ok());
+    pub fn try_new(width: usize, height: usize) -> Result<Self, AggError> {
+        if width == 0 || height == 0 {
+            return Err(AggError::ZeroSize { width, height });
+        }
+        Ok(Self { rbuf: RenderingBuffer::new(width, height, Self::BPP),
+                  phantom: PhantomData
+        })
+    }
+    pub fn from_file<P: AsRef<Path>>(filename: P) -> Result<Self,image::ImageError> {
+        let (buf,w,h) = crate::ppm::read_file(filename)?;
+        Ok(Self{ rbuf: RenderingBuffer::from_buf(buf, w, h, 3), phantom: PhantomData })
+    }
+}
+
+impl<T, B> Pixfmt<T, B> where B: PixelData, Pixfmt<T, B>: Pixel + PixelSize {
+    /// Attach to existing pixel data without copying
+    ///
+    /// Rows are `stride` bytes apart and may include padding.  A negative
+    ///   `stride` is used for bottom-up images, where the first row is
+    ///   stored at the end of `data`.
+    ///
+    /// Returns an error if `width` or `height` is 0, if `stride` is smaller
+    ///   than a row, or if `data` is too small for the image
+    ///
+    ///     use agg::{Pixfmt,Pixel,Source,Rgb8,Rgba8};
+    ///
+    ///     // 2x2 image, rows padded to 8 bytes and stored bottom-up
+    ///     let mut buf = [0u8; 16];
+    ///     let mut pix = Pixfmt::<Rgb8,_>::attach(&mut buf[..], 2, 2, -8).unwrap();
+    ///     pix.set((1,0), Rgba8::white());
+    ///     assert_eq!(pix.get((1,0)), Rgba8::white());
+    ///     assert_eq!(buf[8..14], [0,0,0,255,255,255]);
+    pub fn attach(data: B, width: usize, height: usize, stride: i64) -> Result<Self, AggError> {
         if width == 0 || height == 0 {
-            panic!("Cannot create pixfmt with 0 width or height");
+            return Err(AggError::ZeroSize { width, height });
         }
-        Self { rbuf: RenderingBuffer::new(width, height, Self::bpp()),
-               phantom: PhantomData
+        let len = data.as_ref().len();
+        let rbuf = RenderingBuffer::attach(data, width, height, Self::BPP, stride)
+            .ok_or(AggError::BufferSize { width, height, stride, len })?;
+        Ok(Self { rbuf, phantom: PhantomData })
+    }
+}
+
+impl<T, B> Pixfmt<T, B> where B: PixelData, Pixfmt<T, B>: Pixel {
+    /// Bytes from the start of one row to the start of the next
+    ///
+    /// Negative if rows are stored bottom-up
+    pub fn stride(&self) -> i64 {
+        self.rbuf.stride
+    }
+    /// Release the underlying pixel data
+    pub fn into_inner(self) -> B {
+        self.rbuf.data
+    }
+    /// Mutable view of the region at (`x`,`y`) of size `width` x `height`
+    ///
+    /// The view uses local coordinates, with (0,0) at (`x`,`y`), and shares
+    ///   the pixel data.  The region is clipped to the image.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the clipped region is empty, see [`try_view`](#method.try_view)
+    ///
+    ///     use agg::{Pixfmt,Pixel,Source,Rgb8,Rgba8};
+    ///
+    ///     let mut pix = Pixfmt::<Rgb8>::new(10,10);
+    ///     let mut view = pix.view(8, 2, 5, 5);
+    ///     assert_eq!((view.width(), view.height()), (2, 5));
+    ///     view.set((1,0), Rgba8::white());
+    ///     assert_eq!(pix.get((9,2)), Rgba8::white());
+    pub fn view(&mut self, x: usize, y: usize, width: usize, height: usize) -> Pixfmt<T, &mut [u8]>
+        where for<'a> Pixfmt<T, &'a mut [u8]>: Pixel {
+        match self.try_view(x, y, width, height) {
+            Ok(pix) => pix,
+            Err(err) => panic!("{}", err),
+        }
+    }
+    /// Mutable view of the region at (`x`,`y`) of size `width` x `height`
+    ///
+    /// Returns an error if the region clipped to the image is empty
+    pub fn try_view(&mut self, x: usize, y: usize, width: usize, height: usize) -> Result<Pixfmt<T, &mut [u8]>, AggError>
+        where for<'a> Pixfmt<T, &'a mut [u8]>: Pixel {
+        let w = width.min(self.width().saturating_sub(x));
+        let h = height.min(self.height().saturating_sub(y));
+        if w == 0 || h == 0 {
+            return Err(AggError::ZeroSize { width: w, height: h });
         }
+        Ok(Pixfmt { rbuf: self.rbuf.sub(x, y, w, h), phantom: PhantomData })
     }
     // /// Fill with a color
     // pub fn fill<C: Color>(&mut self, color: C) {
@@ -43,6 +164,8 @@ impl<T> Pixfmt<T> where Pixfmt<T>: Pixel {
     // }
 
     /// Size of Rendering Buffer in bytes; width * height * bpp
+    ///
+    /// Includes any padding for attached data
     pub fn size(&self) -> usize {
         self.rbuf.len()
     }
@@ -73,6 +196,16 @@ impl<T> Pixfmt<T> where Pixfmt<T>: Pixel {
     pub fn clear(&mut self) {
         self.rbuf.clear();
     }
+    /// Raw components of the pixel at `id`
+    pub(crate) fn components(&self, id: (usize, usize)) -> &[u8] {
+        let bpp = self.rbuf.bpp;
+        &self.rbuf[id][..bpp]
+    }
+    /// Mutable raw components of the pixel at `id`
+    pub(crate) fn components_mut(&mut self, id: (usize, usize)) -> &mut [u8] {
+        let bpp = self.rbuf.bpp;
+        &mut self.rbuf[id][..bpp]
+    }
     //pub fn from(rbuf: RenderingBuffer) -> Self {
     //    Self { rbuf, phantom: PhantomData }
     //}
@@ -150,33 +283,34 @@ impl<T> Pixfmt<T> where Pixfmt<T>: Pixel {
             self.set((x,y+i), c);
         }
     }
-
-    pub fn from_file<P: AsRef<Path>>(filename: P) -> Result<Self,image::ImageError> {
-        let (buf,w,h) = crate::ppm::read_file(filename)?;
-        Ok(Self{ rbuf: RenderingBuffer::from_buf(buf, w, h, 3), phantom: PhantomData })
-    }
 }
 
-impl Source for Pixfmt<Rgba8> {
+impl<B: PixelData> Source for Pixfmt<Rgba8, B> {
     fn get(&self, id: (usize, usize)) -> Rgba8 {
         let p = &self.rbuf[id];
         Rgba8::new(p[0],p[1],p[2],p[3])
     }
 }
-impl Source for Pixfmt<Rgba8pre> {
+impl<B: PixelData> Source for Pixfmt<Rgba8pre, B> {
     fn get(&self, id: (usize, usize)) -> Rgba8 {
         let p = &self.rbuf[id];
         Rgba8::new(p[0],p[1],p[2],p[3])
     }
 }
-impl Source for Pixfmt<Rgb8> {
+impl<B: PixelData> Source for Pixfmt<Rgb8, B> {
     fn get(&self, id: (usize, usize)) -> Rgba8 {
         let p = &self.rbuf[id];
         Rgba8::new(p[0],p[1],p[2],255)
     }
 }
-impl Source for Pixfmt<Rgba32> {
+impl<B: PixelData> Source for Pixfmt<Rgba32, B> {
     fn get(&self, id: (usize, usize)) -> Rgba8 {
+        Rgba8::from_trait(self.raw(id))
+    }
+}
+impl<B: PixelData> Pixfmt<Rgba32, B> {
+    /// Color of the pixel at `id`
+    pub fn raw(&self, id: (usize, usize)) -> Rgba32 {
         //let n = (id.0 + id.1 * self.rbuf.width) * Pixfmt::<Rgba32>::bpp();
         let p = &self.rbuf[id];
         let red   : f32 = unsafe { std::mem::transmute::<[u8;4],f32>([p[0],p[1],p[2],p[3]]) };
@@ -184,8 +318,7 @@ impl Source for Pixfmt<Rgba32> {
         let blue  : f32 = unsafe { std::mem::transmute::<[u8;4],f32>([p[8],p[9],p[10],p[11]]) };
         let alpha : f32 = unsafe { std::mem::transmute::<[u8;4],f32>([p[12],p[13],p[14],p[15]]) };
 
-        let c = Rgba32::new(red,green,blue,alpha);
-        Rgba8::from_trait(c)
+        Rgba32::new(red,green,blue,alpha)
     }
 }
 
@@ -199,29 +332,39 @@ macro_rules! impl_pixel {
         fn width(&self) -> usize {
             self.rbuf.width
         }
-        /// Return a underlying raw pixel/component data
+        /// Underlying storage, including padding and parent pixels
         fn as_bytes(&self) -> &[u8] {
-            &self.rbuf.data
+            self.rbuf.data.as_ref()
+        }
+        /// Pixels of the image, top-down without padding
+        fn packed(&self) -> std::borrow::Cow<'_, [u8]> {
+            self.rbuf.packed()
+        }
+        /// Bytes per pixel
+        fn bpp(&self) -> usize {
+            self.rbuf.bpp
         }
         
     }
 }
 
-impl Pixel for Pixfmt<Rgba8> {
+impl<B: PixelData> PixelSize for Pixfmt<Rgba8, B> {
+    const BPP: usize = 4;
+}
+impl<B: PixelData> Pixel for Pixfmt<Rgba8, B> {
     impl_pixel!();
     fn setn<C: Color>(&mut self, id: (usize, usize), n: usize, c: C) {
-        let bpp = Self::bpp();
+        let bpp = Self::BPP;
         let c = Rgba8::from_trait(c).into_slice();
         let p = &mut self.rbuf[id][..n*bpp];
         for chunk in p.chunks_mut(bpp) {
             chunk.copy_from_slice(&c);
         }
     }
-    fn bpp() -> usize { 4 }
     fn cover_mask() -> u64 { 255 }
     fn set<C: Color>(&mut self, id: (usize, usize), c: C) {
         let c = Rgba8::from_trait(c);
-        assert!(! self.rbuf.data.is_empty() );
+        assert!(! self.rbuf.data.as_ref().is_empty() );
         self.rbuf[id][0] = c.red8();
         self.rbuf[id][1] = c.green8();
         self.rbuf[id][2] = c.blue8();
@@ -244,29 +387,23 @@ impl Pixel for Pixfmt<Rgba8> {
         self.set(id, pix);
     }
     fn fill<C: Color>(&mut self, color: C) {
-        let n = 4;
-        let bpp = Self::bpp();
         let c = Rgba8::from_trait(color).into_slice();
-        let c2 = [ c[0],c[1],c[2],c[3],  c[0],c[1],c[2],c[3],  c[0],c[1],c[2],c[3],  c[0],c[1],c[2],c[3] ];
-        let mut chunks = self.rbuf.data.chunks_exact_mut(bpp*n);
-        while let Some(chunk) = chunks.next() {
-            chunk.copy_from_slice(&c2);
-        }
-        for chunk in chunks.into_remainder().chunks_mut(bpp) {
-            chunk.copy_from_slice(&c);
-        }
+        self.rbuf.fill(&c);
     }
     
     fn to_file<P: AsRef<std::path::Path>>(&self, filename: P) -> Result<(),image::ImageError> {
-        crate::ppm::write_file(self.as_bytes(), self.width(), self.height(), filename, image::ColorType::Rgba8)
+        crate::ppm::write_file(&self.rbuf.packed(), self.width(), self.height(), filename, image::ColorType::Rgba8)
     }
 
 }
 
-impl Pixel for Pixfmt<Rgb8> {
+impl<B: PixelData> PixelSize for Pixfmt<Rgb8, B> {
+    const BPP: usize = 3;
+}
+impl<B: PixelData> Pixel for Pixfmt<Rgb8, B> {
     impl_pixel!();
     fn setn<C: Color>(&mut self, id: (usize, usize), n: usize, c: C) {
-        let bpp = Self::bpp();
+        let bpp = Self::BPP;
         let c = Rgb8::from_trait(c).into_slice();
         let p = &mut self.rbuf[id][..bpp*n];
         for chunk in p.chunks_mut(bpp) {
@@ -281,7 +418,6 @@ impl Pixel for Pixfmt<Rgb8> {
         //p[1] = c.green8();
         //p[2] = c.blue8();
     }
-    fn bpp() -> usize { 3 }
     fn cover_mask() -> u64 { 255 }
     fn blend_pix<C: Color>(&mut self, id: (usize, usize), c: C, cover: u64) {
         let pix0 = self.raw(id);
@@ -289,25 +425,16 @@ impl Pixel for Pixfmt<Rgb8> {
         self.set(id, pix);
     }
     fn fill<C: Color>(&mut self, color: C) {
-        let n = 4;
-        let bpp = Self::bpp();
         let c = Rgb8::from_trait(color).into_slice();
-        let c2 = [ c[0],c[1],c[2],  c[0],c[1],c[2], c[0],c[1],c[2], c[0],c[1],c[2] ];
-        let mut chunks = self.rbuf.data.chunks_exact_mut(bpp*n);
-        while let Some(chunk) = chunks.next() {
-            chunk.copy_from_slice(&c2);
-        }
-        for chunk in chunks.into_remainder().chunks_mut(bpp) {
-            chunk.copy_from_slice(&c);
-        }
+        self.rbuf.fill(&c);
     }
     
     fn to_file<P: AsRef<std::path::Path>>(&self, filename: P) -> Result<(),image::ImageError> {
-        crate::ppm::write_file(self.as_bytes(), self.width(), self.height(), filename, image::ColorType::Rgb8)
+        crate::ppm::write_file(&self.rbuf.packed(), self.width(), self.height(), filename, image::ColorType::Rgb8)
     }
 
 }
-impl Pixfmt<Gray8> {
+impl<B: PixelData> Pixfmt<Gray8, B> {
     fn mix_pix(&mut self, id: (usize,usize), c: Gray8, alpha: u8) -> Gray8 {
         let p = Gray8::from_slice( &self.rbuf[id] );
         Gray8::new_with_alpha(lerp_u8(p.value, c.value, alpha), alpha)
@@ -316,8 +443,13 @@ impl Pixfmt<Gray8> {
         Gray8::from_slice( &self.rbuf[id] )
     }
 }
+impl<B: PixelData> Source for Pixfmt<Gray8, B> {
+    fn get(&self, id: (usize, usize)) -> Rgba8 {
+        Rgba8::from_trait(self.raw(id))
+    }
+}
 
-impl Pixfmt<Rgba8> {
+impl<B: PixelData> Pixfmt<Rgba8, B> {
     /// Computer **over** operator
     ///
     /// # Arguments
@@ -343,10 +475,13 @@ impl Pixfmt<Rgba8> {
         self.set(id, pix);
     }
 }
-impl Pixel for Pixfmt<Rgba8pre> {
+impl<B: PixelData> PixelSize for Pixfmt<Rgba8pre, B> {
+    const BPP: usize = 4;
+}
+impl<B: PixelData> Pixel for Pixfmt<Rgba8pre, B> {
     impl_pixel!();
     fn setn<C: Color>(&mut self, id: (usize, usize), n: usize, c: C) {
-        let bpp = Self::bpp();
+        let bpp = Self::BPP;
         let c = Rgba8pre::from_trait(c).into_slice();
         let p = &mut self.rbuf[id][..n*bpp];
         for chunk in p.chunks_mut(bpp) {
@@ -360,7 +495,6 @@ impl Pixel for Pixfmt<Rgba8pre> {
         self.rbuf[id][2] = c.blue8();
         self.rbuf[id][3] = c.alpha8();
     }
-    fn bpp() -> usize { 4 }
     fn cover_mask() -> u64 { 255 }
     fn blend_pix<C: Color>(&mut self, id: (usize, usize), c: C, cover: u64) {
         let p = self.get(id);
@@ -370,24 +504,15 @@ impl Pixel for Pixfmt<Rgba8pre> {
         self.set(id, p);
     }
     fn fill<C: Color>(&mut self, color: C) {
-        let n = 4;
-        let bpp = Self::bpp();
         let c = Rgba8pre::from_trait(color).into_slice();
-        let c2 = [ c[0],c[1],c[2],c[3],  c[0],c[1],c[2],c[3],  c[0],c[1],c[2],c[3],  c[0],c[1],c[2],c[3] ];
-        let mut chunks = self.rbuf.data.chunks_exact_mut(bpp*n);
-        while let Some(chunk) = chunks.next() {
-            chunk.copy_from_slice(&c2);
-        }
-        for chunk in chunks.into_remainder().chunks_mut(bpp) {
-            chunk.copy_from_slice(&c);
-        }
+        self.rbuf.fill(&c);
     }
     fn to_file<P: AsRef<std::path::Path>>(&self, filename: P) -> Result<(),image::ImageError> {
-        crate::ppm::write_file(self.as_bytes(), self.width(), self.height(), filename, image::ColorType::Rgba8)
+        crate::ppm::write_file(&self.rbuf.packed(), self.width(), self.height(), filename, image::ColorType::Rgba8)
     }
 }
 
-impl Pixfmt<Rgb8> {
+impl<B: PixelData> Pixfmt<Rgb8, B> {
     pub fn raw(&self, id: (usize, usize)) -> Rgb8 {
          let p = &self.rbuf[id];
         Rgb8::new(p[0],p[1],p[2])
@@ -411,7 +536,7 @@ impl Pixfmt<Rgb8> {
         Rgb8::new(red, green, blue)
     }
 }
-impl Pixfmt<Rgba8pre> {
+impl<B: PixelData> Pixfmt<Rgba8pre, B> {
     /// Compute **over** operator
     ///
     /// # Arguments
@@ -424,19 +549,10 @@ impl Pixfmt<Rgba8pre> {
     ///   - prelerp(p, c * cover, alpha * cover)
     ///
     fn mix_pix(&mut self, p: Rgba8pre, c: Rgba8pre, alpha: u8, cover: u64) -> Rgba8pre {
-        let alpha = multiply_u8(alpha, cover as u8);
-        let red   = multiply_u8(c.r, cover as u8);
-        let green = multiply_u8(c.g, cover as u8);
-        let blue  = multiply_u8(c.b, cover as u8);
-
-        let red   = prelerp_u8(p.r, red,   alpha);
-        let green = prelerp_u8(p.g, green, alpha);
-        let blue  = prelerp_u8(p.b, blue,  alpha);
-        let alpha = prelerp_u8(p.a, alpha, alpha);
-        Rgba8pre::new(red, green, blue, alpha)
+        mix_pix_pre(p, c, alpha, cover)
     }
     pub fn drop_alpha(&self) -> Pixfmt<Rgb8> {
-        let buf : Vec<_> = self.as_bytes().iter()
+        let buf : Vec<_> = self.rbuf.packed().iter()
             .enumerate()
             .filter(|(i,_)| i%4 < 3)
             .map(|(_,x)| *x)
@@ -451,7 +567,10 @@ impl Pixfmt<Rgba8pre> {
     }
 }
 
-impl Pixel for Pixfmt<Rgba32> {
+impl<B: PixelData> PixelSize for Pixfmt<Rgba32, B> {
+    const BPP: usize = 4*4;
+}
+impl<B: PixelData> Pixel for Pixfmt<Rgba32, B> {
     impl_pixel!();
     fn setn<C: Color>(&mut self, id: (usize, usize), n: usize, c: C) {
         for i in 0 .. n {
@@ -460,7 +579,7 @@ impl Pixel for Pixfmt<Rgba32> {
     }
     fn set<C: Color>(&mut self, id: (usize, usize), c: C) {
         let c = Rgba32::from_trait(c);
-        assert!(self.rbuf.data.len() > 0);
+        assert!(! self.rbuf.data.as_ref().is_empty());
         let red   : [u8;4] = unsafe { std::mem::transmute(c.r) };
         let green : [u8;4] = unsafe { std::mem::transmute(c.g) };
         let blue  : [u8;4] = unsafe { std::mem::transmute(c.b) };
@@ -476,7 +595,6 @@ impl Pixel for Pixfmt<Rgba32> {
         //self.rbuf[id][ 8..12] = unsafe { std::mem::transmute(c.b) };
         //self.rbuf[id][12..16] = unsafe { std::mem::transmute(c.a) };
     }
-    fn bpp() -> usize { 4*4 }
     fn cover_mask() -> u64 { unimplemented!("no cover mask") }
     fn blend_pix<C: Color>(&mut self, _id: (usize, usize), _c: C, _cover: u64) {
         unimplemented!("no blending");
@@ -495,15 +613,18 @@ impl Pixel for Pixfmt<Rgba32> {
     }
     
     fn to_file<P: AsRef<std::path::Path>>(&self, filename: P) -> Result<(),image::ImageError> {
-        crate::ppm::write_file(self.as_bytes(), self.width(), self.height(), filename, image::ColorType::Rgba8)
+        crate::ppm::write_file(&self.rbuf.packed(), self.width(), self.height(), filename, image::ColorType::Rgba8)
     }
 
 }
 
-impl Pixel for Pixfmt<Gray8> {
+impl<B: PixelData> PixelSize for Pixfmt<Gray8, B> {
+    const BPP: usize = 2;
+}
+impl<B: PixelData> Pixel for Pixfmt<Gray8, B> {
     impl_pixel!();
     fn setn<C: Color>(&mut self, id: (usize, usize), n: usize, color: C) {
-        let bpp = Self::bpp();
+        let bpp = Self::BPP;
         let c = Gray8::from_trait(color).into_slice();
         let p = &mut self.rbuf[id][..n*bpp];
         for chunk in p.chunks_mut(bpp) {
@@ -516,34 +637,481 @@ impl Pixel for Pixfmt<Gray8> {
         self.rbuf[id][1] = c.alpha;
     }
     fn cover_mask() -> u64 {  255  }
-    fn bpp() -> usize { 2 }
     fn blend_pix<C: Color>(&mut self, id: (usize, usize), c: C, cover: u64) {
         let alpha = multiply_u8(c.alpha8(), cover as u8);
         let p0 = self.mix_pix(id, Gray8::from_trait(c), alpha);
         self.set(id, p0);
     }
     fn fill<C: Color>(&mut self, color: C) {
-        let n = 4;
-        let bpp = Self::bpp();
         let c = Gray8::from_trait(color).into_slice();
-        let c2 = [c[0],c[1], c[0],c[1], c[0],c[1], c[0],c[1]];
-        let mut chunks = self.rbuf.data.chunks_exact_mut(bpp*n);
-        while let Some(chunk) = chunks.next() {
-            chunk.copy_from_slice(&c2);
-        }
-        for chunk in chunks.into_remainder().chunks_mut(bpp) {
-            chunk.copy_from_slice(&c);
-        }
+        self.rbuf.fill(&c);
     }
     
     fn to_file<P: AsRef<std::path::Path>>(&self, filename: P) -> Result<(),image::ImageError> {
-        crate::ppm::write_file(self.as_bytes(), self.width(), self.height(), filename, image::ColorType::L8)
+        crate::ppm::write_file(&self.rbuf.packed(), self.width(), self.height(), filename, image::ColorType::La8)
     }
     
 }
 
+/// Pixel Formats with 16-bit components, stored in native byte order
+///
+/// Each format provides `raw`, `pack` and `mix_pix`
+macro_rules! impl_pixfmt_u16 {
+    ($t:ident, $n:expr, $color_type:expr) => {
+        impl<B: PixelData> Pixfmt<$t, B> {
+            /// 16-bit components of the pixel at `id`
+            fn components16(&self, id: (usize, usize)) -> [u16; $n] {
+                let p = &self.rbuf[id];
+                let mut v = [0u16; $n];
+                for (i, v) in v.iter_mut().enumerate() {
+                    *v = u16::from_ne_bytes([p[2*i], p[2*i+1]]);
+                }
+                v
+            }
+            /// Bytes of the 16-bit components of `c`
+            fn pack_bytes<C: Color>(c: C) -> [u8; 2*$n] {
+                let mut p = [0u8; 2*$n];
+                for (i, v) in Self::pack(c).iter().enumerate() {
+                    p[2*i..2*i+2].copy_from_slice(&v.to_ne_bytes());
+                }
+                p
+            }
+        }
+        impl<B: PixelData> Source for Pixfmt<$t, B> {
+            fn get(&self, id: (usize, usize)) -> Rgba8 {
+                Rgba8::from_trait(self.raw(id))
+            }
+        }
+        impl<B: PixelData> PixelSize for Pixfmt<$t, B> {
+            const BPP: usize = 2 * $n;
+        }
+        impl<B: PixelData> Pixel for Pixfmt<$t, B> {
+            impl_pixel!();
+            fn setn<C: Color>(&mut self, id: (usize, usize), n: usize, c: C) {
+                let bpp = Self::BPP;
+                let c = Self::pack_bytes(c);
+                let p = &mut self.rbuf[id][..n*bpp];
+                for chunk in p.chunks_mut(bpp) {
+                    chunk.copy_from_slice(&c);
+                }
+            }
+            fn set<C: Color>(&mut self, id: (usize, usize), c: C) {
+                let c = Self::pack_bytes(c);
+                self.rbuf[id][..2*$n].copy_from_slice(&c);
+            }
+            /// Covers are 8-bit and expanded to 16-bit when blending
+            fn cover_mask() -> u64 { 255 }
+            fn blend_pix<C: Color>(&mut self, id: (usize, usize), c: C, cover: u64) {
+                let p = self.mix_pix(self.raw(id), c, cover_u16(cover));
+                self.set(id, p);
+            }
+            fn fill<C: Color>(&mut self, color: C) {
+                let c = Self::pack_bytes(color);
+                self.rbuf.fill(&c);
+            }
+            /// Write a 16-bit image
+            fn to_file<P: AsRef<std::path::Path>>(&self, filename: P) -> Result<(),image::ImageError> {
+                crate::ppm::write_file(&self.rbuf.packed(), self.width(), self.height(), filename, $color_type)
+            }
+        }
+    }
+}
+
+impl<B: PixelData> Pixfmt<Gray16, B> {
+    /// Color of the pixel at `id`
+    pub fn raw(&self, id: (usize, usize)) -> Gray16 {
+        let v = self.components16(id);
+        Gray16::new_with_alpha(v[0], v[1])
+    }
+    fn pack<C: Color>(c: C) -> [u16; 2] {
+        let c = Gray16::from_trait(c);
+        [c.value, c.alpha]
+    }
+    /// Compute **over** operator
+    ///
+    /// # Output
+    ///   - lerp(p, c, alpha(c) * cover)
+    fn mix_pix<C: Color>(&self, p: Gray16, c: C, cover: u16) -> Gray16 {
+        let c = Gray16::from_trait(c);
+        let alpha = multiply_u16(c.alpha, cover);
+        let value = lerp_u16(p.value, c.value, alpha);
+        Gray16::new_with_alpha(value, prelerp_u16(p.alpha, alpha, alpha))
+    }
+}
+impl<B: PixelData> Pixfmt<Rgb48, B> {
+    /// Color of the pixel at `id`
+    pub fn raw(&self, id: (usize, usize)) -> Rgb48 {
+        let v = self.components16(id);
+        Rgb48::new(v[0], v[1], v[2])
+    }
+    fn pack<C: Color>(c: C) -> [u16; 3] {
+        let c = Rgb48::from_trait(c);
+        [c.r, c.g, c.b]
+    }
+    /// Compute **over** operator
+    ///
+    /// # Output
+    ///   - lerp(p, c, alpha(c) * cover)
+    fn mix_pix<C: Color>(&self, p: Rgb48, c: C, cover: u16) -> Rgb48 {
+        let c = Rgba64::from_trait(c);
+        let alpha = multiply_u16(c.a, cover);
+        Rgb48::new(lerp_u16(p.r, c.r, alpha),
+                   lerp_u16(p.g, c.g, alpha),
+                   lerp_u16(p.b, c.b, alpha))
+    }
+}
+impl<B: PixelData> Pixfmt<Rgba64, B> {
+    /// Color of the pixel at `id`
+    pub fn raw(&self, id: (usize, usize)) -> Rgba64 {
+        let v = self.components16(id);
+        Rgba64::new(v[0], v[1], v[2], v[3])
+    }
+    fn pack<C: Color>(c: C) -> [u16; 4] {
+        let c = Rgba64::from_trait(c);
+        [c.r, c.g, c.b, c.a]
+    }
+    /// Compute **over** operator
+    ///
+    /// # Output
+    ///   - lerp(p, c, alpha(c) * cover)
+    fn mix_pix<C: Color>(&self, p: Rgba64, c: C, cover: u16) -> Rgba64 {
+        let c = Rgba64::from_trait(c);
+        let alpha = multiply_u16(c.a, cover);
+        Rgba64::new(lerp_u16(p.r, c.r, alpha),
+                    lerp_u16(p.g, c.g, alpha),
+                    lerp_u16(p.b, c.b, alpha),
+                    prelerp_u16(p.a, alpha, alpha))
+    }
+}
+impl<B: PixelData> Pixfmt<Rgba64pre, B> {
+    /// Color of the pixel at `id`
+    pub fn raw(&self, id: (usize, usize)) -> Rgba64pre {
+        let v = self.components16(id);
+        Rgba64pre::new(v[0], v[1], v[2], v[3])
+    }
+    fn pack<C: Color>(c: C) -> [u16; 4] {
+        let c = Rgba64pre::from_trait(c);
+        [c.r, c.g, c.b, c.a]
+    }
+    /// Compute **over** operator
+    ///
+    /// # Output
+    ///   - prelerp(p, c * cover, alpha(c) * cover)
+    fn mix_pix<C: Color>(&self, p: Rgba64pre, c: C, cover: u16) -> Rgba64pre {
+        let c = Rgba64pre::from_trait(c);
+        let alpha = multiply_u16(c.a, cover);
+        Rgba64pre::new(prelerp_u16(p.r, multiply_u16(c.r, cover), alpha),
+                       prelerp_u16(p.g, multiply_u16(c.g, cover), alpha),
+                       prelerp_u16(p.b, multiply_u16(c.b, cover), alpha),
+                       prelerp_u16(p.a, alpha, alpha))
+    }
+}
+
+impl_pixfmt_u16!(Gray16,    2, image::ColorType::La16);
+impl_pixfmt_u16!(Rgb48,     3, image::ColorType::Rgb16);
+impl_pixfmt_u16!(Rgba64,    4, image::ColorType::Rgba16);
+impl_pixfmt_u16!(Rgba64pre, 4, image::ColorType::Rgba16);
+
+/// Pixel Formats with red, green and blue packed into 16 bits, stored in
+///   native byte order
+///
+/// Pixels are unpacked to 8-bit components, blended and packed again
+macro_rules! impl_pixfmt_packed {
+    ($t:ident) => {
+        impl<B: PixelData> Pixfmt<$t, B> {
+            /// Color of the pixel at `id`
+            pub fn raw(&self, id: (usize, usize)) -> $t {
+                let p = &self.rbuf[id];
+                $t(u16::from_ne_bytes([p[0], p[1]]))
+            }
+        }
+        impl<B: PixelData> Source for Pixfmt<$t, B> {
+            fn get(&self, id: (usize, usize)) -> Rgba8 {
+                Rgba8::from(self.raw(id))
+            }
+        }
+        impl<B: PixelData> PixelSize for Pixfmt<$t, B> {
+            const BPP: usize = 2;
+        }
+        impl<B: PixelData> Pixel for Pixfmt<$t, B> {
+            impl_pixel!();
+            fn setn<C: Color>(&mut self, id: (usize, usize), n: usize, c: C) {
+                let c = $t::from_trait(c).0.to_ne_bytes();
+                let p = &mut self.rbuf[id][..n*2];
+                for chunk in p.chunks_mut(2) {
+                    chunk.copy_from_slice(&c);
+                }
+            }
+            fn set<C: Color>(&mut self, id: (usize, usize), c: C) {
+                let c = $t::from_trait(c).0.to_ne_bytes();
+                self.rbuf[id][..2].copy_from_slice(&c);
+            }
+            fn cover_mask() -> u64 { 255 }
+            /// Compute **over** operator with coverage
+            ///
+            /// # Output
+            ///   - lerp(pixel(x,y), color, cover * alpha(color))
+            fn blend_pix<C: Color>(&mut self, id: (usize, usize), c: C, cover: u64) {
+                let alpha = multiply_u8(c.alpha8(), cover as u8);
+                let p = self.raw(id);
+                let red   = lerp_u8(p.red8(),   c.red8(),   alpha);
+                let green = lerp_u8(p.green8(), c.green8(), alpha);
+                let blue  = lerp_u8(p.blue8(),  c.blue8(),  alpha);
+                self.set(id, $t::new(red, green, blue));
+            }
+            fn fill<C: Color>(&mut self, color: C) {
+                let c = $t::from_trait(color).0.to_ne_bytes();
+                self.rbuf.fill(&c);
+            }
+            /// Write the image unpacked to 8-bit RGB
+            fn to_file<P: AsRef<std::path::Path>>(&self, filename: P) -> Result<(),image::ImageError> {
+                let (w, h) = (self.width(), self.height());
+                let mut buf = Vec::with_capacity(w * h * 3);
+                for y in 0 .. h {
+                    for x in 0 .. w {
+                        let c = self.raw((x,y));
+                        buf.extend_from_slice(&[c.red8(), c.green8(), c.blue8()]);
+                    }
+                }
+                crate::ppm::write_file(&buf, w, h, filename, image::ColorType::Rgb8)
+            }
+        }
+    }
+}
+
+impl_pixfmt_packed!(Rgb565);
+impl_pixfmt_packed!(Rgb555);
+
+/// Pixel Formats with sRGB encoded components, blended in linear light
+///
+/// Components are converted to 16-bit linear values through lookup tables,
+///   blended and converted back.  Alpha is not encoded.
+///
+/// Each format provides `mix_pix`
+macro_rules! impl_pixfmt_srgb {
+    ($t:ident, $n:expr, $color_type:expr) => {
+        impl<B: PixelData> Pixfmt<$t, B> {
+            /// Color of the pixel at `id`
+            pub fn raw(&self, id: (usize, usize)) -> [u8; $n] {
+                let mut p = [0u8; $n];
+                p.copy_from_slice(&self.rbuf[id][..$n]);
+                p
+            }
+        }
+        impl<B: PixelData> Source for Pixfmt<$t, B> {
+            fn get(&self, id: (usize, usize)) -> Rgba8 {
+                let p = self.raw(id);
+                let a = if $n == 4 { p[$n-1] } else { 255 };
+                Rgba8::from_trait(Srgba8::new(p[0], p[1], p[2], a))
+            }
+        }
+        impl<B: PixelData> PixelSize for Pixfmt<$t, B> {
+            const BPP: usize = $n;
+        }
+        impl<B: PixelData> Pixel for Pixfmt<$t, B> {
+            impl_pixel!();
+            fn setn<C: Color>(&mut self, id: (usize, usize), n: usize, c: C) {
+                let c = $t::from_rgb(c).into_slice();
+                let p = &mut self.rbuf[id][..n*$n];
+                for chunk in p.chunks_mut($n) {
+                    chunk.copy_from_slice(&c);
+                }
+            }
+            fn set<C: Color>(&mut self, id: (usize, usize), c: C) {
+                let c = $t::from_rgb(c).into_slice();
+                self.rbuf[id][..$n].copy_from_slice(&c);
+            }
+            fn cover_mask() -> u64 { 255 }
+            fn blend_pix<C: Color>(&mut self, id: (usize, usize), c: C, cover: u64) {
+                let p = Self::mix_pix(self.raw(id), c, cover);
+                self.rbuf[id][..$n].copy_from_slice(&p);
+            }
+            fn fill<C: Color>(&mut self, color: C) {
+                let c = $t::from_rgb(color).into_slice();
+                self.rbuf.fill(&c);
+            }
+            /// Write the sRGB encoded image
+            fn to_file<P: AsRef<std::path::Path>>(&self, filename: P) -> Result<(),image::ImageError> {
+                crate::ppm::write_file(&self.rbuf.packed(), self.width(), self.height(), filename, $color_type)
+            }
+        }
+    }
+}
+
+/// Blend the sRGB component `v` with the linear component `c` in linear light
+fn srgb_lerp(v: u8, c: f64, alpha: u16) -> u8 {
+    linear16_to_srgb8(lerp_u16(srgb8_to_linear16(v), cu16(c), alpha))
+}
+
+impl<B: PixelData> Pixfmt<Srgb8, B> {
+    /// Compute **over** operator in linear light
+    ///
+    /// # Output
+    ///   - lerp(p, c, alpha(c) * cover)
+    fn mix_pix<C: Color>(p: [u8; 3], c: C, cover: u64) -> [u8; 3] {
+        let alpha = cover_u16(u64::from(multiply_u8(c.alpha8(), cover as u8)));
+        [srgb_lerp(p[0], c.red(),   alpha),
+         srgb_lerp(p[1], c.green(), alpha),
+         srgb_lerp(p[2], c.blue(),  alpha)]
+    }
+}
+impl<B: PixelData> Pixfmt<Srgba8, B> {
+    /// Compute **over** operator in linear light
+    ///
+    /// # Output
+    ///   - lerp(p, c, alpha(c) * cover)
+    fn mix_pix<C: Color>(p: [u8; 4], c: C, cover: u64) -> [u8; 4] {
+        let alpha = multiply_u8(c.alpha8(), cover as u8);
+        let alpha16 = cover_u16(u64::from(alpha));
+        [srgb_lerp(p[0], c.red(),   alpha16),
+         srgb_lerp(p[1], c.green(), alpha16),
+         srgb_lerp(p[2], c.blue(),  alpha16),
+         prelerp_u8(p[3], alpha, alpha)]
+    }
+}
+impl<B: PixelData> Pixfmt<Srgba8pre, B> {
+    /// Compute **over** operator in linear light
+    ///
+    /// # Arguments
+    ///   - p     - Current pixel, premultiplied
+    ///   - c     - Overlaying pixel, premultiplied
+    ///   - cover - Coverage
+    ///
+    /// # Output
+    ///   - prelerp(p, c * cover, alpha(c) * cover)
+    fn mix_pix<C: Color>(p: [u8; 4], c: C, cover: u64) -> [u8; 4] {
+        let alpha = multiply_u8(c.alpha8(), cover as u8);
+        let (alpha16, cover16) = (cover_u16(u64::from(alpha)), cover_u16(cover));
+        let mix = |v: u8, c: f64| {
+            let c = multiply_u16(cu16(c), cover16);
+            linear16_to_srgb8(prelerp_u16(srgb8_to_linear16(v), c, alpha16))
+        };
+        [mix(p[0], c.red()),
+         mix(p[1], c.green()),
+         mix(p[2], c.blue()),
+         prelerp_u8(p[3], alpha, alpha)]
+    }
+}
+
+impl_pixfmt_srgb!(Srgb8,     3, image::ColorType::Rgb8);
+impl_pixfmt_srgb!(Srgba8,    4, image::ColorType::Rgba8);
+impl_pixfmt_srgb!(Srgba8pre, 4, image::ColorType::Rgba8);
+
 use crate::base::RenderingBase;
 
+/// Compute **over** operator for premultiplied components
+///
+///   prelerp(p, c * cover, alpha * cover)
+fn mix_pix_pre(p: Rgba8pre, c: Rgba8pre, alpha: u8, cover: u64) -> Rgba8pre {
+    let alpha = multiply_u8(alpha, cover as u8);
+    let red   = multiply_u8(c.r, cover as u8);
+    let green = multiply_u8(c.g, cover as u8);
+    let blue  = multiply_u8(c.b, cover as u8);
+
+    let red   = prelerp_u8(p.r, red,   alpha);
+    let green = prelerp_u8(p.g, green, alpha);
+    let blue  = prelerp_u8(p.b, blue,  alpha);
+    let alpha = prelerp_u8(p.a, alpha, alpha);
+    Rgba8pre::new(red, green, blue, alpha)
+}
+
+/// Pixel Formats with 8-bit components in any order
+///
+/// `[$r, $g, $b]` are the offsets of red, green and blue within a pixel and
+///   `$a` is the offset of alpha, if present.  If `$pre` is true the
+///   components are premultiplied and blended as [`Rgba8pre`](../color/struct.Rgba8pre.html)
+macro_rules! impl_pixfmt_order {
+    ($t:ident, $bpp:expr, [$r:expr, $g:expr, $b:expr], $a:expr, $pre:expr) => {
+        impl<B: PixelData> Pixfmt<$t, B> {
+            /// Components of `c` in memory order
+            fn pack<C: Color>(c: C) -> [u8; $bpp] {
+                let mut p = [0u8; $bpp];
+                p[$r] = c.red8();
+                p[$g] = c.green8();
+                p[$b] = c.blue8();
+                if let Some(a) = $a {
+                    p[a] = c.alpha8();
+                }
+                p
+            }
+        }
+        impl<B: PixelData> Source for Pixfmt<$t, B> {
+            fn get(&self, id: (usize, usize)) -> Rgba8 {
+                let p = &self.rbuf[id];
+                let a = match $a {
+                    Some(a) => p[a],
+                    None => 255,
+                };
+                Rgba8::new(p[$r], p[$g], p[$b], a)
+            }
+        }
+        impl<B: PixelData> PixelSize for Pixfmt<$t, B> {
+            const BPP: usize = $bpp;
+        }
+        impl<B: PixelData> Pixel for Pixfmt<$t, B> {
+            impl_pixel!();
+            fn setn<C: Color>(&mut self, id: (usize, usize), n: usize, c: C) {
+                let bpp = Self::BPP;
+                let c = Self::pack(c);
+                let p = &mut self.rbuf[id][..n*bpp];
+                for chunk in p.chunks_mut(bpp) {
+                    chunk.copy_from_slice(&c);
+                }
+            }
+            fn set<C: Color>(&mut self, id: (usize, usize), c: C) {
+                let c = Self::pack(c);
+                self.rbuf[id][..$bpp].copy_from_slice(&c);
+            }
+            fn cover_mask() -> u64 { 255 }
+            /// Compute **over** operator with coverage
+            fn blend_pix<C: Color>(&mut self, id: (usize, usize), c: C, cover: u64) {
+                let p = self.get(id);
+                if $pre {
+                    let p0 = Rgba8pre::new(p.r, p.g, p.b, p.a);
+                    let c0 = Rgba8pre::new(c.red8(), c.green8(), c.blue8(), c.alpha8());
+                    self.set(id, mix_pix_pre(p0, c0, c.alpha8(), cover));
+                    return;
+                }
+                let alpha = multiply_u8(c.alpha8(), cover as u8);
+                let red   = lerp_u8(p.r, c.red8(),   alpha);
+                let green = lerp_u8(p.g, c.green8(), alpha);
+                let blue  = lerp_u8(p.b, c.blue8(),  alpha);
+                let alpha = prelerp_u8(p.a, alpha, alpha);
+                self.set(id, Rgba8::new(red, green, blue, alpha));
+            }
+            fn fill<C: Color>(&mut self, color: C) {
+                let c = Self::pack(color);
+                self.rbuf.fill(&c);
+            }
+            /// Write the image in RGB(A) order
+            fn to_file<P: AsRef<std::path::Path>>(&self, filename: P) -> Result<(),image::ImageError> {
+                let (w, h) = (self.width(), self.height());
+                let mut buf = Vec::with_capacity(w * h * $bpp);
+                for y in 0 .. h {
+                    for x in 0 .. w {
+                        let c = self.get((x,y)).into_slice();
+                        buf.extend_from_slice(&c[..$bpp]);
+                    }
+                }
+                let color_type = match $a {
+                    Some(_) => image::ColorType::Rgba8,
+                    None => image::ColorType::Rgb8,
+                };
+                crate::ppm::write_file(&buf, w, h, filename, color_type)
+            }
+        }
+    }
+}
+
+impl_pixfmt_order!(Bgr8,     3, [2, 1, 0], None::<usize>, false);
+impl_pixfmt_order!(Bgra8,    4, [2, 1, 0], Some(3), false);
+impl_pixfmt_order!(Argb8,    4, [1, 2, 3], Some(0), false);
+impl_pixfmt_order!(Abgr8,    4, [3, 2, 1], Some(0), false);
+impl_pixfmt_order!(Bgra8pre, 4, [2, 1, 0], Some(3), true);
+impl_pixfmt_order!(Argb8pre, 4, [1, 2, 3], Some(0), true);
+impl_pixfmt_order!(Abgr8pre, 4, [3, 2, 1], Some(0), true);
+
+
 pub struct PixfmtAlphaBlend<'a,T,C> where T: Pixel {
     ren: &'a mut RenderingBase<T>,
     offset: usize,
@@ -582,8 +1150,11 @@ impl Pixel for PixfmtAlphaBlend<'_,Pixfmt<Rgb8>,Gray8> {
     fn as_bytes(&self) -> &[u8] {
         self.ren.pixf.as_bytes()
     }
+    fn packed(&self) -> std::borrow::Cow<'_, [u8]> {
+        self.ren.pixf.packed()
+    }
     fn to_file<P: AsRef<std::path::Path>>(&self, filename: P) -> Result<(),image::ImageError> {
-        crate::ppm::write_file(self.as_bytes(), self.width(), self.height(), filename, image::ColorType::Rgba8)
+        crate::ppm::write_file(&self.packed(), self.width(), self.height(), filename, image::ColorType::Rgba8)
     }
     fn fill<C: Color>(&mut self, color: C) {
         let (w,h) = (self.width(), self.height());
@@ -604,7 +1175,7 @@ impl Pixel for PixfmtAlphaBlend<'_,Pixfmt<Rgb8>,Gray8> {
         self.ren.pixf.rbuf[id][self.offset] = self.component(c).value;
     }
     fn cover_mask() -> u64 { Pixfmt::<Rgb8>::cover_mask() }
-    fn bpp() -> usize { Pixfmt::<Rgb8>::bpp() }
+    fn bpp(&self) -> usize { self.ren.pixf.bpp() }
     fn blend_pix<C: Color>(&mut self, id: (usize, usize), c: C, cover: u64) {
         let alpha = multiply_u8(c.alpha8(), cover as u8);
 
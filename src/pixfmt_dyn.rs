@@ -0,0 +1,441 @@
+//! Pixel Format chosen at Runtime
+//!
+//! [`Pixel`] has generic methods and cannot be used as a trait object.
+//!   [`DynPixfmt`] wraps each of the pixel formats in an enum and
+//!   implements [`Pixel`] and [`Source`], so it may be used with a
+//!   [`RenderingBase`] and all renderers
+//!
+//!     use agg::{DynPixfmt,PixelFormat,RenderingBase,Rgba8};
+//!
+//!     let format : PixelFormat = "gray8".parse().unwrap();
+//!     let pix = DynPixfmt::new(format, 100, 100);
+//!     let mut ren_base = RenderingBase::new(pix);
+//!     ren_base.clear(Rgba8::white());
+//!     assert_eq!(ren_base.pixf.format(), PixelFormat::Gray8);
+//!
+//! [`Pixel`]: ../trait.Pixel.html
+//! [`Source`]: ../trait.Source.html
+//! [`DynPixfmt`]: enum.DynPixfmt.html
+//! [`RenderingBase`]: ../base/struct.RenderingBase.html
+
+use crate::color::*;
+use crate::pixfmt::Pixfmt;
+use crate::pixfmt::PixelSize;
+use crate::image_accessors::ImageSource;
+use crate::Color;
+use crate::Source;
+use crate::Pixel;
+use crate::AggError;
+
+/// Pixel Formats available in a DynPixfmt
+#[derive(Debug,Copy,Clone,PartialEq,Eq)]
+pub enum PixelFormat {
+    /// Red, Green, Blue
+    Rgb8,
+    /// Red, Green, Blue, Alpha
+    Rgba8,
+    /// Red, Green, Blue, Alpha, Premultiplied
+    Rgba8pre,
+    /// Gray, Alpha
+    Gray8,
+    /// Blue, Green, Red
+    Bgr8,
+    /// Blue, Green, Red, Alpha
+    Bgra8,
+    /// Alpha, Red, Green, Blue
+    Argb8,
+    /// Alpha, Blue, Green, Red
+    Abgr8,
+    /// Blue, Green, Red, Alpha, Premultiplied
+    Bgra8pre,
+    /// Alpha, Red, Green, Blue, Premultiplied
+    Argb8pre,
+    /// Alpha, Blue, Green, Red, Premultiplied
+    Abgr8pre,
+    /// Gray, Alpha, 16-bit
+    Gray16,
+    /// Red, Green, Blue, 16-bit
+    Rgb48,
+    /// Red, Green, Blue, Alpha, 16-bit
+    Rgba64,
+    /// Red, Green, Blue, Alpha, Premultiplied, 16-bit
+    Rgba64pre,
+    /// Red, Green, Blue, packed 5-6-5
+    Rgb565,
+    /// Red, Green, Blue, packed 5-5-5
+    Rgb555,
+    /// Standard Red, Green, Blue, blended in linear light
+    Srgb8,
+    /// Standard Red, Green, Blue, Alpha, blended in linear light
+    Srgba8,
+    /// Standard Red, Green, Blue, Alpha, Premultiplied, blended in linear light
+    Srgba8pre,
+}
+
+impl PixelFormat {
+    /// Bytes per pixel
+    pub fn bpp(&self) -> usize {
+        match self {
+            PixelFormat::Rgb8     => Pixfmt::<Rgb8>::BPP,
+            PixelFormat::Rgba8    => Pixfmt::<Rgba8>::BPP,
+            PixelFormat::Rgba8pre => Pixfmt::<Rgba8pre>::BPP,
+            PixelFormat::Gray8    => Pixfmt::<Gray8>::BPP,
+            PixelFormat::Bgr8     => Pixfmt::<Bgr8>::BPP,
+            PixelFormat::Bgra8    => Pixfmt::<Bgra8>::BPP,
+            PixelFormat::Argb8    => Pixfmt::<Argb8>::BPP,
+            PixelFormat::Abgr8    => Pixfmt::<Abgr8>::BPP,
+            PixelFormat::Bgra8pre => Pixfmt::<Bgra8pre>::BPP,
+            PixelFormat::Argb8pre => Pixfmt::<Argb8pre>::BPP,
+            PixelFormat::Abgr8pre => Pixfmt::<Abgr8pre>::BPP,
+            PixelFormat::Gray16    => Pixfmt::<Gray16>::BPP,
+            PixelFormat::Rgb48     => Pixfmt::<Rgb48>::BPP,
+            PixelFormat::Rgba64    => Pixfmt::<Rgba64>::BPP,
+            PixelFormat::Rgba64pre => Pixfmt::<Rgba64pre>::BPP,
+            PixelFormat::Rgb565    => Pixfmt::<Rgb565>::BPP,
+            PixelFormat::Rgb555    => Pixfmt::<Rgb555>::BPP,
+            PixelFormat::Srgb8     => Pixfmt::<Srgb8>::BPP,
+            PixelFormat::Srgba8    => Pixfmt::<Srgba8>::BPP,
+            PixelFormat::Srgba8pre => Pixfmt::<Srgba8pre>::BPP,
+        }
+    }
+}
+
+/// Parse a format name, ignoring case, e.g. "rgb8" or "Rgba8pre"
+impl std::str::FromStr for PixelFormat {
+    type Err = AggError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "rgb8"     => Ok(PixelFormat::Rgb8),
+            "rgba8"    => Ok(PixelFormat::Rgba8),
+            "rgba8pre" => Ok(PixelFormat::Rgba8pre),
+            "gray8"    => Ok(PixelFormat::Gray8),
+            "bgr8"     => Ok(PixelFormat::Bgr8),
+            "bgra8"    => Ok(PixelFormat::Bgra8),
+            "argb8"    => Ok(PixelFormat::Argb8),
+            "abgr8"    => Ok(PixelFormat::Abgr8),
+            "bgra8pre" => Ok(PixelFormat::Bgra8pre),
+            "argb8pre" => Ok(PixelFormat::Argb8pre),
+            "abgr8pre" => Ok(PixelFormat::Abgr8pre),
+            "gray16"   => Ok(PixelFormat::Gray16),
+            "rgb48"    => Ok(PixelFormat::Rgb48),
+            "rgba64"   => Ok(PixelFormat::Rgba64),
+            "rgba64pre" => Ok(PixelFormat::Rgba64pre),
+            "rgb565"   => Ok(PixelFormat::Rgb565),
+            "rgb555"   => Ok(PixelFormat::Rgb555),
+            "srgb8"    => Ok(PixelFormat::Srgb8),
+            "srgba8"   => Ok(PixelFormat::Srgba8),
+            "srgba8pre" => Ok(PixelFormat::Srgba8pre),
+            _ => Err(AggError::UnknownFormat(s.to_string())),
+        }
+    }
+}
+
+/// Pixel Format chosen at runtime
+#[derive(Debug)]
+pub enum DynPixfmt {
+    /// Red, Green, Blue
+    Rgb8(Pixfmt<Rgb8>),
+    /// Red, Green, Blue, Alpha
+    Rgba8(Pixfmt<Rgba8>),
+    /// Red, Green, Blue, Alpha, Premultiplied
+    Rgba8pre(Pixfmt<Rgba8pre>),
+    /// Gray, Alpha
+    Gray8(Pixfmt<Gray8>),
+    /// Blue, Green, Red
+    Bgr8(Pixfmt<Bgr8>),
+    /// Blue, Green, Red, Alpha
+    Bgra8(Pixfmt<Bgra8>),
+    /// Alpha, Red, Green, Blue
+    Argb8(Pixfmt<Argb8>),
+    /// Alpha, Blue, Green, Red
+    Abgr8(Pixfmt<Abgr8>),
+    /// Blue, Green, Red, Alpha, Premultiplied
+    Bgra8pre(Pixfmt<Bgra8pre>),
+    /// Alpha, Red, Green, Blue, Premultiplied
+    Argb8pre(Pixfmt<Argb8pre>),
+    /// Alpha, Blue, Green, Red, Premultiplied
+    Abgr8pre(Pixfmt<Abgr8pre>),
+    /// Gray, Alpha, 16-bit
+    Gray16(Pixfmt<Gray16>),
+    /// Red, Green, Blue, 16-bit
+    Rgb48(Pixfmt<Rgb48>),
+    /// Red, Green, Blue, Alpha, 16-bit
+    Rgba64(Pixfmt<Rgba64>),
+    /// Red, Green, Blue, Alpha, Premultiplied, 16-bit
+    Rgba64pre(Pixfmt<Rgba64pre>),
+    /// Red, Green, Blue, packed 5-6-5
+    Rgb565(Pixfmt<Rgb565>),
+    /// Red, Green, Blue, packed 5-5-5
+    Rgb555(Pixfmt<Rgb555>),
+    /// Standard Red, Green, Blue, blended in linear light
+    Srgb8(Pixfmt<Srgb8>),
+    /// Standard Red, Green, Blue, Alpha, blended in linear light
+    Srgba8(Pixfmt<Srgba8>),
+    /// Standard Red, Green, Blue, Alpha, Premultiplied, blended in linear light
+    Srgba8pre(Pixfmt<Srgba8pre>),
+}
+
+/// Call `$e` with the pixel format within `$self` bound to `$p`
+macro_rules! dispatch {
+    ($self:expr, $p:ident => $e:expr) => {
+        match $self {
+            DynPixfmt::Rgb8($p)     => $e,
+            DynPixfmt::Rgba8($p)    => $e,
+            DynPixfmt::Rgba8pre($p) => $e,
+            DynPixfmt::Gray8($p)    => $e,
+            DynPixfmt::Bgr8($p)     => $e,
+            DynPixfmt::Bgra8($p)    => $e,
+            DynPixfmt::Argb8($p)    => $e,
+            DynPixfmt::Abgr8($p)    => $e,
+            DynPixfmt::Bgra8pre($p) => $e,
+            DynPixfmt::Argb8pre($p) => $e,
+            DynPixfmt::Abgr8pre($p) => $e,
+            DynPixfmt::Gray16($p)    => $e,
+            DynPixfmt::Rgb48($p)     => $e,
+            DynPixfmt::Rgba64($p)    => $e,
+            DynPixfmt::Rgba64pre($p) => $e,
+            DynPixfmt::Rgb565($p)    => $e,
+            DynPixfmt::Rgb555($p)    => $e,
+            DynPixfmt::Srgb8($p)     => $e,
+            DynPixfmt::Srgba8($p)    => $e,
+            DynPixfmt::Srgba8pre($p) => $e,
+        }
+    }
+}
+
+impl DynPixfmt {
+    /// Create new Pixel Format of `format` and width * height
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` or `height` is 0, see [`try_new`](#method.try_new)
+    pub fn new(format: PixelFormat, width: usize, height: usize) -> Self {
+        match Self::try_new(format, width, height) {
+            Ok(pix) => pix,
+            Err(err) => panic!("{}", err),
+        }
+    }
+    /// Create new Pixel Format of `format` and width * height
+    ///
+    /// Returns an error if `width` or `height` is 0
+    pub fn try_new(format: PixelFormat, width: usize, height: usize) -> Result<Self, AggError> {
+        Ok(match format {
+            PixelFormat::Rgb8     => DynPixfmt::Rgb8(Pixfmt::try_new(width, height)?),
+            PixelFormat::Rgba8    => DynPixfmt::Rgba8(Pixfmt::try_new(width, height)?),
+            PixelFormat::Rgba8pre => DynPixfmt::Rgba8pre(Pixfmt::try_new(width, height)?),
+            PixelFormat::Gray8    => DynPixfmt::Gray8(Pixfmt::try_new(width, height)?),
+            PixelFormat::Bgr8     => DynPixfmt::Bgr8(Pixfmt::try_new(width, height)?),
+            PixelFormat::Bgra8    => DynPixfmt::Bgra8(Pixfmt::try_new(width, height)?),
+            PixelFormat::Argb8    => DynPixfmt::Argb8(Pixfmt::try_new(width, height)?),
+            PixelFormat::Abgr8    => DynPixfmt::Abgr8(Pixfmt::try_new(width, height)?),
+            PixelFormat::Bgra8pre => DynPixfmt::Bgra8pre(Pixfmt::try_new(width, height)?),
+            PixelFormat::Argb8pre => DynPixfmt::Argb8pre(Pixfmt::try_new(width, height)?),
+            PixelFormat::Abgr8pre => DynPixfmt::Abgr8pre(Pixfmt::try_new(width, height)?),
+            PixelFormat::Gray16    => DynPixfmt::Gray16(Pixfmt::try_new(width, height)?),
+            PixelFormat::Rgb48     => DynPixfmt::Rgb48(Pixfmt::try_new(width, height)?),
+            PixelFormat::Rgba64    => DynPixfmt::Rgba64(Pixfmt::try_new(width, height)?),
+            PixelFormat::Rgba64pre => DynPixfmt::Rgba64pre(Pixfmt::try_new(width, height)?),
+            PixelFormat::Rgb565    => DynPixfmt::Rgb565(Pixfmt::try_new(width, height)?),
+            PixelFormat::Rgb555    => DynPixfmt::Rgb555(Pixfmt::try_new(width, height)?),
+            PixelFormat::Srgb8     => DynPixfmt::Srgb8(Pixfmt::try_new(width, height)?),
+            PixelFormat::Srgba8    => DynPixfmt::Srgba8(Pixfmt::try_new(width, height)?),
+            PixelFormat::Srgba8pre => DynPixfmt::Srgba8pre(Pixfmt::try_new(width, height)?),
+        })
+    }
+    /// Format of the pixels
+    pub fn format(&self) -> PixelFormat {
+        match self {
+            DynPixfmt::Rgb8(_)     => PixelFormat::Rgb8,
+            DynPixfmt::Rgba8(_)    => PixelFormat::Rgba8,
+            DynPixfmt::Rgba8pre(_) => PixelFormat::Rgba8pre,
+            DynPixfmt::Gray8(_)    => PixelFormat::Gray8,
+            DynPixfmt::Bgr8(_)     => PixelFormat::Bgr8,
+            DynPixfmt::Bgra8(_)    => PixelFormat::Bgra8,
+            DynPixfmt::Argb8(_)    => PixelFormat::Argb8,
+            DynPixfmt::Abgr8(_)    => PixelFormat::Abgr8,
+            DynPixfmt::Bgra8pre(_) => PixelFormat::Bgra8pre,
+            DynPixfmt::Argb8pre(_) => PixelFormat::Argb8pre,
+            DynPixfmt::Abgr8pre(_) => PixelFormat::Abgr8pre,
+            DynPixfmt::Gray16(_)    => PixelFormat::Gray16,
+            DynPixfmt::Rgb48(_)     => PixelFormat::Rgb48,
+            DynPixfmt::Rgba64(_)    => PixelFormat::Rgba64,
+            DynPixfmt::Rgba64pre(_) => PixelFormat::Rgba64pre,
+            DynPixfmt::Rgb565(_)    => PixelFormat::Rgb565,
+            DynPixfmt::Rgb555(_)    => PixelFormat::Rgb555,
+            DynPixfmt::Srgb8(_)     => PixelFormat::Srgb8,
+            DynPixfmt::Srgba8(_)    => PixelFormat::Srgba8,
+            DynPixfmt::Srgba8pre(_) => PixelFormat::Srgba8pre,
+        }
+    }
+}
+
+impl From<Pixfmt<Rgb8>> for DynPixfmt {
+    fn from(pix: Pixfmt<Rgb8>) -> Self {
+        DynPixfmt::Rgb8(pix)
+    }
+}
+impl From<Pixfmt<Rgba8>> for DynPixfmt {
+    fn from(pix: Pixfmt<Rgba8>) -> Self {
+        DynPixfmt::Rgba8(pix)
+    }
+}
+impl From<Pixfmt<Rgba8pre>> for DynPixfmt {
+    fn from(pix: Pixfmt<Rgba8pre>) -> Self {
+        DynPixfmt::Rgba8pre(pix)
+    }
+}
+impl From<Pixfmt<Gray8>> for DynPixfmt {
+    fn from(pix: Pixfmt<Gray8>) -> Self {
+        DynPixfmt::Gray8(pix)
+    }
+}
+impl From<Pixfmt<Bgr8>> for DynPixfmt {
+    fn from(pix: Pixfmt<Bgr8>) -> Self {
+        DynPixfmt::Bgr8(pix)
+    }
+}
+impl From<Pixfmt<Bgra8>> for DynPixfmt {
+    fn from(pix: Pixfmt<Bgra8>) -> Self {
+        DynPixfmt::Bgra8(pix)
+    }
+}
+impl From<Pixfmt<Argb8>> for DynPixfmt {
+    fn from(pix: Pixfmt<Argb8>) -> Self {
+        DynPixfmt::Argb8(pix)
+    }
+}
+impl From<Pixfmt<Abgr8>> for DynPixfmt {
+    fn from(pix: Pixfmt<Abgr8>) -> Self {
+        DynPixfmt::Abgr8(pix)
+    }
+}
+impl From<Pixfmt<Bgra8pre>> for DynPixfmt {
+    fn from(pix: Pixfmt<Bgra8pre>) -> Self {
+        DynPixfmt::Bgra8pre(pix)
+    }
+}
+impl From<Pixfmt<Argb8pre>> for DynPixfmt {
+    fn from(pix: Pixfmt<Argb8pre>) -> Self {
+        DynPixfmt::Argb8pre(pix)
+    }
+}
+impl From<Pixfmt<Abgr8pre>> for DynPixfmt {
+    fn from(pix: Pixfmt<Abgr8pre>) -> Self {
+        DynPixfmt::Abgr8pre(pix)
+    }
+}
+impl From<Pixfmt<Gray16>> for DynPixfmt {
+    fn from(pix: Pixfmt<Gray16>) -> Self {
+        DynPixfmt::Gray16(pix)
+    }
+}
+impl From<Pixfmt<Rgb48>> for DynPixfmt {
+    fn from(pix: Pixfmt<Rgb48>) -> Self {
+        DynPixfmt::Rgb48(pix)
+    }
+}
+impl From<Pixfmt<Rgba64>> for DynPixfmt {
+    fn from(pix: Pixfmt<Rgba64>) -> Self {
+        DynPixfmt::Rgba64(pix)
+    }
+}
+impl From<Pixfmt<Rgba64pre>> for DynPixfmt {
+    fn from(pix: Pixfmt<Rgba64pre>) -> Self {
+        DynPixfmt::Rgba64pre(pix)
+    }
+}
+impl From<Pixfmt<Rgb565>> for DynPixfmt {
+    fn from(pix: Pixfmt<Rgb565>) -> Self {
+        DynPixfmt::Rgb565(pix)
+    }
+}
+impl From<Pixfmt<Rgb555>> for DynPixfmt {
+    fn from(pix: Pixfmt<Rgb555>) -> Self {
+        DynPixfmt::Rgb555(pix)
+    }
+}
+impl From<Pixfmt<Srgb8>> for DynPixfmt {
+    fn from(pix: Pixfmt<Srgb8>) -> Self {
+        DynPixfmt::Srgb8(pix)
+    }
+}
+impl From<Pixfmt<Srgba8>> for DynPixfmt {
+    fn from(pix: Pixfmt<Srgba8>) -> Self {
+        DynPixfmt::Srgba8(pix)
+    }
+}
+impl From<Pixfmt<Srgba8pre>> for DynPixfmt {
+    fn from(pix: Pixfmt<Srgba8pre>) -> Self {
+        DynPixfmt::Srgba8pre(pix)
+    }
+}
+
+impl Source for DynPixfmt {
+    fn get(&self, id: (usize, usize)) -> Rgba8 {
+        match self {
+            DynPixfmt::Rgb8(p)     => p.get(id),
+            DynPixfmt::Rgba8(p)    => p.get(id),
+            DynPixfmt::Rgba8pre(p) => p.get(id),
+            DynPixfmt::Gray8(p)    => Rgba8::from_trait(p.raw(id)),
+            DynPixfmt::Bgr8(p)     => p.get(id),
+            DynPixfmt::Bgra8(p)    => p.get(id),
+            DynPixfmt::Argb8(p)    => p.get(id),
+            DynPixfmt::Abgr8(p)    => p.get(id),
+            DynPixfmt::Bgra8pre(p) => p.get(id),
+            DynPixfmt::Argb8pre(p) => p.get(id),
+            DynPixfmt::Abgr8pre(p) => p.get(id),
+            DynPixfmt::Gray16(p)    => p.get(id),
+            DynPixfmt::Rgb48(p)     => p.get(id),
+            DynPixfmt::Rgba64(p)    => p.get(id),
+            DynPixfmt::Rgba64pre(p) => p.get(id),
+            DynPixfmt::Rgb565(p)    => p.get(id),
+            DynPixfmt::Rgb555(p)    => p.get(id),
+            DynPixfmt::Srgb8(p)     => p.get(id),
+            DynPixfmt::Srgba8(p)    => p.get(id),
+            DynPixfmt::Srgba8pre(p) => p.get(id),
+        }
+    }
+}
+
+impl ImageSource for DynPixfmt {
+    fn pixel(&self, x: i64, y: i64) -> Rgba8 {
+        if x < 0 || y < 0 || x >= self.width() as i64 || y >= self.height() as i64 {
+            return Rgba8::new(0,0,0,0);
+        }
+        self.get((x as usize, y as usize))
+    }
+}
+
+impl Pixel for DynPixfmt {
+    fn cover_mask() -> u64 { 255 }
+    fn bpp(&self) -> usize {
+        dispatch!(self, p => p.bpp())
+    }
+    fn as_bytes(&self) -> &[u8] {
+        dispatch!(self, p => p.as_bytes())
+    }
+    fn packed(&self) -> std::borrow::Cow<'_, [u8]> {
+        dispatch!(self, p => p.packed())
+    }
+    fn to_file<P: AsRef<std::path::Path>>(&self, filename: P) -> Result<(),image::ImageError> {
+        dispatch!(self, p => p.to_file(filename))
+    }
+    fn width(&self) -> usize {
+        dispatch!(self, p => p.width())
+    }
+    fn height(&self) -> usize {
+        dispatch!(self, p => p.height())
+    }
+    fn set<C: Color>(&mut self, id: (usize, usize), c: C) {
+        dispatch!(self, p => p.set(id, c))
+    }
+    fn setn<C: Color>(&mut self, id: (usize, usize), n: usize, c: C) {
+        dispatch!(self, p => p.setn(id, n, c))
+    }
+    fn blend_pix<C: Color>(&mut self, id: (usize, usize), c: C, cover: u64) {
+        dispatch!(self, p => p.blend_pix(id, c, cover))
+    }
+    fn fill<C: Color>(&mut self, color: C) {
+        dispatch!(self, p => p.fill(color))
+    }
+}
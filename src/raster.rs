@@ -12,19 +12,89 @@ use crate::paths::Vertex;
 
 //use crate::Rasterize;
 use crate::VertexSource;
+use crate::gamma::Gamma;
 
 use std::cmp::min;
 use std::cmp::max;
 
-struct RasConvInt {
+/// Maximum absolute coordinate at subpixel scale
+///
+/// Larger coordinates are saturated to this value
+const POLY_MAX_COORD: i64 = (1 << 30) - 1;
+
+/// Convert a value to subpixel scale, saturating to the allowed range
+///
+/// NaN is converted to 0
+fn upscale_sat(v: f64) -> i64 {
+    let v = (v * POLY_SUBPIXEL_SCALE as f64).round();
+    if v < -POLY_MAX_COORD as f64 {
+        -POLY_MAX_COORD
+    } else if v > POLY_MAX_COORD as f64 {
+        POLY_MAX_COORD
+    } else {
+        v as i64
+    }
+}
+
+/// Coordinate Conversion for the Rasterizer Clipper
+///
+/// Input coordinates are converted into the clipper coordinate type using
+///   upscale(), clipped, and then converted into subpixel values for the
+///   Rasterizer Cells with xi() and yi()
+pub trait RasConv: std::fmt::Debug {
+    /// Coordinate type used during clipping
+    type Coord: Copy + PartialOrd + std::fmt::Debug +
+        std::ops::Add<Output=Self::Coord> + std::ops::Sub<Output=Self::Coord>;
+    /// Convert an input coordinate to the clipper coordinate type
+    fn upscale(v: f64) -> Self::Coord;
+    /// Compute a * b / c
+    fn mul_div(a: Self::Coord, b: Self::Coord, c: Self::Coord) -> Self::Coord;
+    /// Convert a clipper x coordinate to subpixel scale
+    fn xi(v: Self::Coord) -> i64;
+    /// Convert a clipper y coordinate to subpixel scale
+    fn yi(v: Self::Coord) -> i64;
+}
+
+/// Integer Clipper Coordinates, Default
+///
+/// Coordinates are converted to subpixel scale before clipping and saturated
+///   to +/- 2^30 - 1 (about 4 million pixels)
+#[derive(Debug)]
+pub struct RasConvInt {
+}
+impl RasConv for RasConvInt {
+    type Coord = i64;
+    fn upscale(v: f64) -> i64 {
+        upscale_sat(v)
+    }
+    fn mul_div(a: i64, b: i64, c: i64) -> i64 {
+        let (a,b,c) = (a as f64, b as f64, c as f64);
+        (a * b / c).round() as i64
+    }
+    fn xi(v: i64) -> i64 { v }
+    fn yi(v: i64) -> i64 { v }
+}
+
+/// Double Precision Clipper Coordinates
+///
+/// Coordinates are clipped as f64 values and converted to subpixel scale
+///   afterwards.  Use for very large world coordinates together with a
+///   clip box, see [`RasterizerScanline::new_dbl`]
+///
+/// [`RasterizerScanline::new_dbl`]: struct.RasterizerScanline.html#method.new_dbl
+#[derive(Debug)]
+pub struct RasConvDbl {
 }
-impl RasConvInt {
-    pub fn upscale(v: f64) -> i64 {
-        (v * POLY_SUBPIXEL_SCALE as f64).round() as i64
+impl RasConv for RasConvDbl {
+    type Coord = f64;
+    fn upscale(v: f64) -> f64 {
+        v
+    }
+    fn mul_div(a: f64, b: f64, c: f64) -> f64 {
+        a * b / c
     }
-    //pub fn downscale(v: i64) -> i64 {
-    //    v
-    //}
+    fn xi(v: f64) -> i64 { upscale_sat(v) }
+    fn yi(v: f64) -> i64 { upscale_sat(v) }
 }
 
 /// Winding / Filling Rule
@@ -57,18 +127,27 @@ impl Default for PathStatus {
 }
 
 /// Rasterizer Anti-Alias using Scanline
+///
+/// Vertices with a NaN or infinite coordinate are skipped.  If a move_to()
+///   is skipped, the next valid line_to() starts the new sub-path.
+///   Large coordinates are saturated, see [`RasConvInt`] and [`RasConvDbl`]
+///
+/// [`RasConvInt`]: struct.RasConvInt.html
+/// [`RasConvDbl`]: struct.RasConvDbl.html
 #[derive(Debug)]
-pub struct RasterizerScanline {
+pub struct RasterizerScanline<C: RasConv = RasConvInt> {
     /// Clipping Region
-    pub(crate) clipper: Clip,
+    pub(crate) clipper: Clip<C>,
     /// Collection of Rasterizing Cells
     outline: RasterizerCell,
     /// Status of Path
     pub(crate) status: PathStatus,
     /// Current x position
-    pub(crate) x0: i64,
+    pub(crate) x0: C::Coord,
     /// Current y position
-    pub(crate) y0: i64,
+    pub(crate) y0: C::Coord,
+    /// Initial point skipped, next point starts a new sub-path
+    move_pending: bool,
     /// Current y row being worked on, for output
     scan_y: i64,
     /// Filling Rule for Polygons
@@ -77,7 +156,7 @@ pub struct RasterizerScanline {
     gamma: Vec<u64>,
 }
 
-impl RasterizerScanline {
+impl<C: RasConv> RasterizerScanline<C> {
     /// Reset Rasterizer
     ///
     /// Reset the RasterizerCell and set PathStatus to Initial
@@ -87,7 +166,10 @@ impl RasterizerScanline {
     }
     /// Add a Path
     ///
-    /// Walks the path from the VertexSource and rasterizes it
+    /// Walks the path from the VertexSource and rasterizes it;
+    ///   a [`PathCommand::Stop`] ends the path
+    ///
+    /// [`PathCommand::Stop`]: ../paths/enum.PathCommand.html#variant.Stop
     pub fn add_path<VS: VertexSource>(&mut self, path: &VS) {
         //path.rewind();
         if ! self.outline.sorted_y.is_empty() {
@@ -98,7 +180,7 @@ impl RasterizerScanline {
                 PathCommand::LineTo => self.line_to(seg.x, seg.y),
                 PathCommand::MoveTo => self.move_to(seg.x, seg.y),
                 PathCommand::Close  => self.close_polygon(),
-                PathCommand::Stop => unimplemented!("stop encountered"),
+                PathCommand::Stop => break,
             }
         }
     }
@@ -134,8 +216,7 @@ impl RasterizerScanline {
     pub(crate) fn sweep_scanline(&mut self, sl: &mut ScanlineU8) -> bool {
         loop {
             if self.scan_y < 0 {
-                self.scan_y += 1;
-                continue;
+                self.scan_y = 0;
             }
             if self.scan_y > self.outline.max_y {
                 return false;
@@ -198,11 +279,12 @@ impl RasterizerScanline {
         self.outline.max_x
     }
 
-    /// Create a new RasterizerScanline
-    pub fn new() -> Self {
+    /// Create a RasterizerScanline for any coordinate conversion
+    fn with_conv() -> Self {
         Self { clipper: Clip::new(), status: PathStatus::Initial,
                outline: RasterizerCell::new(),
-               x0: 0, y0: 0, scan_y: 0,
+               x0: C::upscale(0.0), y0: C::upscale(0.0), scan_y: 0,
+               move_pending: false,
                filling_rule: FillingRule::NonZero,
                gamma: (0..256).collect(),
         }
@@ -214,49 +296,71 @@ impl RasterizerScanline {
     ///      gamma = gfunc( v / mask ) * mask
     ///```
     /// where v = 0 to 255
+    ///
+    /// See [`set_gamma`](#method.set_gamma) for [`Gamma`] functions
+    ///
+    /// [`Gamma`]: ../gamma/trait.Gamma.html
     pub fn gamma<F>(&mut self, gfunc: F)
         where F: Fn(f64) -> f64
+    {
+        self.set_gamma(gfunc);
+    }
+    /// Set the gamma function from a [`Gamma`] function, e.g. [`GammaPower`]
+    ///
+    /// See [`gamma`](#method.gamma) for a description
+    ///
+    /// [`Gamma`]: ../gamma/trait.Gamma.html
+    /// [`GammaPower`]: ../gamma/struct.GammaPower.html
+    pub fn set_gamma<G>(&mut self, gfunc: G)
+        where G: Gamma
     {
         let aa_shift  = 8;
         let aa_scale  = 1 << aa_shift;
         let aa_mask   = f64::from(aa_scale - 1);
 
         self.gamma = (0..256)
-            .map(|i| gfunc(f64::from(i) / aa_mask ))
+            .map(|i| gfunc.apply(f64::from(i) / aa_mask ))
             .map(|v| (v * aa_mask).round() as u64)
             .collect();
     }
-    /// Create a new RasterizerScanline with a gamma function
-    ///
-    /// See gamma() function for description
-    ///
-    pub fn new_with_gamma<F>(gfunc: F) -> Self
-        where F: Fn(f64) -> f64
-    {
-        let mut new = Self::new();
-        new.gamma( gfunc );
-        new
-    }
     /// Set Clip Box
     pub fn clip_box(&mut self, x1: f64, y1: f64, x2: f64, y2: f64) {
-        self.clipper.clip_box(RasConvInt::upscale(x1),
-                              RasConvInt::upscale(y1),
-                              RasConvInt::upscale(x2),
-                              RasConvInt::upscale(y2));
+        self.clipper.clip_box(C::upscale(x1),
+                              C::upscale(y1),
+                              C::upscale(x2),
+                              C::upscale(y2));
     }
     /// Move to point (x,y)
     ///
     /// Sets point as the initial point
+    ///
+    /// If x or y is NaN or infinite, the point is skipped and the next
+    ///   valid line_to() point is used as the initial point
     pub fn move_to(&mut self, x: f64, y: f64) {
-        self.x0 = RasConvInt::upscale( x );
-        self.y0 = RasConvInt::upscale( y );
+        if ! x.is_finite() || ! y.is_finite() {
+            self.close_polygon();
+            self.move_pending = true;
+            return;
+        }
+        self.move_pending = false;
+        self.x0 = C::upscale( x );
+        self.y0 = C::upscale( y );
         self.clipper.move_to(self.x0,self.y0);
         self.status = PathStatus::MoveTo;
     }
     /// Draw line from previous point to point (x,y)
+    ///
+    /// If x or y is NaN or infinite, the point is skipped
     pub fn line_to(&mut self, x: f64, y: f64) {
-        let x = RasConvInt::upscale( x );
-        let y = RasConvInt::upscale( y );
+        if ! x.is_finite() || ! y.is_finite() {
+            return;
+        }
+        if self.move_pending {
+            self.move_to(x, y);
+            return;
+        }
+        let x = C::upscale( x );
+        let y = C::upscale( y );
         self.clipper.line_to(&mut self.outline, x,y);
         self.status = PathStatus::LineTo;
     }
@@ -292,6 +396,42 @@ impl RasterizerScanline {
     }
 }
 
+impl Default for RasterizerScanline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RasterizerScanline {
+    /// Create a new RasterizerScanline
+    pub fn new() -> Self {
+        Self::with_conv()
+    }
+    /// Create a new RasterizerScanline with a [`Gamma`] function
+    ///
+    /// Closures are accepted as well, e.g. `|v: f64| v * v`.
+    /// See [`gamma`](#method.gamma) for a description
+    ///
+    /// [`Gamma`]: ../gamma/trait.Gamma.html
+    pub fn new_with_gamma<G>(gfunc: G) -> Self
+        where G: Gamma
+    {
+        let mut new = Self::new();
+        new.set_gamma( gfunc );
+        new
+    }
+}
+
+impl RasterizerScanline<RasConvDbl> {
+    /// Create a new RasterizerScanline clipping in double precision
+    ///
+    /// Coordinates are clipped to the clip box before conversion to subpixel
+    ///   scale, preserving the geometry of very large world coordinates
+    pub fn new_dbl() -> Self {
+        Self::with_conv()
+    }
+}
+
 
 
 
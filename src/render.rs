@@ -16,12 +16,19 @@ use crate::raster::len_i64_xy;
 use crate::clip::{INSIDE, TOP,BOTTOM,LEFT,RIGHT};
 use crate::pixfmt::Pixfmt;
 use crate::raster::RasterizerScanline;
-use crate::Rgb8;
+use crate::raster::RasConv;
 use crate::Transform;
+use crate::gradient::GradientFunction;
+use crate::gradient::GradientX;
+use crate::gradient::GradientLut;
+use crate::gradient::GRADIENT_SUBPIXEL_SHIFT;
+use crate::image_accessors::ImageSource;
+use crate::image_accessors::{ImageAccessor,Wrap};
 
 use crate::Source;
 use crate::VertexSource;
 use crate::Render;
+use crate::SpanGenerator;
 use crate::Color;
 use crate::DrawOutline;
 use crate::Pixel;
@@ -43,30 +50,37 @@ pub struct RenderingScanlineAASolid<'a,T> where T: 'a {
     color: Rgba8,
 }
 
+/// Anti-Aliased Renderer using a Span Generator for colors
 #[derive(Debug)]
-pub struct RenderingScanlineAA<'a,T> {
+pub struct RenderingScanlineAA<'a,T,G> where G: SpanGenerator {
     base: &'a mut RenderingBase<T>,
-    span: SpanGradient,
+    span: G,
+    colors: Vec<G::Output>,
 }
 
+/// Gradient Span Generator
+///
+/// Colors are selected from a [`GradientLut`] by the distance from the
+///   [`GradientFunction`] `G` of each pixel mapped through `trans`,
+///   from `d1` to `d2`
+///
+/// [`GradientLut`]: ../gradient/struct.GradientLut.html
+/// [`GradientFunction`]: ../gradient/trait.GradientFunction.html
 #[derive(Debug)]
-pub struct SpanGradient {
+pub struct SpanGradient<G = GradientX> {
     d1: i64,
     d2: i64,
-    gradient: GradientX,
-    color: Vec<Rgb8>,
+    gradient: G,
+    lut: GradientLut,
     trans: Transform,
 }
-#[derive(Debug)]
-pub struct GradientX {}
-impl GradientX {
-    pub fn calculate(&self, x: i64, _: i64, _: i64) -> i64 {
-        x
-    }
-}
 
+/// Linear Span Interpolator
+///
+/// Maps pixels along a span through a Transform, coordinates are returned
+///   with 8 bits of subpixel accuracy
 #[derive(Debug)]
-struct Interpolator {
+pub(crate) struct Interpolator {
     li_x: Option<LineInterpolator>,
     li_y: Option<LineInterpolator>,
     trans: Transform,
@@ -115,17 +129,24 @@ impl Interpolator {
     }
 }
 
-impl SpanGradient {
+impl<G> SpanGradient<G> where G: GradientFunction {
     #[inline]
     pub fn subpixel_shift(&self) -> i64 {
-        4
+        GRADIENT_SUBPIXEL_SHIFT
     }
     #[inline]
     pub fn subpixel_scale(&self) -> i64 {
         1 << self.subpixel_shift()
     }
-    pub fn new(trans: Transform, gradient: GradientX, color: &[Rgb8], d1: f64, d2: f64) -> Self {
-        let mut s = Self { d1: 0, d2: 1, color: color.to_vec(), gradient, trans };
+    /// Create a new Gradient Span Generator
+    ///
+    /// `lut` is a [`GradientLut`] or a slice of colors used as is
+    ///
+    /// [`GradientLut`]: ../gradient/struct.GradientLut.html
+    pub fn new<L>(trans: Transform, gradient: G, lut: L, d1: f64, d2: f64) -> Self
+        where L: Into<GradientLut>
+    {
+        let mut s = Self { d1: 0, d2: 1, lut: lut.into(), gradient, trans };
         s.d1(d1);
         s.d2(d2);
         s
@@ -136,9 +157,32 @@ impl SpanGradient {
     pub fn d2(&mut self, d2: f64) {
         self.d2 = (d2 * self.subpixel_scale() as f64).round() as i64;
     }
-    pub fn prepare(&mut self) {
+    /// Set the gradient Transform, from pixels to gradient space
+    pub fn transform(&mut self, trans: Transform) {
+        self.trans = trans;
+    }
+    /// Gradient function
+    pub fn gradient(&self) -> &G {
+        &self.gradient
+    }
+    /// Mutable gradient function
+    pub fn gradient_mut(&mut self) -> &mut G {
+        &mut self.gradient
+    }
+    /// Color Lookup Table
+    pub fn lut(&self) -> &GradientLut {
+        &self.lut
+    }
+    /// Mutable Color Lookup Table
+    pub fn lut_mut(&mut self) -> &mut GradientLut {
+        &mut self.lut
     }
-    pub fn generate(&self, x: i64, y: i64, len: usize) -> Vec<Rgb8> {
+}
+
+impl<G> SpanGenerator for SpanGradient<G> where G: GradientFunction {
+    type Output = Rgba8;
+    fn generate(&mut self, span: &mut [Rgba8], x: i64, y: i64) {
+        let len = span.len();
         let mut interp = Interpolator::new(self.trans);
 
         let downscale_shift = interp.subpixel_shift() - self.subpixel_shift();
@@ -147,27 +191,19 @@ impl SpanGradient {
         if dd < 1 {
             dd = 1;
         }
-        let ncolors = self.color.len() as i64;
-        let mut span = vec![Rgb8::white() ; len];
+        let ncolors = self.lut.len() as i64;
 
         interp.begin(x as f64 + 0.5, y as f64 + 0.5, len);
 
-        for i in 0 .. len {
+        for c in span.iter_mut() {
             let (x,y) = interp.coordinates();
             let d = self.gradient.calculate(x >> downscale_shift,
                                             y >> downscale_shift,
                                             self.d2);
-            let mut d = ((d-self.d1) * ncolors) / dd;
-            if d < 0 {
-                d = 0;
-            }
-            if d >= ncolors {
-                d = ncolors - 1;
-            }
-            span[i] = self.color[d as usize];
+            let d = ((d-self.d1) * ncolors).div_euclid(dd);
+            *c = self.lut.get(d);
             interp.inc();
         }
-        span
     }
 }
 
@@ -200,9 +236,15 @@ fn render_scanline_aa_solid<T,C: Color>(sl: &ScanlineU8,
 }
 
 /// Render a single Scanline (y-row) with Anti-Aliasing
-fn render_scanline_aa<T>(sl: &ScanlineU8,
-                         ren: &mut RenderingBase<T>,
-                         span_gen: &SpanGradient) where T: Pixel {
+///
+/// Colors are generated into `colors`, which is resized as necessary
+fn render_scanline_aa<T,G>(sl: &ScanlineU8,
+                           ren: &mut RenderingBase<T>,
+                           span_gen: &mut G,
+                           colors: &mut Vec<G::Output>)
+    where T: Pixel,
+          G: SpanGenerator
+{
     let y = sl.y;
     for span in &sl.spans {
         let x = span.x;
@@ -211,12 +253,12 @@ fn render_scanline_aa<T>(sl: &ScanlineU8,
         if len < 0 {
             len = -len;
         }
-        //dbg!(x);
-        //dbg!(y);
-        //dbg!(len);
-        let colors = span_gen.generate(x, y, len as usize);
-        //dbg!(&colors);
-        ren.blend_color_hspan(x, y, len, &colors,
+        if colors.len() < len as usize {
+            colors.resize(len as usize, G::Output::default());
+        }
+        let colors = &mut colors[..len as usize];
+        span_gen.generate(colors, x, y);
+        ren.blend_color_hspan(x, y, len, colors,
                               if span.len < 0 { &[] } else { &covers },
                               covers[0]);
     }
@@ -256,14 +298,19 @@ impl<T> Render for RenderingScanlineBinSolid<'_,T> where T: Pixel {
                                 color.blue8(), color.alpha8());
     }
 }
-impl<T> Render for RenderingScanlineAA<'_,T> where T: Pixel {
+impl<T,G> Render for RenderingScanlineAA<'_,T,G> where T: Pixel, G: SpanGenerator {
     /// Render a single Scanline Row
     fn render(&mut self, data: &RenderData) {
-        render_scanline_aa(&data.sl, &mut self.base, &self.span);
+        render_scanline_aa(&data.sl, &mut self.base, &mut self.span, &mut self.colors);
     }
     /// Set the current Color
+    ///
+    /// Ignored, colors are provided by the Span Generator
     fn color<C: Color>(&mut self, _color: C) {
-        unimplemented!("oops");
+    }
+    /// Prepare the Span Generator
+    fn prepare(&mut self) {
+        self.span.prepare();
     }
 }
 
@@ -283,9 +330,18 @@ impl<'a,T> RenderingScanlineBinSolid<'a,T> where T: Pixel {
     }
 
 }
-impl<'a,T> RenderingScanlineAA<'a,T> where T: Pixel {
-    pub fn new(base: &'a mut RenderingBase<T>, span: SpanGradient) -> Self {
-        Self { base, span }
+impl<'a,T,G> RenderingScanlineAA<'a,T,G> where T: Pixel, G: SpanGenerator {
+    /// Create a new Renderer from a Rendering Base and Span Generator
+    pub fn new(base: &'a mut RenderingBase<T>, span: G) -> Self {
+        Self { base, span, colors: vec![] }
+    }
+    /// Get the Span Generator
+    pub fn span(&self) -> &G {
+        &self.span
+    }
+    /// Get the Span Generator, mutable
+    pub fn span_mut(&mut self) -> &mut G {
+        &mut self.span
     }
 }
 impl<'a,T> RenderingScanlineAASolid<'a,T> where T: Pixel {
@@ -308,11 +364,12 @@ impl<'a,T> RenderingScanlineAASolid<'a,T> where T: Pixel {
 }*/
 
 /// Render rasterized data to an image using a single color, Binary
-pub fn render_scanlines_bin_solid<C,T>(ras: &mut RasterizerScanline,
-                                       ren: &mut RenderingBase<T>,
-                                       color: C)
+pub fn render_scanlines_bin_solid<C,T,R>(ras: &mut RasterizerScanline<R>,
+                                         ren: &mut RenderingBase<T>,
+                                         color: C)
     where C: Color,
-          T: Pixel
+          T: Pixel,
+          R: RasConv
 {
     let mut sl = ScanlineU8::new();
     if ras.rewind_scanlines() {
@@ -324,11 +381,12 @@ pub fn render_scanlines_bin_solid<C,T>(ras: &mut RasterizerScanline,
 }
 
 /// Render rasterized data to an image using a single color, Anti-aliased
-pub fn render_scanlines_aa_solid<C,T>(ras: &mut RasterizerScanline,
-                                      ren: &mut RenderingBase<T>,
-                                      color: C)
+pub fn render_scanlines_aa_solid<C,T,R>(ras: &mut RasterizerScanline<R>,
+                                        ren: &mut RenderingBase<T>,
+                                        color: C)
     where C: Color,
-          T: Pixel
+          T: Pixel,
+          R: RasConv
 {
     let mut sl = ScanlineU8::new();
     if ras.rewind_scanlines() {
@@ -340,9 +398,10 @@ pub fn render_scanlines_aa_solid<C,T>(ras: &mut RasterizerScanline,
 }
 
 /// Render rasterized data to an image using the current color
-pub fn render_scanlines<REN>(ras: &mut RasterizerScanline,
-                             ren: &mut REN)
-    where REN: Render
+pub fn render_scanlines<REN,R>(ras: &mut RasterizerScanline<R>,
+                               ren: &mut REN)
+    where REN: Render,
+          R: RasConv
 {
     let mut data = RenderData::new();
     if ras.rewind_scanlines() {
@@ -354,14 +413,34 @@ pub fn render_scanlines<REN>(ras: &mut RasterizerScanline,
     }
 }
 
+/// Render rasterized data to an image using a Span Generator, Anti-aliased
+pub fn render_scanlines_aa<T,G,R>(ras: &mut RasterizerScanline<R>,
+                                  ren: &mut RenderingBase<T>,
+                                  span_gen: &mut G)
+    where T: Pixel,
+          G: SpanGenerator,
+          R: RasConv
+{
+    let mut sl = ScanlineU8::new();
+    let mut colors = vec![];
+    if ras.rewind_scanlines() {
+        sl.reset(ras.min_x(), ras.max_x());
+        span_gen.prepare();
+        while ras.sweep_scanline(&mut sl) {
+            render_scanline_aa(&sl, ren, span_gen, &mut colors);
+        }
+    }
+}
+
 /// Render paths after rasterizing to an image using a set of colors
-pub fn render_all_paths<REN,VS,C>(ras: &mut RasterizerScanline,
-                                  ren: &mut REN,
-                                  paths: &[VS],
-                                  colors: &[C])
+pub fn render_all_paths<REN,VS,C,R>(ras: &mut RasterizerScanline<R>,
+                                    ren: &mut REN,
+                                    paths: &[VS],
+                                    colors: &[C])
     where C: Color,
           REN: Render,
-          VS: VertexSource
+          VS: VertexSource,
+          R: RasConv
 {
     debug_assert!(paths.len() == colors.len());
     for (path, color) in paths.iter().zip(colors.iter()) {
@@ -753,6 +832,8 @@ pub struct LineImagePattern {
     width_hr: i64,
     half_height_hr: i64,
     offset_y_hr: i64,
+    wrap_x: Wrap,
+    wrap_y: Wrap,
 }
 
 impl LineImagePattern {
@@ -762,9 +843,26 @@ impl LineImagePattern {
         Self { filter, dilation, dilation_hr,
                width: 0, height: 0, width_hr: 0,
                half_height_hr: 0, offset_y_hr: 0,
-               pix: Pixfmt::new(1,1)
+               pix: Pixfmt::new(1,1),
+               wrap_x: Wrap::Repeat, wrap_y: Wrap::Clip,
         }
     }
+    /// Set the wrap modes along (`wrap_x`) and across (`wrap_y`) the line
+    ///
+    /// Default is [`Wrap::Repeat`] along and [`Wrap::Clip`] across the
+    ///   line, with transparent edges.  Use before [`create`](#method.create)
+    ///
+    /// [`Wrap::Repeat`]: ../image_accessors/enum.Wrap.html#variant.Repeat
+    /// [`Wrap::Clip`]: ../image_accessors/enum.Wrap.html#variant.Clip
+    pub fn wrap(&mut self, wrap_x: Wrap, wrap_y: Wrap) {
+        self.wrap_x = wrap_x;
+        self.wrap_y = wrap_y;
+    }
+    /// Create the pattern from `src`
+    ///
+    /// Pixels around `src` used by the filter are sampled with an
+    ///   [`ImageAccessor`](../image_accessors/struct.ImageAccessor.html)
+    ///   and the wrap modes
     pub fn create<T>(&mut self, src: &T) where T: Source + Pixel {
         self.height = src.height() as u64;
         self.width  = src.width() as u64;
@@ -773,51 +871,14 @@ impl LineImagePattern {
         self.offset_y_hr = self.dilation_hr + self.half_height_hr - POLY_SUBPIXEL_SCALE/2;
         self.half_height_hr += POLY_SUBPIXEL_SCALE/2;
 
-        self.pix = Pixfmt::<Rgba8>::new((self.width  + self.dilation * 2) as usize,
-                                        (self.height + self.dilation * 2) as usize);
-        for y in 0 .. self.height as usize {
-            let x1 = self.dilation as usize;
-            let y1 = y + self.dilation as usize;
-            for x in 0 .. self.width as usize {
-                self.pix.set((x1+x,y1), src.get((x,y)));
-            }
-        }
-        //const color_type* s1;
-        //const color_type* s2;
-        let none = Rgba8::new(0,0,0,0);
-        let dill = self.dilation as usize;
-        for y in 0 .. dill {
-            //s1 = self.buf.row_ptr(self.height + self.dilation - 1) + self.dilation;
-            //s2 = self.buf.row_ptr(self.dilation) + self.dilation;
-            //let d1 = self.buf.row_ptr(self.dilation + self.height + y) + self.dilation;
-            //let d2 = self.buf.row_ptr(self.dilation - y - 1) + self.dilation;
-            let (x1,y1) = (dill, dill + y + self.height as usize);
-            let (x2,y2) = (dill, dill - y - 1);
-            for x in 0 .. self.width as usize{
-                //*d1++ = color_type(*s1++, 0);
-                //*d2++ = color_type(*s2++, 0);
-                //*d1++ = color_type::no_color();
-                //*d2++ = color_type::no_color();
-                self.pix.set((x1+x,y1), none);
-                self.pix.set((x2+x,y2), none);
-            }
-        }
+        let w = self.width  + self.dilation * 2;
         let h = self.height + self.dilation * 2;
-        for y in  0 .. h as usize {
-            let sx1 = self.dilation as usize;
-            let sx2 = (self.dilation + self.width) as usize;
-            let dx1 = sx2;
-            let dx2 = sx1;
-            //s1 = self.buf.row_ptr(y) + self.dilation;
-            //s2 = self.buf.row_ptr(y) + self.dilation + self.width;
-            //d1 = self.buf.row_ptr(y) + self.dilation + self.width;
-            //d2 = self.buf.row_ptr(y) + self.dilation;
-
-            for x in 0 .. self.dilation as usize {
-                //*d1++ = *s1++;
-                //*--d2 = *--s2;
-                self.pix.set((dx1 + x,y), self.pix.get((sx1 + x,y)));
-                self.pix.set((dx2 - x - 1,y), self.pix.get((sx2 - x - 1,y)));
+        self.pix = Pixfmt::<Rgba8>::new(w as usize, h as usize);
+        let img = ImageAccessor::new(src, self.wrap_x, self.wrap_y);
+        let dil = self.dilation as i64;
+        for y in 0 .. h as usize {
+            for x in 0 .. w as usize {
+                self.pix.set((x,y), img.pixel(x as i64 - dil, y as i64 - dil));
             }
         }
     }
@@ -830,6 +891,11 @@ impl LineImagePattern {
     pub fn width(&self) -> u64 {
         self.height
     }
+    pub fn pixel(&self, x: i64, y: i64) -> Rgba8 {
+        self.filter.pixel_high_res(&self.pix,
+                                   x.rem_euclid(self.width_hr) + self.dilation_hr,
+                                   y + self.offset_y_hr)
+    }
 }
 
 #[derive(Debug)]
@@ -843,6 +909,12 @@ impl LineImagePatternPow2 {
         let base = LineImagePattern::new( filter );
         Self { base, mask: POLY_SUBPIXEL_MASK as u64}
     }
+    /// Set the wrap modes, see [`LineImagePattern::wrap`]
+    ///
+    /// [`LineImagePattern::wrap`]: struct.LineImagePattern.html#method.wrap
+    pub fn wrap(&mut self, wrap_x: Wrap, wrap_y: Wrap) {
+        self.base.wrap(wrap_x, wrap_y);
+    }
     pub fn create<T>(&mut self, src: &T) where T: Source + Pixel {
         self.base.create(src);
         self.mask = 1;
@@ -882,40 +954,47 @@ impl PatternFilterBilinear {
     pub fn dilation(&self) -> u64 {
         1
     }
-    pub fn pixel_low_res(&self, pix: &Pixfmt<Rgba8>, x: i64, y: i64) -> Rgba8
+    pub fn pixel_low_res<A: ImageSource>(&self, pix: &A, x: i64, y: i64) -> Rgba8
     {
-        pix.get((x as usize, y as usize))
+        pix.pixel(x, y)
     }
-    pub fn pixel_high_res(&self, pix: &Pixfmt<Rgba8>, x: i64, y: i64) -> Rgba8 {
+    /// Bilinear filtered pixel at subpixel location (`x`,`y`)
+    ///
+    /// Pixels outside of `pix` are defined by its [`ImageSource`]
+    ///   implementation, see [`ImageAccessor`]
+    ///
+    /// [`ImageSource`]: ../image_accessors/trait.ImageSource.html
+    /// [`ImageAccessor`]: ../image_accessors/struct.ImageAccessor.html
+    pub fn pixel_high_res<A: ImageSource>(&self, pix: &A, x: i64, y: i64) -> Rgba8 {
 
         let (mut red, mut green, mut blue, mut alpha) = (0i64, 0i64, 0i64, 0i64);
 
-        let x_lr = (x as usize) >> POLY_SUBPIXEL_SHIFT;
-        let y_lr = (y as usize) >> POLY_SUBPIXEL_SHIFT;
+        let x_lr = x >> POLY_SUBPIXEL_SHIFT;
+        let y_lr = y >> POLY_SUBPIXEL_SHIFT;
 
         let x = x & POLY_SUBPIXEL_MASK;
         let y = y & POLY_SUBPIXEL_MASK;
 
-        let ptr = pix.get((x_lr,y_lr));
+        let ptr = pix.pixel(x_lr, y_lr);
 
         let weight = (POLY_SUBPIXEL_SCALE - x) * (POLY_SUBPIXEL_SCALE - y);
         red   += weight * i64::from(ptr.r);
         green += weight * i64::from(ptr.g);
         blue  += weight * i64::from(ptr.b);
         alpha += weight * i64::from(ptr.a);
-        let ptr = pix.get((x_lr + 1,y_lr));
+        let ptr = pix.pixel(x_lr + 1, y_lr);
         let weight = x * (POLY_SUBPIXEL_SCALE - y);
         red   += weight * i64::from(ptr.r);
         green += weight * i64::from(ptr.g);
         blue  += weight * i64::from(ptr.b);
         alpha += weight * i64::from(ptr.a);
-        let ptr = pix.get((x_lr,y_lr+1));
+        let ptr = pix.pixel(x_lr, y_lr + 1);
         let weight = (POLY_SUBPIXEL_SCALE - x) * y;
         red   += weight * i64::from(ptr.r);
         green += weight * i64::from(ptr.g);
         blue  += weight * i64::from(ptr.b);
         alpha += weight * i64::from(ptr.a);
-        let ptr = pix.get((x_lr+1,y_lr+1));
+        let ptr = pix.pixel(x_lr + 1, y_lr + 1);
         let weight = x * y;
         red   += weight * i64::from(ptr.r);
         green += weight * i64::from(ptr.g);
@@ -0,0 +1,112 @@
+//! Drop Shadows and Glows
+//!
+//! Shadows are drawn by rasterizing a shape into a gray scale mask, blurring
+//!   the mask and blending the shadow color through the mask.  Only the
+//!   region around the shape, grown by three times the blur radius, is
+//!   rasterized, blurred and blended.  Draw the shadow before the shape itself
+//!
+//!     use agg::{Pixfmt,Rgb8,Rgba8,RenderingBase,Render,Source};
+//!
+//!     let mut ren_base = RenderingBase::new(Pixfmt::<Rgb8>::new(100,100));
+//!     ren_base.clear(Rgba8::white());
+//!     let card = agg::RoundedRect::new(20.0, 20.0, 70.0, 60.0, 5.0);
+//!
+//!     agg::drop_shadow(&mut ren_base, &card, Rgba8::new(0,0,0,128), 4.0, 4.0, 3.0);
+//!
+//!     let mut ras = agg::RasterizerScanline::new();
+//!     ras.add_path(&card);
+//!     let mut ren = agg::RenderingScanlineAASolid::with_base(&mut ren_base);
+//!     ren.color(Rgba8::new(255,255,200,255));
+//!     agg::render_scanlines(&mut ras, &mut ren);
+
+use crate::color::*;
+use crate::paths::{Vertex,PathCommand};
+use crate::pixfmt::Pixfmt;
+use crate::base::RenderingBase;
+use crate::paths::bounding_rect;
+use crate::math::multiply_u8;
+use crate::raster::RasterizerScanline;
+use crate::render::RenderingScanlineAASolid;
+use crate::render::render_scanlines;
+use crate::blur::recursive_blur;
+use crate::Color;
+use crate::Pixel;
+use crate::Render;
+use crate::VertexSource;
+
+/// Vertex source offset by (`dx`,`dy`)
+struct Offset<'a,VS> {
+    src: &'a VS,
+    dx: f64,
+    dy: f64,
+}
+
+impl<VS> VertexSource for Offset<'_,VS> where VS: VertexSource {
+    fn xconvert(&self) -> Vec<Vertex<f64>> {
+        self.src.xconvert().into_iter().map(|v| match v.cmd {
+            PathCommand::MoveTo | PathCommand::LineTo =>
+                Vertex::new(v.x + self.dx, v.y + self.dy, v.cmd),
+            _ => v,
+        }).collect()
+    }
+}
+
+/// Draw a shadow of `path` offset by (`dx`,`dy`) and blurred by `radius`
+///
+/// The shadow is tinted with `color`, including its alpha
+pub fn drop_shadow<T,VS,C>(ren_base: &mut RenderingBase<T>, path: &VS, color: C,
+                           dx: f64, dy: f64, radius: f64)
+    where T: Pixel, VS: VertexSource, C: Color
+{
+    let (w, h) = (ren_base.pixf.width() as f64, ren_base.pixf.height() as f64);
+
+    // Region of the shadow, limited to the image
+    let r = match bounding_rect(path) {
+        Some(r) => r,
+        None => return,
+    };
+    let grow = (3.0 * radius.max(0.0)).ceil() + 1.0;
+    let x1 = (r.x1() + dx - grow).floor().max(0.0);
+    let y1 = (r.y1() + dy - grow).floor().max(0.0);
+    let x2 = (r.x2() + dx + grow).ceil().min(w);
+    let y2 = (r.y2() + dy + grow).ceil().min(h);
+    if x1 >= x2 || y1 >= y2 {
+        return;
+    }
+    let (x0, y0) = (x1 as usize, y1 as usize);
+
+    // Coverage of the shape within the region
+    let mut mask = RenderingBase::new(Pixfmt::<Gray8>::new(x2 as usize - x0, y2 as usize - y0));
+    {
+        let mut ras = RasterizerScanline::new();
+        ras.add_path(&Offset { src: path, dx: dx - x1, dy: dy - y1 });
+        let mut ren = RenderingScanlineAASolid::with_base(&mut mask);
+        ren.color(Gray8::new(255));
+        render_scanlines(&mut ras, &mut ren);
+    }
+    recursive_blur(&mut mask.pixf, radius, radius, None);
+
+    // Shadow color with the coverage as alpha
+    let opacity = (color.alpha() * 255.0) as u8;
+    for y in 0 .. mask.pixf.height() {
+        for x in 0 .. mask.pixf.width() {
+            let m = mask.pixf.raw((x,y)).value;
+            if m == 0 {
+                continue;
+            }
+            let (x, y) = (x0 + x, y0 + y);
+            let clip = ren_base.clip_cover(x as i64, y as i64) as u8;
+            let c = Rgba8::new(color.red8(), color.green8(), color.blue8(), m);
+            ren_base.pixf.blend_pix((x,y), c, u64::from(multiply_u8(opacity, clip)));
+        }
+    }
+}
+
+/// Draw a glow around `path`, blurred by `radius`
+///
+/// A glow is a shadow without an offset
+pub fn glow<T,VS,C>(ren_base: &mut RenderingBase<T>, path: &VS, color: C, radius: f64)
+    where T: Pixel, VS: VertexSource, C: Color
+{
+    drop_shadow(ren_base, path, color, 0.0, 0.0, radius);
+}
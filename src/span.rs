@@ -0,0 +1,117 @@
+//! Span Converters
+//!
+//! Span converters apply extra per-pixel processing to the colors
+//!   produced by a [`SpanGenerator`], e.g. alpha modulation
+//!
+//!     use agg::{Pixfmt,Rgb8,Rgba8,RenderingBase,SpanGenerator};
+//!     use agg::{SpanConverter,SpanAlpha};
+//!
+//!     // Solid color "shader"
+//!     struct Solid(Rgba8);
+//!     impl SpanGenerator for Solid {
+//!         type Output = Rgba8;
+//!         fn generate(&mut self, span: &mut [Rgba8], _x: i64, _y: i64) {
+//!             for c in span.iter_mut() {
+//!                 *c = self.0;
+//!             }
+//!         }
+//!     }
+//!     let pix = Pixfmt::<Rgb8>::new(100,100);
+//!     let mut ren_base = RenderingBase::new(pix);
+//!     ren_base.clear(Rgba8::white());
+//!
+//!     let mut span = SpanConverter::new(Solid(Rgba8::black()), SpanAlpha::new(0.5));
+//!     let mut ras = agg::RasterizerScanline::new();
+//!     ras.add_path(&agg::Ellipse::new(50.0, 50.0, 40.0, 40.0, 64));
+//!     agg::render_scanlines_aa(&mut ras, &mut ren_base, &mut span);
+//!
+//! [`SpanGenerator`]: ../trait.SpanGenerator.html
+
+use crate::SpanGenerator;
+use crate::color::Rgba8;
+use crate::color::Rgba8pre;
+use crate::math::multiply_u8;
+
+/// Process a span of colors in place
+///
+/// Any closure `FnMut(&mut [C], i64, i64)` is also a span converter
+pub trait SpanConvert<C> {
+    /// Prepare the converter, called once before rendering the scanlines
+    fn prepare(&mut self) { }
+    /// Convert the colors of the span starting at (`x`,`y`)
+    fn convert(&mut self, span: &mut [C], x: i64, y: i64);
+}
+
+impl<C,F> SpanConvert<C> for F where F: FnMut(&mut [C], i64, i64) {
+    fn convert(&mut self, span: &mut [C], x: i64, y: i64) {
+        self(span, x, y)
+    }
+}
+
+/// Span Generator followed by a Span Converter
+#[derive(Debug)]
+pub struct SpanConverter<G,V> {
+    /// Span Generator
+    pub gen: G,
+    /// Span Converter
+    pub conv: V,
+}
+
+impl<G,V> SpanConverter<G,V> where G: SpanGenerator, V: SpanConvert<G::Output> {
+    /// Create a new Span Converter from a Span Generator and Converter
+    pub fn new(gen: G, conv: V) -> Self {
+        Self { gen, conv }
+    }
+}
+
+impl<G,V> SpanGenerator for SpanConverter<G,V>
+    where G: SpanGenerator, V: SpanConvert<G::Output>
+{
+    type Output = G::Output;
+    fn prepare(&mut self) {
+        self.gen.prepare();
+        self.conv.prepare();
+    }
+    fn generate(&mut self, span: &mut [Self::Output], x: i64, y: i64) {
+        self.gen.generate(span, x, y);
+        self.conv.convert(span, x, y);
+    }
+}
+
+/// Alpha Modulation, scale the alpha of each color by a constant
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub struct SpanAlpha {
+    alpha: u8,
+}
+
+impl SpanAlpha {
+    /// Create new Alpha Modulation with alpha in [0,1]
+    pub fn new(alpha: f64) -> Self {
+        let mut s = Self { alpha: 255 };
+        s.alpha(alpha);
+        s
+    }
+    /// Set alpha in [0,1]
+    pub fn alpha(&mut self, alpha: f64) {
+        self.alpha = (alpha.clamp(0.0, 1.0) * 255.0).round() as u8;
+    }
+}
+
+impl SpanConvert<Rgba8> for SpanAlpha {
+    fn convert(&mut self, span: &mut [Rgba8], _x: i64, _y: i64) {
+        for c in span.iter_mut() {
+            c.a = multiply_u8(c.a, self.alpha);
+        }
+    }
+}
+
+impl SpanConvert<Rgba8pre> for SpanAlpha {
+    fn convert(&mut self, span: &mut [Rgba8pre], _x: i64, _y: i64) {
+        for c in span.iter_mut() {
+            c.r = multiply_u8(c.r, self.alpha);
+            c.g = multiply_u8(c.g, self.alpha);
+            c.b = multiply_u8(c.b, self.alpha);
+            c.a = multiply_u8(c.a, self.alpha);
+        }
+    }
+}
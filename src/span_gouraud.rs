@@ -0,0 +1,310 @@
+//! Gouraud Shading
+//!
+//! Triangles with colors interpolated from the colors at each vertex.
+//!   The triangle is both the [`VertexSource`] to rasterize and the
+//!   [`SpanGenerator`] producing the colors
+//!
+//!     use agg::{Pixfmt,Rgb8,Rgba8,RenderingBase,SpanGouraudRgba};
+//!
+//!     let pix = Pixfmt::<Rgb8>::new(100,100);
+//!     let mut ren_base = RenderingBase::new(pix);
+//!     ren_base.clear(Rgba8::white());
+//!
+//!     let mut tri = SpanGouraudRgba::new(Rgba8::new(255,0,0,255),
+//!                                        Rgba8::new(0,255,0,255),
+//!                                        Rgba8::new(0,0,255,255),
+//!                                        (10.0, 10.0), (90.0, 30.0), (40.0, 90.0),
+//!                                        0.0);
+//!     let mut ras = agg::RasterizerScanline::new();
+//!     ras.add_path(&tri);
+//!     agg::render_scanlines_aa(&mut ras, &mut ren_base, &mut tri);
+//!
+//! [`VertexSource`]: ../trait.VertexSource.html
+//! [`SpanGenerator`]: ../trait.SpanGenerator.html
+
+use crate::paths::Vertex;
+use crate::paths::cross;
+use crate::color::Rgba8;
+use crate::Color;
+use crate::VertexSource;
+use crate::SpanGenerator;
+
+/// Subpixel shift of span x coordinates
+const SUBPIXEL_SHIFT : i64 = 4;
+/// Subpixel scale of span x coordinates
+const SUBPIXEL_SCALE : i64 = 1 << SUBPIXEL_SHIFT;
+
+/// Triangle vertex and color
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+struct Coord {
+    x: f64,
+    y: f64,
+    color: Rgba8,
+}
+
+/// Gouraud shaded triangle with Rgba8 colors
+///
+/// A non-zero dilation `d` expands the triangle by `d` pixels, removing
+///   seams between adjacent triangles.  The shape rasterized is then a
+///   hexagon with the vertices moved to the intersections of the expanded
+///   edges.  A value of 0.175 is a good choice
+#[derive(Debug,Default,Clone)]
+pub struct SpanGouraudRgba {
+    /// Vertices and colors
+    coord: [Coord;3],
+    /// Outline of the, possibly dilated, triangle
+    vertices: Vec<Vertex<f64>>,
+    /// Left and right sides are swapped
+    swap: bool,
+    /// y value of the middle vertex
+    y2: i64,
+    /// Long edge, from top to bottom vertex
+    rgba1: RgbaCalc,
+    /// Top edge, from top to middle vertex
+    rgba2: RgbaCalc,
+    /// Bottom edge, from middle to bottom vertex
+    rgba3: RgbaCalc,
+}
+
+impl SpanGouraudRgba {
+    /// Create a new Gouraud triangle with colors `c1`, `c2` and `c3`
+    ///   at vertices `p1`, `p2` and `p3` and dilation `d`
+    pub fn new<C: Color>(c1: C, c2: C, c3: C,
+                         p1: (f64,f64), p2: (f64,f64), p3: (f64,f64),
+                         d: f64) -> Self {
+        let mut s = Self::default();
+        s.colors(c1, c2, c3);
+        s.triangle(p1, p2, p3, d);
+        s
+    }
+    /// Set colors of the vertices
+    pub fn colors<C: Color>(&mut self, c1: C, c2: C, c3: C) {
+        self.coord[0].color = Rgba8::from_trait(c1);
+        self.coord[1].color = Rgba8::from_trait(c2);
+        self.coord[2].color = Rgba8::from_trait(c3);
+    }
+    /// Set vertices of the triangle and dilation `d`
+    pub fn triangle(&mut self, p1: (f64,f64), p2: (f64,f64), p3: (f64,f64), d: f64) {
+        for (c, p) in self.coord.iter_mut().zip([p1, p2, p3].iter()) {
+            c.x = p.0;
+            c.y = p.1;
+        }
+        let mut v : Vec<_> = self.coord.iter()
+            .map(|c| Vertex::line_to(c.x, c.y))
+            .collect();
+        if d != 0.0 {
+            v = dilate_triangle(&v[0], &v[1], &v[2], d);
+            // Triangle vertices at the intersections of the dilated edges
+            let pts = [(4, 0), (0, 2), (2, 4)];
+            for (c, &(i, j)) in self.coord.iter_mut().zip(pts.iter()) {
+                if let Some((x, y)) = intersection(&v[i], &v[i+1], &v[j], &v[j+1]) {
+                    c.x = x;
+                    c.y = y;
+                }
+            }
+        }
+        v[0] = Vertex::move_to(v[0].x, v[0].y);
+        v.push(Vertex::close_polygon(0.0, 0.0));
+        self.vertices = v;
+    }
+    /// Vertices sorted by y value
+    fn arrange_vertices(&self) -> [Coord;3] {
+        let mut coord = self.coord;
+        if coord[0].y > coord[2].y {
+            coord.swap(0, 2);
+        }
+        if coord[0].y > coord[1].y {
+            coord.swap(0, 1);
+        }
+        if coord[1].y > coord[2].y {
+            coord.swap(1, 2);
+        }
+        coord
+    }
+}
+
+impl VertexSource for SpanGouraudRgba {
+    fn xconvert(&self) -> Vec<Vertex<f64>> {
+        self.vertices.clone()
+    }
+}
+
+impl SpanGenerator for SpanGouraudRgba {
+    type Output = Rgba8;
+    fn prepare(&mut self) {
+        let coord = self.arrange_vertices();
+        self.y2 = coord[1].y as i64;
+        let (p0, p1, p2) = (Vertex::line_to(coord[0].x, coord[0].y),
+                            Vertex::line_to(coord[1].x, coord[1].y),
+                            Vertex::line_to(coord[2].x, coord[2].y));
+        self.swap = cross(&p0, &p2, &p1) < 0.0;
+        self.rgba1 = RgbaCalc::new(&coord[0], &coord[2]);
+        self.rgba2 = RgbaCalc::new(&coord[0], &coord[1]);
+        self.rgba3 = RgbaCalc::new(&coord[1], &coord[2]);
+    }
+    fn generate(&mut self, span: &mut [Rgba8], x: i64, y: i64) {
+        self.rgba1.calc(y as f64);
+        let pc2 = if y <= self.y2 {
+            // Top part of the triangle
+            self.rgba2.calc(y as f64 + self.rgba2.dy_inv);
+            &self.rgba2
+        } else {
+            // Bottom part of the triangle
+            self.rgba3.calc(y as f64 - self.rgba3.dy_inv);
+            &self.rgba3
+        };
+        let (pc1, pc2) = if self.swap {
+            (pc2, &self.rgba1)
+        } else {
+            (&self.rgba1, pc2)
+        };
+
+        // Interpolators start at the left edge, roll them back to x
+        let mut nlen = (pc2.x - pc1.x).abs();
+        if nlen <= 0 {
+            nlen = 1;
+        }
+        let mut r = DdaLine::new(pc1.r, pc2.r, nlen);
+        let mut g = DdaLine::new(pc1.g, pc2.g, nlen);
+        let mut b = DdaLine::new(pc1.b, pc2.b, nlen);
+        let mut a = DdaLine::new(pc1.a, pc2.a, nlen);
+        let start = pc1.x - (x << SUBPIXEL_SHIFT);
+        r.inc(-start);
+        g.inc(-start);
+        b.inc(-start);
+        a.inc(-start);
+
+        // Values outside of the edges may overflow
+        for c in span.iter_mut() {
+            *c = Rgba8::new(r.clamp(), g.clamp(), b.clamp(), a.clamp());
+            r.inc(SUBPIXEL_SCALE);
+            g.inc(SUBPIXEL_SCALE);
+            b.inc(SUBPIXEL_SCALE);
+            a.inc(SUBPIXEL_SCALE);
+        }
+    }
+}
+
+/// Color and x value along a triangle edge
+#[derive(Debug,Default,Copy,Clone,PartialEq)]
+struct RgbaCalc {
+    x1: f64,
+    y1: f64,
+    dx: f64,
+    dy_inv: f64,
+    r1: i64,
+    g1: i64,
+    b1: i64,
+    a1: i64,
+    dr: i64,
+    dg: i64,
+    db: i64,
+    da: i64,
+    r: i64,
+    g: i64,
+    b: i64,
+    a: i64,
+    x: i64,
+}
+
+impl RgbaCalc {
+    /// Edge from `c1` to `c2`
+    fn new(c1: &Coord, c2: &Coord) -> Self {
+        let dy = c2.y - c1.y;
+        let (r1, g1) = (i64::from(c1.color.r), i64::from(c1.color.g));
+        let (b1, a1) = (i64::from(c1.color.b), i64::from(c1.color.a));
+        Self {
+            x1: c1.x - 0.5,
+            y1: c1.y - 0.5,
+            dx: c2.x - c1.x,
+            dy_inv: if dy < 1e-5 { 1e5 } else { 1.0 / dy },
+            r1, g1, b1, a1,
+            dr: i64::from(c2.color.r) - r1,
+            dg: i64::from(c2.color.g) - g1,
+            db: i64::from(c2.color.b) - b1,
+            da: i64::from(c2.color.a) - a1,
+            .. Self::default()
+        }
+    }
+    /// Compute color and x value at `y`
+    fn calc(&mut self, y: f64) {
+        let k = ((y - self.y1) * self.dy_inv).clamp(0.0, 1.0);
+        self.r = self.r1 + (self.dr as f64 * k).round() as i64;
+        self.g = self.g1 + (self.dg as f64 * k).round() as i64;
+        self.b = self.b1 + (self.db as f64 * k).round() as i64;
+        self.a = self.a1 + (self.da as f64 * k).round() as i64;
+        self.x = ((self.x1 + self.dx * k) * SUBPIXEL_SCALE as f64).round() as i64;
+    }
+}
+
+/// Linear interpolation from y1 to y2 in `count` steps
+///
+/// Values are stored with 14 bits of fraction
+#[derive(Debug,Copy,Clone,PartialEq)]
+struct DdaLine {
+    y: i64,
+    inc: i64,
+    dy: i64,
+}
+
+impl DdaLine {
+    const SHIFT : i64 = 14;
+    fn new(y1: i64, y2: i64, count: i64) -> Self {
+        Self { y: y1, inc: ((y2 - y1) << Self::SHIFT) / count, dy: 0 }
+    }
+    /// Advance `n` steps
+    fn inc(&mut self, n: i64) {
+        self.dy += self.inc * n;
+    }
+    /// Current value
+    fn y(&self) -> i64 {
+        self.y + (self.dy >> Self::SHIFT)
+    }
+    /// Value limited to [0,255]
+    fn clamp(&self) -> u8 {
+        self.y().clamp(0, 255) as u8
+    }
+}
+
+/// Expand triangle edges outward by `d`
+///
+/// Returns the six vertices of the two end points of each moved edge
+fn dilate_triangle(p1: &Vertex<f64>, p2: &Vertex<f64>, p3: &Vertex<f64>,
+                   d: f64) -> Vec<Vertex<f64>> {
+    let mut d1 = (0.0, 0.0);
+    let mut d2 = (0.0, 0.0);
+    let mut d3 = (0.0, 0.0);
+    let loc = cross(p1, p2, p3);
+    if loc.abs() > 1.0e-30 {
+        let d = if loc > 0.0 { -d } else { d };
+        d1 = orthogonal(d, p1, p2);
+        d2 = orthogonal(d, p2, p3);
+        d3 = orthogonal(d, p3, p1);
+    }
+    vec![Vertex::line_to(p1.x + d1.0, p1.y + d1.1),
+         Vertex::line_to(p2.x + d1.0, p2.y + d1.1),
+         Vertex::line_to(p2.x + d2.0, p2.y + d2.1),
+         Vertex::line_to(p3.x + d2.0, p3.y + d2.1),
+         Vertex::line_to(p3.x + d3.0, p3.y + d3.1),
+         Vertex::line_to(p1.x + d3.0, p1.y + d3.1)]
+}
+
+/// Offset of length `d` orthogonal to the line from `p1` to `p2`
+fn orthogonal(d: f64, p1: &Vertex<f64>, p2: &Vertex<f64>) -> (f64, f64) {
+    let dx = p2.x - p1.x;
+    let dy = p2.y - p1.y;
+    let len = dx.hypot(dy);
+    (d * dy / len, -d * dx / len)
+}
+
+/// Intersection of the lines through a,b and c,d
+fn intersection(a: &Vertex<f64>, b: &Vertex<f64>,
+                c: &Vertex<f64>, d: &Vertex<f64>) -> Option<(f64, f64)> {
+    let num = (a.y - c.y) * (d.x - c.x) - (a.x - c.x) * (d.y - c.y);
+    let den = (b.x - a.x) * (d.y - c.y) - (b.y - a.y) * (d.x - c.x);
+    if den.abs() < 1.0e-30 {
+        return None;
+    }
+    let r = num / den;
+    Some((a.x + r * (b.x - a.x), a.y + r * (b.y - a.y)))
+}
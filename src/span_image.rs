@@ -0,0 +1,202 @@
+//! Image Span Generators
+//!
+//! Span generators sampling a source image through a [`Transform`].  The
+//!   transform maps pixels of the destination to the source image and is
+//!   usually the inverse of the transform used to place the image.  Pixels
+//!   outside of the source image are transparent, or defined by an
+//!   [`ImageAccessor`]
+//!
+//!     use agg::{Pixfmt,Rgb8,Rgba8,RenderingBase,Transform};
+//!     use agg::{SpanImageFilter,ImageFilterLut,ImageFilterBicubic};
+//!
+//!     let src = Pixfmt::<Rgba8>::new(50,50);
+//!     let mut ren_base = RenderingBase::new(Pixfmt::<Rgb8>::new(100,100));
+//!
+//!     // Image scaled by 2 and rotated by 30 degrees about (50,50)
+//!     let mut mtx = Transform::new_translate(-25.0, -25.0);
+//!     mtx.scale(2.0, 2.0);
+//!     mtx.rotate(30.0_f64.to_radians());
+//!     mtx.translate(50.0, 50.0);
+//!     mtx.invert();
+//!
+//!     let lut = ImageFilterLut::new(&ImageFilterBicubic{});
+//!     let mut span = SpanImageFilter::new(&src, mtx, lut);
+//!     let mut ras = agg::RasterizerScanline::new();
+//!     ras.add_path(&agg::Ellipse::new(50.0, 50.0, 40.0, 40.0, 64));
+//!     agg::render_scanlines_aa(&mut ras, &mut ren_base, &mut span);
+//!
+//! [`Transform`]: ../transform/struct.Transform.html
+//! [`ImageAccessor`]: ../image_accessors/struct.ImageAccessor.html
+
+use crate::color::Rgba8;
+use crate::render::Interpolator;
+use crate::image_filters::ImageFilterLut;
+use crate::image_filters::{IMAGE_SUBPIXEL_SHIFT,IMAGE_SUBPIXEL_SCALE,IMAGE_SUBPIXEL_MASK};
+use crate::image_filters::{IMAGE_FILTER_SHIFT,IMAGE_FILTER_SCALE};
+use crate::Transform;
+use crate::SpanGenerator;
+use crate::image_accessors::ImageSource;
+
+/// Sum of weighted colors
+///
+/// Color components are weighted by alpha, producing correctly
+///   filtered colors at transparent edges
+#[derive(Debug,Default,Copy,Clone)]
+struct Accum {
+    r: i64,
+    g: i64,
+    b: i64,
+    a: i64,
+}
+impl Accum {
+    fn add(&mut self, weight: i64, c: Rgba8) {
+        let wa = weight * i64::from(c.a);
+        self.r += wa * i64::from(c.r);
+        self.g += wa * i64::from(c.g);
+        self.b += wa * i64::from(c.b);
+        self.a += wa;
+    }
+    /// Color from sums with weights scaled by 1 << `shift`
+    fn color(&self, shift: i64) -> Rgba8 {
+        if self.a <= 0 {
+            return Rgba8::new(0,0,0,0);
+        }
+        let v = |x: i64| ((x + self.a / 2) / self.a).clamp(0, 255) as u8;
+        let a = ((self.a + (1 << (shift - 1))) >> shift).clamp(0, 255) as u8;
+        Rgba8::new(v(self.r), v(self.g), v(self.b), a)
+    }
+}
+
+/// Image Span Generator, Nearest Neighbor
+#[derive(Debug)]
+pub struct SpanImageFilterNN<S> {
+    src: S,
+    trans: Transform,
+}
+
+impl<S> SpanImageFilterNN<S> where S: ImageSource {
+    /// Create new span generator from source image and Transform
+    pub fn new(src: S, trans: Transform) -> Self {
+        Self { src, trans }
+    }
+    /// Set the Transform, from destination to source image
+    pub fn transform(&mut self, trans: Transform) {
+        self.trans = trans;
+    }
+}
+
+impl<S> SpanGenerator for SpanImageFilterNN<S> where S: ImageSource {
+    type Output = Rgba8;
+    fn generate(&mut self, span: &mut [Rgba8], x: i64, y: i64) {
+        let mut interp = Interpolator::new(self.trans);
+        interp.begin(x as f64 + 0.5, y as f64 + 0.5, span.len());
+        for c in span.iter_mut() {
+            let (x, y) = interp.coordinates();
+            *c = self.src.pixel(x >> IMAGE_SUBPIXEL_SHIFT, y >> IMAGE_SUBPIXEL_SHIFT);
+            interp.inc();
+        }
+    }
+}
+
+/// Image Span Generator, Bilinear
+#[derive(Debug)]
+pub struct SpanImageFilterBilinear<S> {
+    src: S,
+    trans: Transform,
+}
+
+impl<S> SpanImageFilterBilinear<S> where S: ImageSource {
+    /// Create new span generator from source image and Transform
+    pub fn new(src: S, trans: Transform) -> Self {
+        Self { src, trans }
+    }
+    /// Set the Transform, from destination to source image
+    pub fn transform(&mut self, trans: Transform) {
+        self.trans = trans;
+    }
+}
+
+impl<S> SpanGenerator for SpanImageFilterBilinear<S> where S: ImageSource {
+    type Output = Rgba8;
+    fn generate(&mut self, span: &mut [Rgba8], x: i64, y: i64) {
+        let mut interp = Interpolator::new(self.trans);
+        interp.begin(x as f64 + 0.5, y as f64 + 0.5, span.len());
+        let scale = IMAGE_SUBPIXEL_SCALE;
+        for c in span.iter_mut() {
+            let (x_hr, y_hr) = interp.coordinates();
+            let x_hr = x_hr - scale / 2;
+            let y_hr = y_hr - scale / 2;
+            let x_lr = x_hr >> IMAGE_SUBPIXEL_SHIFT;
+            let y_lr = y_hr >> IMAGE_SUBPIXEL_SHIFT;
+            let x_hr = x_hr & IMAGE_SUBPIXEL_MASK;
+            let y_hr = y_hr & IMAGE_SUBPIXEL_MASK;
+
+            let mut acc = Accum::default();
+            acc.add((scale - x_hr) * (scale - y_hr), self.src.pixel(x_lr,   y_lr));
+            acc.add(x_hr * (scale - y_hr),           self.src.pixel(x_lr+1, y_lr));
+            acc.add((scale - x_hr) * y_hr,           self.src.pixel(x_lr,   y_lr+1));
+            acc.add(x_hr * y_hr,                     self.src.pixel(x_lr+1, y_lr+1));
+            *c = acc.color(IMAGE_SUBPIXEL_SHIFT * 2);
+            interp.inc();
+        }
+    }
+}
+
+/// Image Span Generator, using an Image Filter
+///
+/// Any filter from [`image_filters`] may be used, see [`ImageFilterLut`]
+///
+/// [`image_filters`]: ../image_filters/index.html
+/// [`ImageFilterLut`]: ../image_filters/struct.ImageFilterLut.html
+#[derive(Debug)]
+pub struct SpanImageFilter<S> {
+    src: S,
+    trans: Transform,
+    lut: ImageFilterLut,
+}
+
+impl<S> SpanImageFilter<S> where S: ImageSource {
+    /// Create new span generator from source image, Transform and filter
+    pub fn new(src: S, trans: Transform, lut: ImageFilterLut) -> Self {
+        Self { src, trans, lut }
+    }
+    /// Set the Transform, from destination to source image
+    pub fn transform(&mut self, trans: Transform) {
+        self.trans = trans;
+    }
+    /// Set the filter
+    pub fn filter(&mut self, lut: ImageFilterLut) {
+        self.lut = lut;
+    }
+}
+
+impl<S> SpanGenerator for SpanImageFilter<S> where S: ImageSource {
+    type Output = Rgba8;
+    fn generate(&mut self, span: &mut [Rgba8], x: i64, y: i64) {
+        let mut interp = Interpolator::new(self.trans);
+        interp.begin(x as f64 + 0.5, y as f64 + 0.5, span.len());
+        let diameter = self.lut.diameter() as i64;
+        let start = self.lut.start();
+        for c in span.iter_mut() {
+            let (x_hr, y_hr) = interp.coordinates();
+            let x_hr = x_hr - IMAGE_SUBPIXEL_SCALE / 2;
+            let y_hr = y_hr - IMAGE_SUBPIXEL_SCALE / 2;
+            let x_lr = (x_hr >> IMAGE_SUBPIXEL_SHIFT) + start;
+            let y_lr = (y_hr >> IMAGE_SUBPIXEL_SHIFT) + start;
+            let x_fract = x_hr & IMAGE_SUBPIXEL_MASK;
+            let y_fract = y_hr & IMAGE_SUBPIXEL_MASK;
+
+            let mut acc = Accum::default();
+            for j in 0 .. diameter {
+                let wy = self.lut.weight(y_fract, j);
+                for i in 0 .. diameter {
+                    let wx = self.lut.weight(x_fract, i);
+                    let w = (wy * wx + IMAGE_FILTER_SCALE / 2) >> IMAGE_FILTER_SHIFT;
+                    acc.add(w, self.src.pixel(x_lr + i, y_lr + j));
+                }
+            }
+            *c = acc.color(IMAGE_FILTER_SHIFT);
+            interp.inc();
+        }
+    }
+}
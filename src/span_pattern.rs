@@ -0,0 +1,91 @@
+//! Pattern Span Generator
+//!
+//! Fills shapes with a tiled image
+//!
+//!     use agg::{Pixfmt,Rgb8,Rgba8,Pixel,RenderingBase,SpanPattern,Wrap};
+//!
+//!     let mut tile = Pixfmt::<Rgba8>::new(8,8);
+//!     tile.fill(Rgba8::new(0,128,0,255));
+//!     let mut ren_base = RenderingBase::new(Pixfmt::<Rgb8>::new(100,100));
+//!
+//!     let mut span = SpanPattern::new(&tile, 4, 0);
+//!     span.wrap(Wrap::Reflect, Wrap::Repeat);
+//!     let mut ras = agg::RasterizerScanline::new();
+//!     ras.add_path(&agg::Ellipse::new(50.0, 50.0, 40.0, 40.0, 64));
+//!     agg::render_scanlines_aa(&mut ras, &mut ren_base, &mut span);
+
+use crate::color::Rgba8;
+use crate::render::Interpolator;
+use crate::image_accessors::{ImageAccessor,ImageSource,Wrap};
+use crate::image_filters::IMAGE_SUBPIXEL_SHIFT;
+use crate::Transform;
+use crate::SpanGenerator;
+use crate::Source;
+use crate::Pixel;
+
+/// Pattern Span Generator
+///
+/// Tiles the source image, offset by (`offset_x`,`offset_y`) pixels.  An
+///   optional [`Transform`], from destination to pattern, rotates or scales
+///   the pattern
+///
+/// [`Transform`]: ../transform/struct.Transform.html
+#[derive(Debug)]
+pub struct SpanPattern<'a,S> {
+    src: ImageAccessor<'a,S>,
+    offset_x: i64,
+    offset_y: i64,
+    trans: Option<Transform>,
+}
+
+impl<'a,S> SpanPattern<'a,S> where S: Source + Pixel {
+    /// Create new pattern span generator, repeated in both directions
+    pub fn new(src: &'a S, offset_x: i64, offset_y: i64) -> Self {
+        let src = ImageAccessor::new(src, Wrap::Repeat, Wrap::Repeat);
+        Self { src, offset_x, offset_y, trans: None }
+    }
+    /// Set the wrap modes for x and y
+    pub fn wrap(&mut self, wrap_x: Wrap, wrap_y: Wrap) {
+        self.src.wrap(wrap_x, wrap_y);
+    }
+    /// Set the pattern offset in x
+    pub fn offset_x(&mut self, offset_x: i64) {
+        self.offset_x = offset_x;
+    }
+    /// Set the pattern offset in y
+    pub fn offset_y(&mut self, offset_y: i64) {
+        self.offset_y = offset_y;
+    }
+    /// Set the Transform, from destination to pattern
+    pub fn transform(&mut self, trans: Transform) {
+        self.trans = Some(trans);
+    }
+    /// Remove the Transform
+    pub fn clear_transform(&mut self) {
+        self.trans = None;
+    }
+}
+
+impl<S> SpanGenerator for SpanPattern<'_,S> where S: Source + Pixel {
+    type Output = Rgba8;
+    fn generate(&mut self, span: &mut [Rgba8], x: i64, y: i64) {
+        match self.trans {
+            None => {
+                let y = y + self.offset_y;
+                for (i, c) in span.iter_mut().enumerate() {
+                    *c = self.src.pixel(x + i as i64 + self.offset_x, y);
+                }
+            },
+            Some(trans) => {
+                let mut interp = Interpolator::new(trans);
+                interp.begin(x as f64 + 0.5, y as f64 + 0.5, span.len());
+                for c in span.iter_mut() {
+                    let (x, y) = interp.coordinates();
+                    *c = self.src.pixel((x >> IMAGE_SUBPIXEL_SHIFT) + self.offset_x,
+                                        (y >> IMAGE_SUBPIXEL_SHIFT) + self.offset_y);
+                    interp.inc();
+                }
+            }
+        }
+    }
+}
@@ -26,6 +26,7 @@ use crate::paths::Vertex;
 use crate::paths::len;
 use crate::paths::cross;
 use crate::paths::split;
+use crate::paths::finite_vertices;
 
 use crate::VertexSource;
 use std::f64::consts::PI;
@@ -542,10 +543,12 @@ impl<T> Stroke<T> where T: VertexSource {
     ///
     /// There is lots of logic here and probably overly complex
     ///
+    /// Vertices with a NaN or infinite coordinate are skipped
+    ///
     fn stroke(&self) -> Vec<Vertex<f64>> {
         let mut all_out = vec![];
-        // Get verticies from Vertex Source
-        let v0 = &self.source.xconvert();
+        // Get verticies from Vertex Source, without non-finite values
+        let v0 = &finite_vertices(&self.source.xconvert());
         // Split and loop along unique paths, ended by MoveTo's
         let pairs = split(&v0);
         for (m1,m2) in pairs {
@@ -798,7 +801,8 @@ fn clean_path(v: &[Vertex<f64>]) -> Vec<Vertex<f64>>{
         // Get Last LineTo Command
         let i = match last_line_to(&out) {
             Some(i) => i,
-            None => panic!("Missing Last Line To"),
+            // Degenerate path, all points are the same
+            None => return vec![],
         };
         let last = out[i];
         // If last point and first are **NOT** the same, done
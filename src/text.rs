@@ -5,6 +5,7 @@ use crate::base::RenderingBase;
 use crate::color::Rgba8;
 use crate::Pixel;
 use crate::ft;
+use crate::AggError;
 
 #[derive(Debug,PartialEq)]
 enum TextStatus {
@@ -166,31 +167,63 @@ impl GsvDefaultFont {
 
 
 
-fn string_width(txt: &str, font: &ft::Face) -> f64 {
+fn string_width(txt: &str, font: &ft::Face) -> Result<f64, AggError> {
     let mut width = 0.0;
     for c in txt.chars() {
         let glyph_index = font.get_char_index(c as usize);
-        font.load_glyph(glyph_index, ft::face::LoadFlag::DEFAULT).unwrap();
+        font.load_glyph(glyph_index, ft::face::LoadFlag::DEFAULT)?;
         let glyph = font.glyph();
-        glyph.render_glyph(ft::RenderMode::Normal).unwrap();
+        glyph.render_glyph(ft::RenderMode::Normal)?;
         let adv = glyph.advance();
         width += adv.x as f64
     }
-    width / 64.0
+    Ok(width / 64.0)
 }
 
+/// Size metrics of the font, an error if no size has been set
+fn size_metrics(font: &ft::Face) -> Result<ft::ffi::FT_Size_Metrics, AggError> {
+    font.size_metrics().ok_or(AggError::Ft(ft::error::Error::InvalidSizeHandle))
+}
+
+/// Line height of the font
+///
+/// # Panics
+///
+/// Panics if the font size has not been set, see try_line_height()
 pub fn line_height(font: &ft::Face) -> f64 {
-    let met = font.size_metrics().unwrap();
-    (met.ascender - met.descender) as f64 / 64.0
+    match try_line_height(font) {
+        Ok(h) => h,
+        Err(err) => panic!("{}", err),
+    }
 }
 
+/// Line height of the font, an error if the font size has not been set
+pub fn try_line_height(font: &ft::Face) -> Result<f64, AggError> {
+    let met = size_metrics(font)?;
+    Ok((met.ascender - met.descender) as f64 / 64.0)
+}
+
+/// Draw text centered at (x,y)
+///
+/// # Panics
+///
+/// Panics on a FreeType error, see try_draw_text()
 pub fn draw_text<T>(txt: &str, x: i64, y: i64, font: &ft::Face, ren_base: &mut RenderingBase<T>)
     where T: Pixel
+{
+    if let Err(err) = try_draw_text(txt, x, y, font, ren_base) {
+        panic!("{}", err);
+    }
+}
+
+/// Draw text centered at (x,y), returning any FreeType error
+pub fn try_draw_text<T>(txt: &str, x: i64, y: i64, font: &ft::Face, ren_base: &mut RenderingBase<T>) -> Result<(), AggError>
+    where T: Pixel
 {
     let color = Rgba8::new(0,0,0,255);
     let (mut x, mut y) = (x,y);
-    let width  = string_width(txt, font);
-    let height = line_height(font);
+    let width  = string_width(txt, font)?;
+    let height = try_line_height(font)?;
     // Shift to center justification, x and y
     let dx = (width / 2.0).round() as i64;
     let dy = (height / 2.0).round() as i64;
@@ -198,8 +231,8 @@ pub fn draw_text<T>(txt: &str, x: i64, y: i64, font: &ft::Face, ren_base: &mut R
     y += dy;
     for c in txt.chars() {
         let glyph_index = font.get_char_index(c as usize);
-        font.load_glyph(glyph_index, ft::face::LoadFlag::DEFAULT).unwrap();
-        font.glyph().render_glyph(ft::RenderMode::Normal).unwrap();
+        font.load_glyph(glyph_index, ft::face::LoadFlag::DEFAULT)?;
+        font.glyph().render_glyph(ft::RenderMode::Normal)?;
         let g = font.glyph().bitmap();
         let left = font.glyph().bitmap_left() as i64;
         let top  = font.glyph().bitmap_top() as i64;
@@ -215,26 +248,12 @@ pub fn draw_text<T>(txt: &str, x: i64, y: i64, font: &ft::Face, ren_base: &mut R
         x += (adv.x as f64 / 64.0).round() as i64;
         y += (adv.y as f64 / 64.0).round() as i64;
     }
+    Ok(())
 }
 
 
-#[derive(Debug)]
-pub enum AggFontError {
-    /// Freetype Error
-    Ft(ft::error::Error),
-    Io(String)
-}
-
-impl From<ft::error::Error> for AggFontError {
-    fn from(err: ft::error::Error) -> Self {
-        AggFontError::Ft(err)
-    }
-}
-impl From<String> for AggFontError {
-    fn from(err: String) -> Self {
-        AggFontError::Io(err)
-    }
-}
+/// Font Errors, see AggError
+pub type AggFontError = AggError;
 
 pub fn font(name: &str) -> Result<ft::Face, AggFontError> {
     //let prop = font_loader::system_fonts::FontPropertyBuilder::new().family(name).build();
@@ -281,10 +300,22 @@ impl<'a> Label<'a> {
             }
         )
     }
+    /// Size of the label, (width, height)
+    ///
+    /// # Panics
+    ///
+    /// Panics on a FreeType error, see try_size()
     pub fn size(&self) -> (f64, f64) {
-        let w = string_width(&self.txt, self.font);
-        let h = line_height(self.font);
-        (w, h)
+        match self.try_size() {
+            Ok(size) => size,
+            Err(err) => panic!("{}", err),
+        }
+    }
+    /// Size of the label, (width, height), returning any FreeType error
+    pub fn try_size(&self) -> Result<(f64, f64), AggError> {
+        let w = string_width(&self.txt, self.font)?;
+        let h = try_line_height(self.font)?;
+        Ok((w, h))
     }
     pub fn xalign(mut self, xalign: XAlign) -> Self {
         self.xa = xalign;
@@ -298,12 +329,25 @@ impl<'a> Label<'a> {
         self.color = color;
         self
     }
+    /// Draw the label
+    ///
+    /// # Panics
+    ///
+    /// Panics on a FreeType error, see try_draw()
     pub fn draw<T>(&mut self, ren: &mut RenderingBase<T>)
         where T: Pixel
+    {
+        if let Err(err) = self.try_draw(ren) {
+            panic!("{}", err);
+        }
+    }
+    /// Draw the label, returning any FreeType error
+    pub fn try_draw<T>(&mut self, ren: &mut RenderingBase<T>) -> Result<(), AggError>
+        where T: Pixel
     {
         draw_text_subpixel(&self.txt, self.x, self.y,
                            self.xa, self.ya, self.color,
-                           self.font, ren);
+                           self.font, ren)
     }
 }
 
@@ -314,13 +358,13 @@ fn draw_text_subpixel<T>(txt: &str, x: f64, y: f64,
                          yalign: YAlign,
                          color: Rgba8,
                          font: &ft::Face,
-                         ren_base: &mut RenderingBase<T>)
+                         ren_base: &mut RenderingBase<T>) -> Result<(), AggError>
     where T: Pixel
 {
     let (mut x, mut y) = (x,y);
-    let width  = string_width(txt, font);
+    let width  = string_width(txt, font)?;
 
-    let asc = font.size_metrics().unwrap().ascender as f64 / 64.0;
+    let asc = size_metrics(font)?.ascender as f64 / 64.0;
     x += match xalign {
         XAlign::Left => 0.0,
         XAlign::Right => -width,
@@ -334,15 +378,15 @@ fn draw_text_subpixel<T>(txt: &str, x: f64, y: f64,
 
     for c in txt.chars() {
         let glyph_index = font.get_char_index(c as usize);
-        font.load_glyph(glyph_index, ft::face::LoadFlag::DEFAULT).unwrap();
+        font.load_glyph(glyph_index, ft::face::LoadFlag::DEFAULT)?;
 
-        let glyph = font.glyph().get_glyph().unwrap();
+        let glyph = font.glyph().get_glyph()?;
         let dt = ft::Vector {
             x: ((x - x.floor()) * 64.0).round() as i64,
             y: ((y - y.floor()) * 64.0).round() as i64
         };
-        glyph.transform(None, Some(dt)).unwrap();
-        let g = glyph.to_bitmap(ft::RenderMode::Normal, None).unwrap();
+        glyph.transform(None, Some(dt))?;
+        let g = glyph.to_bitmap(ft::RenderMode::Normal, None)?;
         let left = g.left() as i64;
         let top  = g.top() as i64;
         let bit  = g.bitmap();
@@ -360,6 +404,7 @@ fn draw_text_subpixel<T>(txt: &str, x: f64, y: f64,
         x += glyph.advance_x() as f64 / 65536.0;
         y += glyph.advance_y() as f64 / 65536.0;
     }
+    Ok(())
 }
 
 
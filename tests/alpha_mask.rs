@@ -0,0 +1,142 @@
+extern crate agg;
+
+use agg::{Pixfmt,Rgb8,Rgba8,Gray8,Gray16,Bgr8,Argb8,Pixel,Source,RenderingBase,Render};
+use agg::{AlphaMask,MaskChannel,PixfmtAlphaMask};
+
+/// Mask with a circle at (50,50) of radius 30
+fn circle_mask() -> AlphaMask<Gray8> {
+    let mut mask = AlphaMask::new(Pixfmt::<Gray8>::new(100,100), MaskChannel::Luminance);
+    mask.add_path(&agg::Ellipse::new(50.0, 50.0, 30.0, 30.0, 128), Gray8::new(255));
+    mask
+}
+
+fn rect(x1: f64, y1: f64, x2: f64, y2: f64) -> agg::Path {
+    let mut path = agg::Path::new();
+    path.move_to(x1, y1);
+    path.line_to(x2, y1);
+    path.line_to(x2, y2);
+    path.line_to(x1, y2);
+    path.close_polygon();
+    path
+}
+
+#[test]
+fn alpha_mask() {
+    let mask = circle_mask();
+    assert_eq!(mask.pixel(50, 50), 255);
+    assert_eq!(mask.pixel(5, 5), 0);
+    assert!(mask.pixel(20, 50) > 0 && mask.pixel(20, 50) < 255);
+    // Outside of the mask
+    assert_eq!(mask.pixel(-1, 50), 0);
+    assert_eq!(mask.pixel(50, 100), 0);
+    assert_eq!(mask.combine_pixel(50, 50, 128), 128);
+    assert_eq!(mask.combine_pixel(5, 5, 255), 0);
+
+    let mut pix = Pixfmt::<Rgb8>::new(100,100);
+    pix.fill(Rgba8::white());
+    let mut ren_base = RenderingBase::new(PixfmtAlphaMask::new(pix, &mask));
+    let mut ras = agg::RasterizerScanline::new();
+    ras.add_path(&rect(10.0, 10.0, 90.0, 60.0));
+    let mut ren = agg::RenderingScanlineAASolid::with_base(&mut ren_base);
+    ren.color(Rgba8::new(255,0,0,255));
+    agg::render_scanlines(&mut ras, &mut ren);
+    ren_base.to_file("tests/tmp/alpha_mask.png").unwrap();
+
+    let red = Rgba8::new(255,0,0,255);
+    // Inside the rectangle and the circle
+    assert_eq!(ren_base.pixf.get((50,50)), red);
+    assert_eq!(ren_base.pixf.get((50,25)), red);
+    // Inside the rectangle, outside the circle
+    assert_eq!(ren_base.pixf.get((12,12)), Rgba8::white());
+    assert_eq!(ren_base.pixf.get((88,58)), Rgba8::white());
+    // Inside the circle, outside the rectangle
+    assert_eq!(ren_base.pixf.get((50,70)), Rgba8::white());
+    // Edge of the circle is blended
+    let c = ren_base.pixf.get((20,50));
+    assert!(c.r == 255 && c.g > 0 && c.g < 255, "{:?}", c);
+    assert_eq!(c.g, 255 - mask.pixel(20,50));
+}
+
+#[test]
+fn alpha_mask_channels() {
+    // Alpha channel of an Rgba8 image
+    let mut img = Pixfmt::<Rgba8>::new(10,10);
+    img.fill(Rgba8::new(255,255,255,0));
+    img.set((2,3), Rgba8::new(0,0,0,200));
+    let mask = AlphaMask::new(img, MaskChannel::Alpha);
+    assert_eq!(mask.channel(), MaskChannel::Alpha);
+    assert_eq!(mask.pixel(2,3), 200);
+    assert_eq!(mask.pixel(3,3), 0);
+
+    // Green channel of an Rgb8 image
+    let mut img = Pixfmt::<Rgb8>::new(10,10);
+    img.fill(Rgb8::new(10,20,30));
+    let mask = AlphaMask::new(img, MaskChannel::Green);
+    assert_eq!(mask.pixel(5,5), 20);
+
+    // Luminance of an Rgb8 image
+    let mut img = Pixfmt::<Rgb8>::new(10,10);
+    img.fill(Rgb8::new(0,255,0));
+    img.set((1,1), Rgb8::white());
+    let mask = AlphaMask::new(img, MaskChannel::Luminance);
+    assert_eq!(mask.pixel(0,0), 182);
+    assert_eq!(mask.pixel(1,1), 255);
+    assert_eq!(mask.width(), 10);
+    assert_eq!(mask.height(), 10);
+}
+
+#[test]
+fn alpha_mask_channel_formats() {
+    // Channels follow the color, not the byte order
+    let mut img = Pixfmt::<Bgr8>::new(2,2);
+    img.fill(Rgb8::new(10,20,30));
+    assert_eq!(AlphaMask::new(img, MaskChannel::Red).pixel(1,1), 10);
+    let mut img = Pixfmt::<Bgr8>::new(2,2);
+    img.fill(Rgb8::new(0,255,0));
+    assert_eq!(AlphaMask::new(img, MaskChannel::Luminance).pixel(1,1), 182);
+
+    let mut img = Pixfmt::<Argb8>::new(2,2);
+    img.fill(Rgba8::new(0,255,0,40));
+    assert_eq!(AlphaMask::new(img, MaskChannel::Luminance).pixel(1,1), 182);
+    let mut img = Pixfmt::<Argb8>::new(2,2);
+    img.fill(Rgba8::new(0,255,0,40));
+    assert_eq!(AlphaMask::new(img, MaskChannel::Alpha).pixel(1,1), 40);
+
+    // 16-bit values are reduced to 8 bits
+    let mut img = Pixfmt::<Gray16>::new(2,2);
+    img.fill(Gray16::new(0x80ff));
+    assert_eq!(AlphaMask::new(img, MaskChannel::Luminance).pixel(1,1), 128);
+    let mut img = Pixfmt::<Gray8>::new(2,2);
+    img.fill(Gray8::new(77));
+    assert_eq!(AlphaMask::new(img, MaskChannel::Red).pixel(1,1), 77);
+}
+
+#[test]
+fn alpha_mask_add_path() {
+    // Paths accumulate in the mask
+    let mut mask = AlphaMask::new(Pixfmt::<Gray8>::new(40,40), MaskChannel::Luminance);
+    mask.add_path(&rect(0.0, 0.0, 10.0, 10.0), Gray8::new(255));
+    mask.add_path(&rect(20.0, 20.0, 30.0, 30.0), Gray8::new(100));
+    assert_eq!(mask.pixel(5,5), 255);
+    assert_eq!(mask.pixel(25,25), 100);
+    assert_eq!(mask.pixel(15,15), 0);
+
+    // Copies are masked as well
+    let mut pix = PixfmtAlphaMask::new(Pixfmt::<Rgb8>::new(40,40), &mask);
+    pix.fill(Rgba8::white());
+    assert_eq!(pix.get((5,5)), Rgba8::white());
+    assert_eq!(pix.get((25,25)), Rgba8::new(100,100,100,255));
+    assert_eq!(pix.get((15,15)), Rgba8::black());
+    assert_eq!(pix.mask().pixel(5,5), 255);
+
+    // Copies replace the color, including alpha
+    let mut img = Pixfmt::<Rgba8>::new(40,40);
+    img.fill(Rgba8::white());
+    let mut pix = PixfmtAlphaMask::new(img, &mask);
+    pix.fill(Rgba8::new(255,0,0,0));
+    assert_eq!(pix.get((5,5)), Rgba8::new(255,0,0,0));
+    assert_eq!(pix.get((25,25)), Rgba8::new(255,155,155,155));
+    assert_eq!(pix.get((15,15)), Rgba8::white());
+    pix.set((5,5), Rgba8::new(0,0,255,128));
+    assert_eq!(pix.get((5,5)), Rgba8::new(0,0,255,128));
+}
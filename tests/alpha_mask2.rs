@@ -0,0 +1,111 @@
+extern crate agg;
+
+use agg::{Pixfmt,Rgb8,Rgba8,Gray8,Pixel,Source,RenderingBase,Render};
+use agg::{AlphaMask,MaskChannel,PixfmtAlphaMask};
+
+/// Mask of the left half of the image, with a soft vertical band
+fn half_mask() -> AlphaMask<Gray8> {
+    let mut img = Pixfmt::<Gray8>::new(100,100);
+    for y in 0 .. 100 {
+        img.setn((0,y), 50, Gray8::new(255));
+        img.setn((50,y), 10, Gray8::new(128));
+    }
+    AlphaMask::new(img, MaskChannel::Luminance)
+}
+
+fn base(mask: &AlphaMask<Gray8>) -> RenderingBase<PixfmtAlphaMask<'_,Pixfmt<Rgb8>,Gray8>> {
+    let mut pix = Pixfmt::<Rgb8>::new(100,100);
+    pix.fill(Rgba8::white());
+    RenderingBase::new(PixfmtAlphaMask::new(pix, mask))
+}
+
+fn check(ren_base: &RenderingBase<PixfmtAlphaMask<'_,Pixfmt<Rgb8>,Gray8>>, y: usize) {
+    assert_eq!(ren_base.pixf.get((20,y)), Rgba8::black());
+    assert_eq!(ren_base.pixf.get((55,y)), Rgba8::new(127,127,127,255));
+    assert_eq!(ren_base.pixf.get((80,y)), Rgba8::white());
+}
+
+fn band() -> agg::Path {
+    let mut path = agg::Path::new();
+    path.move_to(10.0, 40.0);
+    path.line_to(90.0, 40.0);
+    path.line_to(90.0, 60.0);
+    path.line_to(10.0, 60.0);
+    path.close_polygon();
+    path
+}
+
+#[test]
+fn alpha_mask2_aa_solid() {
+    let mask = half_mask();
+    let mut ren_base = base(&mask);
+    let mut ras = agg::RasterizerScanline::new();
+    ras.add_path(&band());
+    let mut ren = agg::RenderingScanlineAASolid::with_base(&mut ren_base);
+    ren.color(Rgba8::black());
+    agg::render_scanlines(&mut ras, &mut ren);
+    ren_base.to_file("tests/tmp/alpha_mask2_aa_solid.png").unwrap();
+    check(&ren_base, 50);
+    assert_eq!(ren_base.pixf.get((20,20)), Rgba8::white());
+}
+
+#[test]
+fn alpha_mask2_bin_solid() {
+    let mask = half_mask();
+    let mut ren_base = base(&mask);
+    let mut ras = agg::RasterizerScanline::new();
+    ras.add_path(&band());
+    let mut ren = agg::RenderingScanlineBinSolid::with_base(&mut ren_base);
+    ren.color(Rgba8::black());
+    agg::render_scanlines(&mut ras, &mut ren);
+    check(&ren_base, 50);
+}
+
+#[test]
+fn alpha_mask2_span() {
+    // Colors from a span generator
+    struct Black;
+    impl agg::SpanGenerator for Black {
+        type Output = Rgba8;
+        fn generate(&mut self, span: &mut [Rgba8], _x: i64, _y: i64) {
+            for c in span.iter_mut() {
+                *c = Rgba8::black();
+            }
+        }
+    }
+    let mask = half_mask();
+    let mut ren_base = base(&mask);
+    let mut ras = agg::RasterizerScanline::new();
+    ras.add_path(&band());
+    agg::render_scanlines_aa(&mut ras, &mut ren_base, &mut Black);
+    check(&ren_base, 50);
+}
+
+#[test]
+fn alpha_mask2_outline_aa() {
+    use agg::{RendererOutlineAA,RasterizerOutlineAA,DrawOutline};
+    let mask = half_mask();
+    let mut ren_base = base(&mask);
+    {
+        let mut ren = RendererOutlineAA::with_base(&mut ren_base);
+        ren.color(Rgba8::black());
+        ren.width(10.0);
+        let mut path = agg::Path::new();
+        path.move_to(5.0, 50.0);
+        path.line_to(95.0, 50.0);
+        let mut ras = RasterizerOutlineAA::with_renderer(&mut ren);
+        ras.add_path(&path);
+    }
+    ren_base.to_file("tests/tmp/alpha_mask2_outline_aa.png").unwrap();
+    check(&ren_base, 50);
+}
+
+#[test]
+fn alpha_mask2_clear() {
+    // Clearing the image draws through the mask
+    let mask = half_mask();
+    let mut ren_base = base(&mask);
+    ren_base.clear(Rgba8::black());
+    check(&ren_base, 10);
+    check(&ren_base, 90);
+}
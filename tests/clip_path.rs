@@ -0,0 +1,89 @@
+
+extern crate agg;
+
+use agg::{Pixfmt,Rgb8,Rgba8,RenderingBase,Source,FillingRule};
+
+fn rect(x1: f64, y1: f64, x2: f64, y2: f64) -> agg::Path {
+    let mut path = agg::Path::new();
+    path.move_to(x1, y1);
+    path.line_to(x2, y1);
+    path.line_to(x2, y2);
+    path.line_to(x1, y2);
+    path.close_polygon();
+    path
+}
+
+fn fill(ren_base: &mut RenderingBase<Pixfmt<Rgb8>>, path: &agg::Path, color: Rgba8) {
+    let mut ras = agg::RasterizerScanline::new();
+    ras.add_path(path);
+    agg::render_scanlines_aa_solid(&mut ras, ren_base, color);
+}
+
+#[test]
+fn clip_path() {
+    let pix = Pixfmt::<Rgb8>::new(100,100);
+    let mut ren_base = RenderingBase::new(pix);
+    ren_base.clear(Rgba8::white());
+
+    let circle = agg::Ellipse::new(50.0, 50.0, 30.0, 30.0, 64);
+    ren_base.clip_path(&circle, FillingRule::NonZero);
+    fill(&mut ren_base, &rect(0.0, 0.0, 100.0, 100.0), Rgba8::black());
+    ren_base.to_file("tests/tmp/clip_path.png").unwrap();
+
+    assert_eq!(ren_base.pixf.get((50,50)), Rgba8::black());
+    assert_eq!(ren_base.pixf.get((5,5)), Rgba8::white());
+    assert_eq!(ren_base.pixf.get((95,50)), Rgba8::white());
+    // Anti-aliased edge of the clip path
+    let edge = ren_base.pixf.get((71,71));
+    assert!(edge.r > 0 && edge.r < 255, "edge {:?}", edge);
+}
+
+#[test]
+fn clip_path_nested() {
+    let pix = Pixfmt::<Rgb8>::new(100,100);
+    let mut ren_base = RenderingBase::new(pix);
+    ren_base.clear(Rgba8::white());
+
+    ren_base.clip_path(&rect(0.0, 0.0, 60.0, 100.0), FillingRule::NonZero);
+    ren_base.save_clip();
+    ren_base.clip_path(&rect(40.0, 0.0, 100.0, 100.0), FillingRule::NonZero);
+    fill(&mut ren_base, &rect(0.0, 0.0, 100.0, 50.0), Rgba8::black());
+
+    // Only the intersection 40..60 is drawn
+    assert_eq!(ren_base.pixf.get((50,10)), Rgba8::black());
+    assert_eq!(ren_base.pixf.get((30,10)), Rgba8::white());
+    assert_eq!(ren_base.pixf.get((70,10)), Rgba8::white());
+
+    // Back to the first clip path
+    ren_base.restore_clip();
+    fill(&mut ren_base, &rect(0.0, 50.0, 100.0, 100.0), Rgba8::new(255,0,0,255));
+    assert_eq!(ren_base.pixf.get((30,70)), Rgba8::new(255,0,0,255));
+    assert_eq!(ren_base.pixf.get((70,70)), Rgba8::white());
+
+    // No clip path
+    ren_base.restore_clip();
+    assert!(! ren_base.has_clip());
+    fill(&mut ren_base, &rect(0.0, 50.0, 100.0, 100.0), Rgba8::new(0,0,255,255));
+    assert_eq!(ren_base.pixf.get((70,70)), Rgba8::new(0,0,255,255));
+}
+
+#[test]
+fn clip_path_even_odd() {
+    let pix = Pixfmt::<Rgb8>::new(100,100);
+    let mut ren_base = RenderingBase::new(pix);
+    ren_base.clear(Rgba8::white());
+
+    let mut clip = rect(10.0, 10.0, 90.0, 90.0);
+    clip.move_to(30.0, 30.0);
+    clip.line_to(70.0, 30.0);
+    clip.line_to(70.0, 70.0);
+    clip.line_to(30.0, 70.0);
+    clip.close_polygon();
+    ren_base.clip_path(&clip, FillingRule::EvenOdd);
+
+    fill(&mut ren_base, &rect(0.0, 0.0, 100.0, 100.0), Rgba8::black());
+
+    assert_eq!(ren_base.pixf.get((20,20)), Rgba8::black());
+    assert_eq!(ren_base.pixf.get((50,50)), Rgba8::white());
+    assert_eq!(ren_base.pixf.get((5,5)), Rgba8::white());
+}
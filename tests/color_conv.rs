@@ -0,0 +1,150 @@
+extern crate agg;
+
+use agg::{Pixfmt,Pixel,Source,PixfmtConv,GrayConv,Color};
+use agg::{Rgb8,Rgba8,Rgba8pre,Gray8,Bgr8,Bgra8,Argb8,Abgr8,Gray16,Rgb48,Rgba64,Rgba64pre};
+use agg::{Rgb565,Rgb555,Srgb8,Srgba8,Srgba8pre,Rgba32,Bgra8pre,Argb8pre,Abgr8pre};
+
+/// Opaque grays survive conversion between every pair of formats, within
+///   the precision of packed formats
+fn check<A, B>() where Pixfmt<A>: PixfmtConv, Pixfmt<B>: PixfmtConv {
+    let grays = [0u8, 17, 64, 128, 200, 255];
+    let mut src = Pixfmt::<Rgba8>::new(grays.len(), 1);
+    for (i, &v) in grays.iter().enumerate() {
+        src.set((i,0), Rgba8::new(v,v,v,255));
+    }
+    let a : Pixfmt<A> = agg::convert(&src);
+    let b : Pixfmt<B> = agg::convert(&a);
+    let dst : Pixfmt<Rgba8> = agg::convert(&b);
+    for (i, &v) in grays.iter().enumerate() {
+        let c = dst.get((i,0));
+        let name = std::any::type_name::<(A,B)>();
+        assert_eq!(c.a, 255, "{} {:?}", name, c);
+        for x in [c.r, c.g, c.b] {
+            assert!((x as i32 - v as i32).abs() <= 8, "{} {} {:?}", name, v, c);
+        }
+    }
+}
+
+macro_rules! check_pairs {
+    ($($t:ident),*) => { check_pairs!(@outer [$($t),*] $($t),*); };
+    (@outer $all:tt $($a:ident),*) => { $( check_pairs!(@row $a $all); )* };
+    (@row $a:ident [$($b:ident),*]) => { $( check::<$a,$b>(); )* };
+}
+
+#[test]
+fn color_conv_all_pairs() {
+    check_pairs!(Rgb8, Rgba8, Rgba8pre, Gray8, Bgr8, Bgra8, Argb8, Abgr8,
+                 Gray16, Rgb48, Rgba64, Rgba64pre, Rgb565, Rgb555,
+                 Srgb8, Srgba8, Srgba8pre, Rgba32, Bgra8pre, Argb8pre, Abgr8pre);
+}
+
+#[test]
+fn color_conv_alpha() {
+    let mut pix = Pixfmt::<Rgba8>::new(3,1);
+    pix.set((0,0), Rgba8::new(255,0,0,128));
+    pix.set((1,0), Rgba8::new(10,20,30,0));
+    pix.set((2,0), Rgba8::new(10,20,30,40));
+
+    // Premultiplied
+    let pre : Pixfmt<Rgba8pre> = agg::convert(&pix);
+    assert_eq!(pre.get((0,0)), Rgba8::new(128,0,0,128));
+    assert_eq!(pre.get((1,0)), Rgba8::new(0,0,0,0));
+    let pre16 : Pixfmt<Rgba64pre> = agg::convert(&pix);
+    assert_eq!(pre16.raw((0,0)), Rgba64pre::new(32896,0,0,32896));
+    // Premultiplied in linear light, then encoded
+    let spre : Pixfmt<Srgba8pre> = agg::convert(&pix);
+    assert_eq!(spre.as_bytes()[..4], [188,0,0,128]);
+
+    // Demultiplied
+    let back : Pixfmt<Rgba8> = agg::convert(&pre);
+    assert_eq!(back.get((0,0)), Rgba8::new(255,0,0,128));
+    assert_eq!(back.get((1,0)), Rgba8::new(0,0,0,0));
+    let back : Pixfmt<Rgba8> = agg::convert(&spre);
+    assert_eq!(back.get((0,0)), Rgba8::new(255,0,0,128));
+    let mut pre = Pixfmt::<Rgba8pre>::new(1,1);
+    pre.set((0,0), Rgba8pre::new(64,32,0,128));
+    let straight : Pixfmt<Rgba64> = agg::convert(&pre);
+    assert_eq!(straight.raw((0,0)), Rgba64::new(32768,16384,0,32896));
+
+    // Alpha is dropped
+    let rgb : Pixfmt<Rgb8> = agg::convert(&pix);
+    assert_eq!(rgb.as_bytes()[6..], [10,20,30]);
+    let bgra : Pixfmt<Bgra8> = agg::convert(&pix);
+    assert_eq!(bgra.as_bytes()[8..], [30,20,10,40]);
+    let gray : Pixfmt<Gray8> = agg::convert(&pix);
+    assert_eq!(gray.raw((2,0)).alpha, 40);
+}
+
+#[test]
+fn color_conv_gray() {
+    let mut pix = Pixfmt::<Rgb8>::new(2,1);
+    pix.set((0,0), Rgb8::new(255,0,0));
+    pix.set((1,0), Rgb8::new(0,128,255));
+
+    let gray : Pixfmt<Gray8> = agg::convert(&pix);
+    assert_eq!(gray.raw((0,0)), Gray8::new(54));
+    assert_eq!(gray.raw((1,0)), Gray8::from_trait(Rgb8::new(0,128,255)));
+    let gray : Pixfmt<Gray8> = agg::convert_with(&pix, GrayConv::Lightness);
+    assert_eq!(gray.raw((0,0)), Gray8::new(128));
+    assert_eq!(gray.raw((1,0)), Gray8::new(128));
+    let gray : Pixfmt<Gray8> = agg::convert_with(&pix, GrayConv::Average);
+    assert_eq!(gray.raw((0,0)), Gray8::new(85));
+    assert_eq!(gray.raw((1,0)), Gray8::new(128));
+    let gray : Pixfmt<Gray16> = agg::convert_with(&pix, GrayConv::Average);
+    assert_eq!(gray.raw((0,0)), Gray16::new(21845));
+
+    // Gray to color
+    let rgb : Pixfmt<Rgb48> = agg::convert(&gray);
+    assert_eq!(rgb.raw((0,0)), Rgb48::new(21845,21845,21845));
+    assert_eq!(GrayConv::default(), GrayConv::Luminance);
+    assert!((GrayConv::Lightness.gray(Rgba8::new(255,0,255,255)) - 0.5).abs() < 1e-9);
+}
+
+#[test]
+fn color_conv_precision() {
+    // 16-bit and floating point values are kept
+    let mut pix = Pixfmt::<Rgba64>::new(1,1);
+    pix.set((0,0), Rgba64::new(1,1000,40000,65535));
+    let f : Pixfmt<Rgba32> = agg::convert(&pix);
+    let c = f.raw((0,0));
+    assert!((c.green() - 1000.0/65535.0).abs() < 1e-6, "{:?}", c);
+    let back : Pixfmt<Rgba64> = agg::convert(&f);
+    assert_eq!(back.raw((0,0)), Rgba64::new(1,1000,40000,65535));
+
+    // sRGB encoded values are kept
+    let mut pix = Pixfmt::<Srgb8>::new(256,1);
+    for i in 0 ..= 255 {
+        pix.set((i,0), Srgb8::new(i as u8, 255 - i as u8, 7));
+    }
+    let lin : Pixfmt<Rgb48> = agg::convert(&pix);
+    let back : Pixfmt<Srgba8> = agg::convert(&lin);
+    for i in 0 ..= 255 {
+        assert_eq!(back.raw((i,0)), [i as u8, 255 - i as u8, 7, 255]);
+    }
+    let c = lin.raw((128,0));
+    assert_eq!(Rgba8::from_trait(c), Rgba8::new(55,54,1,255));
+
+    // Packed formats
+    let mut pix = Pixfmt::<Rgb565>::new(1,1);
+    pix.set((0,0), Rgb565(0xF81F));
+    let argb : Pixfmt<Argb8> = agg::convert(&pix);
+    assert_eq!(argb.as_bytes(), &[255,255,0,255]);
+    let abgr : Pixfmt<Abgr8> = agg::convert(&argb);
+    assert_eq!(abgr.as_bytes(), &[255,255,0,255]);
+    let p555 : Pixfmt<Rgb555> = agg::convert(&abgr);
+    assert_eq!(p555.raw((0,0)), Rgb555(0x7C1F));
+    let bgr : Pixfmt<Bgr8> = agg::convert(&p555);
+    assert_eq!(bgr.as_bytes(), &[255,0,255]);
+}
+
+#[test]
+fn color_conv_view() {
+    // Any storage may be converted
+    let mut pix = Pixfmt::<Rgba8>::new(4,4);
+    pix.fill(Rgba8::new(0,0,255,255));
+    pix.view(1,1,2,2).fill(Rgba8::new(255,0,0,255));
+    let out : Pixfmt<Rgb8> = agg::convert(&pix.view(1,0,3,2));
+    assert_eq!((out.width(), out.height()), (3,2));
+    assert_eq!(out.as_bytes(), &[0,0,255, 0,0,255, 0,0,255,
+                                 255,0,0, 255,0,0, 0,0,255]);
+}
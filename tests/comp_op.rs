@@ -0,0 +1,175 @@
+extern crate agg;
+
+use agg::{Pixfmt,Rgba8,Rgba8pre,Pixel,Source,RenderingBase,Render};
+use agg::{CompOp,PixfmtCompOp,CompOpBlend};
+
+fn square<T>(ren_base: &mut RenderingBase<T>, color: Rgba8) where T: Pixel {
+    let mut ras = agg::RasterizerScanline::new();
+    ras.move_to(10.0, 10.0);
+    ras.line_to(30.0, 10.0);
+    ras.line_to(30.0, 30.0);
+    ras.line_to(10.0, 30.0);
+    let mut ren = agg::RenderingScanlineAASolid::with_base(ren_base);
+    ren.color(color);
+    agg::render_scanlines(&mut ras, &mut ren);
+}
+
+fn composite(op: CompOp, dst: Rgba8, src: Rgba8) -> Rgba8 {
+    let mut pix = Pixfmt::<Rgba8>::new(40,40);
+    pix.fill(dst);
+    let mut ren_base = RenderingBase::new(PixfmtCompOp::new(pix, op));
+    square(&mut ren_base, src);
+    // Outside of the shape is unchanged
+    assert_eq!(ren_base.pixf.get((5,5)), dst, "{:?}", op);
+    ren_base.pixf.get((20,20))
+}
+
+#[test]
+fn comp_op_porter_duff() {
+    let red = Rgba8::new(255,0,0,255);
+    let blue = Rgba8::new(0,0,255,255);
+    let clear = Rgba8::new(0,0,0,0);
+    assert_eq!(composite(CompOp::Clear, blue, red), clear);
+    assert_eq!(composite(CompOp::Src, blue, red), red);
+    assert_eq!(composite(CompOp::Dst, blue, red), blue);
+    assert_eq!(composite(CompOp::SrcOver, blue, red), red);
+    assert_eq!(composite(CompOp::DstOver, blue, red), blue);
+    assert_eq!(composite(CompOp::SrcIn, blue, red), red);
+    assert_eq!(composite(CompOp::SrcIn, clear, red), clear);
+    assert_eq!(composite(CompOp::DstIn, blue, red), blue);
+    assert_eq!(composite(CompOp::SrcOut, blue, red), clear);
+    assert_eq!(composite(CompOp::SrcOut, clear, red), red);
+    assert_eq!(composite(CompOp::DstOut, blue, red), clear);
+    assert_eq!(composite(CompOp::SrcAtop, blue, red), red);
+    assert_eq!(composite(CompOp::SrcAtop, clear, red), clear);
+    assert_eq!(composite(CompOp::DstAtop, clear, red), red);
+    assert_eq!(composite(CompOp::Xor, blue, red), clear);
+    assert_eq!(composite(CompOp::Xor, clear, red), red);
+    assert_eq!(composite(CompOp::Plus, blue, red), Rgba8::new(255,0,255,255));
+
+    // Half transparent source
+    let red = Rgba8::new(255,0,0,128);
+    assert_eq!(composite(CompOp::Src, blue, red), red);
+    assert_eq!(composite(CompOp::SrcOver, blue, red), Rgba8::new(128,0,127,255));
+    assert_eq!(composite(CompOp::DstOut, blue, red), Rgba8::new(0,0,255,127));
+}
+
+#[test]
+fn comp_op_blend_modes() {
+    let dst = Rgba8::new(64,128,255,255);
+    let src = Rgba8::new(128,128,128,255);
+    assert_eq!(composite(CompOp::Multiply, dst, src), Rgba8::new(32,64,128,255));
+    assert_eq!(composite(CompOp::Screen, dst, src), Rgba8::new(160,192,255,255));
+    assert_eq!(composite(CompOp::Darken, dst, src), Rgba8::new(64,128,128,255));
+    assert_eq!(composite(CompOp::Lighten, dst, src), Rgba8::new(128,128,255,255));
+    assert_eq!(composite(CompOp::Difference, dst, src), Rgba8::new(64,0,127,255));
+    assert_eq!(composite(CompOp::Overlay, dst, src), Rgba8::new(64,128,255,255));
+    assert_eq!(composite(CompOp::ColorDodge, dst, Rgba8::black()), dst);
+    assert_eq!(composite(CompOp::ColorBurn, dst, Rgba8::white()), dst);
+    assert_eq!(composite(CompOp::HardLight, dst, Rgba8::white()), Rgba8::white());
+    assert_eq!(composite(CompOp::SoftLight, dst, src), Rgba8::new(64,128,255,255));
+    assert_eq!(composite(CompOp::Exclusion, dst, Rgba8::black()), dst);
+
+    // Transparent backdrop shows the source
+    let clear = Rgba8::new(0,0,0,0);
+    assert_eq!(composite(CompOp::Multiply, clear, src), src);
+}
+
+#[test]
+fn comp_op_coverage() {
+    // Partial coverage interpolates towards the result
+    let mut pix = Pixfmt::<Rgba8>::new(1,1);
+    pix.fill(Rgba8::new(0,0,255,255));
+    let mut pix = PixfmtCompOp::new(pix, CompOp::Clear);
+    pix.blend_pix((0,0), Rgba8::black(), 128);
+    assert_eq!(pix.get((0,0)), Rgba8::new(0,0,255,127));
+    pix.blend_pix((0,0), Rgba8::black(), 0);
+    assert_eq!(pix.get((0,0)), Rgba8::new(0,0,255,127));
+
+    // Anti-aliased edge
+    let mut pix = Pixfmt::<Rgba8>::new(40,40);
+    pix.fill(Rgba8::white());
+    let mut ren_base = RenderingBase::new(PixfmtCompOp::new(pix, CompOp::Difference));
+    let mut ras = agg::RasterizerScanline::new();
+    ras.move_to(10.0, 10.0);
+    ras.line_to(30.5, 10.0);
+    ras.line_to(30.5, 30.0);
+    ras.line_to(10.0, 30.0);
+    let mut ren = agg::RenderingScanlineAASolid::with_base(&mut ren_base);
+    ren.color(Rgba8::white());
+    agg::render_scanlines(&mut ras, &mut ren);
+    assert_eq!(ren_base.pixf.get((20,20)), Rgba8::black());
+    assert_eq!(ren_base.pixf.get((30,20)), Rgba8::new(127,127,127,255));
+    ren_base.to_file("tests/tmp/comp_op_coverage.png").unwrap();
+}
+
+#[test]
+fn comp_op_premultiplied() {
+    let mut pix = Pixfmt::<Rgba8pre>::new(40,40);
+    pix.fill(Rgba8pre::new(0,0,128,128));
+    let mut ren_base = RenderingBase::new(PixfmtCompOp::new(pix, CompOp::SrcOver));
+    assert_eq!(ren_base.pixf.comp_op(), CompOp::SrcOver);
+
+    // Straight source color is premultiplied before compositing
+    square(&mut ren_base, Rgba8::new(255,0,0,128));
+    assert_eq!(ren_base.pixf.get((20,20)), Rgba8::new(128,0,64,192));
+
+    ren_base.pixf.set_comp_op(CompOp::DstOut);
+    square(&mut ren_base, Rgba8::new(255,255,255,255));
+    assert_eq!(ren_base.pixf.get((20,20)), Rgba8::new(0,0,0,0));
+    assert_eq!(ren_base.pixf.get((5,5)), Rgba8::new(0,0,128,128));
+
+    // Premultiplied source
+    ren_base.pixf.set_comp_op(CompOp::Plus);
+    ren_base.pixf.blend_pix((5,5), Rgba8pre::new(64,0,0,64), 255);
+    assert_eq!(ren_base.pixf.get((5,5)), Rgba8::new(64,0,128,192));
+}
+
+#[test]
+fn comp_op_non_separable() {
+    let red = Rgba8::new(255,0,0,255);
+    let blue = Rgba8::new(0,0,255,255);
+    let gray = Rgba8::new(128,128,128,255);
+    assert_eq!(composite(CompOp::Hue, red, blue), Rgba8::new(54,54,255,255));
+    assert_eq!(composite(CompOp::Saturation, red, gray), Rgba8::new(77,77,77,255));
+    assert_eq!(composite(CompOp::Color, red, blue), Rgba8::new(54,54,255,255));
+    assert_eq!(composite(CompOp::Luminosity, red, gray), Rgba8::new(255,74,74,255));
+    // Gray keeps its luminosity when colored
+    assert_eq!(composite(CompOp::Color, gray, red), Rgba8::new(255,74,74,255));
+    assert_eq!(composite(CompOp::Hue, gray, red), gray);
+
+    // Directly on the pixel formats
+    let mut pix = Pixfmt::<Rgba8>::new(1,1);
+    pix.fill(red);
+    pix.blend_pix_op((0,0), gray, CompOp::Luminosity, 255);
+    assert_eq!(pix.get((0,0)), Rgba8::new(255,74,74,255));
+
+    let mut pix = Pixfmt::<Rgba8pre>::new(1,1);
+    pix.fill(Rgba8pre::new(128,0,0,128));
+    pix.blend_pix_op((0,0), Rgba8::new(128,128,128,128), CompOp::Saturation, 255);
+    assert_eq!(pix.get((0,0)), Rgba8::new(115,51,51,192));
+}
+
+#[test]
+fn comp_op_blend_from() {
+    let mut layer = Pixfmt::<Rgba8>::new(40,40);
+    layer.fill(Rgba8::new(0,0,0,0));
+    for y in 10 .. 30 {
+        layer.setn((10,y), 20, Rgba8::new(0,0,255,255));
+    }
+    let mut ren_base = RenderingBase::new(Pixfmt::<Rgba8>::new(40,40));
+    ren_base.clear(Rgba8::new(255,0,0,255));
+    ren_base.blend_from_op(&layer, CompOp::Hue, 1.0);
+    ren_base.to_file("tests/tmp/comp_op_blend_from.png").unwrap();
+    assert_eq!(ren_base.pixf.get((20,20)), Rgba8::new(54,54,255,255));
+    assert_eq!(ren_base.pixf.get((5,5)), Rgba8::new(255,0,0,255));
+
+    // Opacity
+    ren_base.clear(Rgba8::new(255,0,0,255));
+    ren_base.blend_from_op(&layer, CompOp::Multiply, 0.5);
+    assert_eq!(ren_base.pixf.get((20,20)), Rgba8::new(128,0,0,255));
+
+    // Sizes must match
+    let small = Pixfmt::<Rgba8>::new(10,10);
+    assert!(ren_base.try_blend_from_op(&small, CompOp::Color, 1.0).is_err());
+}
@@ -0,0 +1,117 @@
+extern crate agg;
+
+use agg::{Pixfmt,Rgb8,Rgba8,Rgba8pre,Bgr8,Bgra8,Argb8,Abgr8,Bgra8pre,Argb8pre,Abgr8pre,Pixel,Source,RenderingBase,Render};
+
+fn draw<T: Pixel>(pix: T) -> RenderingBase<T> {
+    let mut ren_base = RenderingBase::new(pix);
+    ren_base.clear(Rgba8::new(255,255,255,255));
+    let mut ras = agg::RasterizerScanline::new();
+    ras.add_path(&agg::Ellipse::new(50.0, 50.0, 40.0, 30.0, 64));
+    let mut ren = agg::RenderingScanlineAASolid::with_base(&mut ren_base);
+    ren.color(Rgba8::new(200,100,40,160));
+    agg::render_scanlines(&mut ras, &mut ren);
+    ren_base
+}
+
+/// Reorder the components of each pixel in `bytes` with `order`
+fn swizzle(bytes: &[u8], order: &[usize]) -> Vec<u8> {
+    bytes.chunks(4).flat_map(|p| order.iter().map(move |&i| p[i])).collect()
+}
+
+#[test]
+fn component_orders_layout() {
+    let c = Rgba8::new(10,20,30,40);
+    let mut pix = Pixfmt::<Bgr8>::new(1,1);
+    pix.set((0,0), c);
+    assert_eq!(pix.as_bytes(), &[30,20,10]);
+    assert_eq!(pix.get((0,0)), Rgba8::new(10,20,30,255));
+
+    let mut pix = Pixfmt::<Bgra8>::new(1,1);
+    pix.set((0,0), c);
+    assert_eq!(pix.as_bytes(), &[30,20,10,40]);
+    assert_eq!(pix.get((0,0)), c);
+
+    let mut pix = Pixfmt::<Argb8>::new(1,1);
+    pix.set((0,0), c);
+    assert_eq!(pix.as_bytes(), &[40,10,20,30]);
+    assert_eq!(pix.get((0,0)), c);
+
+    let mut pix = Pixfmt::<Abgr8>::new(2,1);
+    pix.fill(c);
+    assert_eq!(pix.as_bytes(), &[40,30,20,10, 40,30,20,10]);
+    pix.setn((0,0), 2, Argb8::new(1,2,3,4));
+    assert_eq!(pix.as_bytes(), &[4,3,2,1, 4,3,2,1]);
+}
+
+#[test]
+fn component_orders_render() {
+    let rgba = draw(Pixfmt::<Rgba8>::new(100,100));
+    let rgba = rgba.pixf.as_bytes();
+    let rgb = draw(Pixfmt::<Rgb8>::new(100,100));
+    let rgb = rgb.pixf.as_bytes();
+
+    let bgr = draw(Pixfmt::<Bgr8>::new(100,100));
+    let bgr : Vec<_> = bgr.pixf.as_bytes().chunks(3).flat_map(|p| vec![p[2],p[1],p[0]]).collect();
+    assert!(bgr == rgb);
+
+    let bgra = draw(Pixfmt::<Bgra8>::new(100,100));
+    assert!(bgra.pixf.as_bytes() == &swizzle(rgba, &[2,1,0,3])[..]);
+    let argb = draw(Pixfmt::<Argb8>::new(100,100));
+    assert!(argb.pixf.as_bytes() == &swizzle(rgba, &[3,0,1,2])[..]);
+    let abgr = draw(Pixfmt::<Abgr8>::new(100,100));
+    assert!(abgr.pixf.as_bytes() == &swizzle(rgba, &[3,2,1,0])[..]);
+
+    // Written to file in RGB(A) order
+    bgra.to_file("tests/tmp/component_orders_bgra.png").unwrap();
+    argb.to_file("tests/tmp/component_orders_argb.png").unwrap();
+    let img = image::open("tests/tmp/component_orders_bgra.png").unwrap().to_rgba8();
+    assert!(img.as_raw() == rgba);
+    let img = image::open("tests/tmp/component_orders_argb.png").unwrap().to_rgba8();
+    assert!(img.as_raw() == rgba);
+}
+
+#[test]
+fn component_orders_premultiplied() {
+    // Components are stored as given, in memory order
+    let c = Rgba8pre::new(10,20,30,40);
+    let mut pix = Pixfmt::<Bgra8pre>::new(1,1);
+    pix.set((0,0), c);
+    assert_eq!(pix.as_bytes(), &[30,20,10,40]);
+    assert_eq!(pix.get((0,0)), Rgba8::new(10,20,30,40));
+    let mut pix = Pixfmt::<Argb8pre>::new(1,1);
+    pix.fill(c);
+    assert_eq!(pix.as_bytes(), &[40,10,20,30]);
+    let mut pix = Pixfmt::<Abgr8pre>::new(1,1);
+    pix.setn((0,0), 1, c);
+    assert_eq!(pix.as_bytes(), &[40,30,20,10]);
+
+    // Blended as Rgba8pre
+    let rgba = draw(Pixfmt::<Rgba8pre>::new(100,100));
+    let rgba = rgba.pixf.as_bytes();
+    let bgra = draw(Pixfmt::<Bgra8pre>::new(100,100));
+    assert!(bgra.pixf.as_bytes() == &swizzle(rgba, &[2,1,0,3])[..]);
+    let argb = draw(Pixfmt::<Argb8pre>::new(100,100));
+    assert!(argb.pixf.as_bytes() == &swizzle(rgba, &[3,0,1,2])[..]);
+    let abgr = draw(Pixfmt::<Abgr8pre>::new(100,100));
+    assert!(abgr.pixf.as_bytes() == &swizzle(rgba, &[3,2,1,0])[..]);
+
+    // Premultiplied color over a transparent, premultiplied framebuffer
+    //   (e.g. Cairo ARGB32 on little-endian, in memory as B, G, R, A)
+    let mut buf = vec![0u8; 4 * 2];
+    {
+        let mut pix = Pixfmt::<Bgra8pre,_>::attach(&mut buf[..], 2, 1, 8).unwrap();
+        pix.blend_pix((0,0), Rgba8pre::new(100,50,0,128), 255);
+        pix.blend_pix((1,0), Rgba8pre::new(100,50,0,128), 128);
+    }
+    assert_eq!(buf, [0,50,100,128, 0,25,50,64]);
+
+    // Conversion premultiplies
+    let mut src = Pixfmt::<Rgba8>::new(1,1);
+    src.set((0,0), Rgba8::new(200,100,0,128));
+    let pre : Pixfmt<Argb8pre> = agg::convert(&src);
+    assert_eq!(pre.as_bytes(), &[128,100,50,0]);
+    let back : Pixfmt<Rgba8> = agg::convert(&pre);
+    // Within 8-bit precision
+    assert_eq!(back.get((0,0)), Rgba8::new(199,100,0,128));
+    assert_eq!("Bgra8pre".parse::<agg::PixelFormat>().unwrap(), agg::PixelFormat::Bgra8pre);
+}
@@ -0,0 +1,129 @@
+extern crate agg;
+
+use agg::{Pixfmt,Rgb8,Rgba8,RenderingBase,Render,Source,Pixel};
+
+fn card() -> agg::Path {
+    let mut path = agg::Path::new();
+    path.move_to(30.0, 30.0);
+    path.line_to(70.0, 30.0);
+    path.line_to(70.0, 70.0);
+    path.line_to(30.0, 70.0);
+    path.close_polygon();
+    path
+}
+
+fn fill(ren_base: &mut RenderingBase<Pixfmt<Rgb8>>, path: &agg::Path) {
+    let mut ras = agg::RasterizerScanline::new();
+    ras.add_path(path);
+    let mut ren = agg::RenderingScanlineAASolid::with_base(ren_base);
+    ren.color(Rgba8::new(255,0,0,255));
+    agg::render_scanlines(&mut ras, &mut ren);
+}
+
+fn gray(ren: &RenderingBase<Pixfmt<Rgb8>>, x: usize, y: usize) -> u8 {
+    let c = ren.pixf.get((x,y));
+    assert_eq!(c.r, c.g, "({},{}) {:?}", x, y, c);
+    assert_eq!(c.r, c.b, "({},{}) {:?}", x, y, c);
+    c.r
+}
+
+#[test]
+fn drop_shadow() {
+    let mut ren_base = RenderingBase::new(Pixfmt::<Rgb8>::new(100,100));
+    ren_base.clear(Rgba8::white());
+    let path = card();
+    agg::drop_shadow(&mut ren_base, &path, Rgba8::black(), 8.0, 8.0, 4.0);
+    fill(&mut ren_base, &path);
+    ren_base.to_file("tests/tmp/drop_shadow.png").unwrap();
+
+    // Shape drawn over the shadow
+    assert_eq!(ren_base.pixf.get((50,50)), Rgba8::new(255,0,0,255));
+    // Shadow below and to the right of the shape
+    assert!(gray(&ren_base, 60, 74) < 64);
+    assert!(gray(&ren_base, 74, 60) < 64);
+    // Blurred edges of the shadow
+    let edge = gray(&ren_base, 78, 60);
+    assert!(edge > 64 && edge < 192, "{}", edge);
+    assert!(gray(&ren_base, 82, 60) > edge);
+    // No shadow above and to the left, or far away
+    assert_eq!(gray(&ren_base, 26, 50), 255);
+    assert_eq!(gray(&ren_base, 50, 26), 255);
+    assert_eq!(gray(&ren_base, 95, 95), 255);
+}
+
+#[test]
+fn drop_shadow_color() {
+    // Shadow color and alpha
+    let mut ren_base = RenderingBase::new(Pixfmt::<Rgb8>::new(100,100));
+    ren_base.clear(Rgba8::white());
+    agg::drop_shadow(&mut ren_base, &card(), Rgba8::new(0,0,255,128), 0.0, 0.0, 2.0);
+    ren_base.to_file("tests/tmp/drop_shadow_color.png").unwrap();
+    let c = ren_base.pixf.get((50,50));
+    assert_eq!((c.b, c.r, c.g), (255, 127, 127));
+    assert_eq!(ren_base.pixf.get((10,10)), Rgba8::white());
+}
+
+#[test]
+fn drop_shadow_glow() {
+    let mut ren_base = RenderingBase::new(Pixfmt::<Rgb8>::new(100,100));
+    ren_base.clear(Rgba8::white());
+    let path = card();
+    agg::glow(&mut ren_base, &path, Rgba8::black(), 6.0);
+    fill(&mut ren_base, &path);
+    ren_base.to_file("tests/tmp/drop_shadow_glow.png").unwrap();
+
+    assert_eq!(ren_base.pixf.get((50,50)), Rgba8::new(255,0,0,255));
+    // Glow is symmetric about the shape
+    for d in 1 .. 8 {
+        let l = gray(&ren_base, 30 - d, 50);
+        let r = gray(&ren_base, 69 + d, 50);
+        let t = gray(&ren_base, 50, 30 - d);
+        let b = gray(&ren_base, 50, 69 + d);
+        assert!(l < 255, "{} {}", d, l);
+        assert!((i64::from(l) - i64::from(r)).abs() <= 2, "{} {} {}", d, l, r);
+        assert!((i64::from(l) - i64::from(t)).abs() <= 2, "{} {} {}", d, l, t);
+        assert!((i64::from(t) - i64::from(b)).abs() <= 2, "{} {} {}", d, t, b);
+    }
+}
+
+#[test]
+fn drop_shadow_clip() {
+    let mut ren_base = RenderingBase::new(Pixfmt::<Rgb8>::new(100,100));
+    ren_base.clear(Rgba8::white());
+    let mut clip = agg::Path::new();
+    clip.move_to(0.0, 0.0);
+    clip.line_to(50.0, 0.0);
+    clip.line_to(50.0, 100.0);
+    clip.line_to(0.0, 100.0);
+    clip.close_polygon();
+    ren_base.clip_path(&clip, agg::FillingRule::NonZero);
+    agg::drop_shadow(&mut ren_base, &card(), Rgba8::black(), 0.0, 0.0, 0.0);
+    assert_eq!(gray(&ren_base, 40, 50), 0);
+    assert_eq!(gray(&ren_base, 60, 50), 255);
+}
+
+#[test]
+fn drop_shadow_region() {
+    // Only the region around the shape is drawn, with the same result as
+    //   on a small image
+    let mut small = RenderingBase::new(Pixfmt::<Rgb8>::new(100,100));
+    small.clear(Rgba8::white());
+    agg::drop_shadow(&mut small, &card(), Rgba8::new(0,0,0,160), 6.0, 4.0, 5.0);
+    let mut large = RenderingBase::new(Pixfmt::<Rgb8>::new(2000,1500));
+    large.clear(Rgba8::white());
+    agg::drop_shadow(&mut large, &card(), Rgba8::new(0,0,0,160), 6.0, 4.0, 5.0);
+    for y in 0 .. 100 {
+        for x in 0 .. 100 {
+            assert_eq!(large.pixf.get((x,y)), small.pixf.get((x,y)), "({},{})", x, y);
+        }
+    }
+    assert_eq!(gray(&large, 100, 50), 255);
+    assert_eq!(gray(&large, 1999, 1499), 255);
+
+    // Shapes outside of the image do nothing
+    let before = large.pixf.as_bytes().to_vec();
+    agg::drop_shadow(&mut large, &card(), Rgba8::black(), 3000.0, 0.0, 5.0);
+    agg::drop_shadow(&mut large, &card(), Rgba8::black(), -200.0, -200.0, 5.0);
+    agg::drop_shadow(&mut large, &agg::Path::new(), Rgba8::black(), 0.0, 0.0, 5.0);
+    assert!(large.pixf.as_bytes() == &before[..]);
+}
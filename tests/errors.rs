@@ -0,0 +1,61 @@
+extern crate agg;
+
+use agg::{Pixfmt,Rgb8,Rgba8,RenderingBase,Source,Pixel};
+use agg::{Vertex,PathCommand,VertexSource,AggError};
+
+/// Square followed by a Stop and a second square which is never drawn
+struct Stopped {}
+
+impl VertexSource for Stopped {
+    fn xconvert(&self) -> Vec<Vertex<f64>> {
+        vec![Vertex::move_to(10.0, 10.0),
+             Vertex::line_to(40.0, 10.0),
+             Vertex::line_to(40.0, 40.0),
+             Vertex::line_to(10.0, 40.0),
+             Vertex::new(0.0, 0.0, PathCommand::Close),
+             Vertex::new(0.0, 0.0, PathCommand::Stop),
+             Vertex::move_to(60.0, 60.0),
+             Vertex::line_to(90.0, 60.0),
+             Vertex::line_to(90.0, 90.0),
+             Vertex::line_to(60.0, 90.0),
+             Vertex::new(0.0, 0.0, PathCommand::Close)]
+    }
+}
+
+#[test]
+fn pixfmt_zero_size() {
+    match Pixfmt::<Rgb8>::try_new(0, 10) {
+        Err(AggError::ZeroSize { width: 0, height: 10 }) => {},
+        other => panic!("expected ZeroSize, got {:?}", other.map(|_| ())),
+    }
+    assert!(Pixfmt::<Rgb8>::try_new(10, 0).is_err());
+    assert!(Pixfmt::<Rgb8>::try_new(10, 10).is_ok());
+}
+
+#[test]
+fn blend_from_size_mismatch() {
+    let mut ren_base = RenderingBase::new(Pixfmt::<Rgb8>::new(10,10));
+    let other = Pixfmt::<Rgb8>::new(20,10);
+    match ren_base.try_blend_from(&other, 1.0) {
+        Err(AggError::SizeMismatch { expected, found }) => {
+            assert_eq!(expected, (10,10));
+            assert_eq!(found, (20,10));
+        },
+        other => panic!("expected SizeMismatch, got {:?}", other),
+    }
+    let other = Pixfmt::<Rgb8>::new(10,10);
+    assert!(ren_base.try_blend_from(&other, 1.0).is_ok());
+}
+
+#[test]
+fn add_path_stop() {
+    let mut ren_base = RenderingBase::new(Pixfmt::<Rgb8>::new(100,100));
+    ren_base.clear(Rgba8::white());
+    let mut ras = agg::RasterizerScanline::new();
+    ras.add_path(&Stopped {});
+    agg::render_scanlines_aa_solid(&mut ras, &mut ren_base, Rgba8::black());
+
+    assert_eq!(ren_base.pixf.get((25,25)), Rgba8::black());
+    assert_eq!(ren_base.pixf.get((75,75)), Rgba8::white());
+    assert_eq!(ren_base.pixf.width(), 100);
+}
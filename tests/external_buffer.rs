@@ -0,0 +1,160 @@
+extern crate agg;
+
+use agg::{Pixfmt,Rgb8,Rgba8,Bgra8,Pixel,Source,RenderingBase,Render,AggError};
+
+fn draw<T: Pixel>(pix: T) -> RenderingBase<T> {
+    let mut ren_base = RenderingBase::new(pix);
+    ren_base.clear(Rgba8::new(255,255,255,255));
+    let mut ras = agg::RasterizerScanline::new();
+    ras.add_path(&agg::Ellipse::new(50.0, 40.0, 40.0, 30.0, 64));
+    let mut ren = agg::RenderingScanlineAASolid::with_base(&mut ren_base);
+    ren.color(Rgba8::new(200,100,40,160));
+    agg::render_scanlines(&mut ras, &mut ren);
+    ren_base
+}
+
+#[test]
+fn external_buffer_stride() {
+    let (w, h) = (100, 80);
+    let expected = draw(Pixfmt::<Rgb8>::new(w,h));
+    let expected = expected.pixf.as_bytes();
+    let row = w * 3;
+
+    // Top-down, rows padded to 320 bytes
+    let stride = 320;
+    let mut buf = vec![0xAA_u8; stride * h];
+    {
+        let pix = Pixfmt::<Rgb8,_>::attach(&mut buf[..], w, h, stride as i64).unwrap();
+        assert_eq!(pix.stride(), 320);
+        let ren_base = draw(pix);
+        assert_eq!(ren_base.pixf.get((50,40)), Rgba8::new(220,158,120,255));
+    }
+    for y in 0 .. h {
+        assert!(buf[y*stride .. y*stride + row] == expected[y*row .. (y+1)*row], "row {}", y);
+        assert!(buf[y*stride + row .. (y+1)*stride].iter().all(|&v| v == 0xAA), "padding {}", y);
+    }
+
+    // Bottom-up, first row at the end of the buffer
+    let mut buf = vec![0xAA_u8; stride * h];
+    {
+        let pix = Pixfmt::<Rgb8,_>::attach(&mut buf[..], w, h, -(stride as i64)).unwrap();
+        assert_eq!(pix.stride(), -320);
+        draw(pix);
+    }
+    for y in 0 .. h {
+        let i = (h - 1 - y) * stride;
+        assert!(buf[i .. i + row] == expected[y*row .. (y+1)*row], "row {}", y);
+        assert!(buf[i + row .. i + stride].iter().all(|&v| v == 0xAA), "padding {}", y);
+    }
+}
+
+#[test]
+fn external_buffer_fill() {
+    // Owned data may also be attached and released again
+    let buf = vec![0u8; 4 * 3 * 5];
+    let mut pix = Pixfmt::<Bgra8,_>::attach(buf, 2, 3, -20).unwrap();
+    pix.fill(Rgba8::new(1,2,3,4));
+    assert_eq!(pix.get((1,2)), Rgba8::new(1,2,3,4));
+    pix.set((0,0), Rgba8::new(10,20,30,40));
+    pix.to_file("tests/tmp/external_buffer_fill.png").unwrap();
+    let img = image::open("tests/tmp/external_buffer_fill.png").unwrap().to_rgba8();
+    assert_eq!(img.dimensions(), (2,3));
+    assert_eq!(img.get_pixel(0,0).0, [10,20,30,40]);
+    assert_eq!(img.get_pixel(1,2).0, [1,2,3,4]);
+
+    let buf = pix.into_inner();
+    assert_eq!(buf[40..48], [30,20,10,40, 3,2,1,4]);
+    assert_eq!(buf[48..60], [0; 12]);
+    assert_eq!(buf[0..8], [3,2,1,4, 3,2,1,4]);
+}
+
+#[test]
+fn external_buffer_errors() {
+    let mut buf = [0u8; 100];
+    match Pixfmt::<Rgb8,_>::attach(&mut buf[..], 10, 4, 25) {
+        Err(AggError::BufferSize { width: 10, height: 4, stride: 25, len: 100 }) => {},
+        other => panic!("expected BufferSize, got {:?}", other.map(|_| ())),
+    }
+    // Stride smaller than a row
+    assert!(Pixfmt::<Rgb8,_>::attach(&mut buf[..], 10, 2, 20).is_err());
+    assert!(Pixfmt::<Rgb8,_>::attach(&mut buf[..], 10, 2, -20).is_err());
+    assert!(Pixfmt::<Rgb8,_>::attach(&mut buf[..], 0, 2, 30).is_err());
+    assert!(Pixfmt::<Rgb8,_>::attach(&mut buf[..], 10, 4, 30).is_err());
+    // Overflowing sizes
+    match Pixfmt::<Rgb8,_>::attach(&mut buf[..], 10, 4, i64::MAX) {
+        Err(AggError::BufferSize { stride: i64::MAX, .. }) => {},
+        other => panic!("expected BufferSize, got {:?}", other.map(|_| ())),
+    }
+    assert!(Pixfmt::<Rgb8,_>::attach(&mut buf[..], 10, 4, i64::MIN).is_err());
+    assert!(Pixfmt::<Rgb8,_>::attach(&mut buf[..], usize::MAX, 1, 30).is_err());
+    // Last row does not need padding
+    assert!(Pixfmt::<Rgb8,_>::attach(&mut buf[..], 10, 3, 35).is_ok());
+    assert!(Pixfmt::<Rgb8,_>::attach(&mut buf[..], 10, 3, -35).is_ok());
+}
+
+#[test]
+fn external_buffer_packed() {
+    let (w, h) = (100, 80);
+    let expected = draw(Pixfmt::<Rgb8>::new(w,h));
+    let expected = expected.packed();
+    assert_eq!(expected.len(), w * h * 3);
+
+    // Padded and bottom-up storage is packed top-down
+    let stride = 320;
+    for &s in &[stride as i64, -(stride as i64)] {
+        let mut buf = vec![0xAA_u8; stride * h];
+        let ren_base = draw(Pixfmt::<Rgb8,_>::attach(&mut buf[..], w, h, s).unwrap());
+        assert_eq!(ren_base.as_bytes().len(), stride * h);
+        assert!(ren_base.packed() == expected, "stride {}", s);
+        assert!(ren_base.pixf.packed() == expected, "stride {}", s);
+    }
+
+    // Views only hold their own pixels
+    let mut pix = Pixfmt::<Rgb8>::new(4,4);
+    pix.fill(Rgba8::new(0,0,255,255));
+    pix.set((2,1), Rgba8::new(255,0,0,255));
+    let view = pix.view(1,1,2,2);
+    // Storage runs through the parent's columns between rows
+    assert_eq!(view.as_bytes().len(), (4 + 2) * 3);
+    assert_eq!(&*view.packed(), &[0,0,255, 255,0,0, 0,0,255, 0,0,255]);
+    let dyn_pix = agg::DynPixfmt::new(agg::PixelFormat::Rgb8, 2, 2);
+    assert!(matches!(dyn_pix.packed(), std::borrow::Cow::Borrowed(_)));
+}
+
+/// Blur and composite a drawing with any storage
+fn effects<B: agg::PixelData>(pix: Pixfmt<Rgba8,B>) -> Pixfmt<Rgba8,B> where Pixfmt<Rgba8,B>: Pixel {
+    let mut ren_base = draw(pix);
+    agg::stack_blur(&mut ren_base.pixf, 3, 3, None);
+    agg::recursive_blur(&mut ren_base.pixf, 2.0, 2.0, Some(agg::Rectangle::new(10, 10, 60, 50)));
+    let mut layer = Pixfmt::<Rgba8>::new(100,80);
+    layer.fill(Rgba8::new(0,0,0,0));
+    layer.view(30,20,40,40).fill(Rgba8::new(0,0,255,255));
+    ren_base.blend_from_op(&layer, agg::CompOp::Multiply, 1.0);
+    let mut pix = agg::PixfmtCompOp::new(ren_base.pixf, agg::CompOp::Screen);
+    pix.blend_hline(0, 70, 100, Rgba8::new(0,255,0,255), 200);
+    pix.pixf
+}
+
+#[test]
+fn external_buffer_effects() {
+    let (w, h) = (100, 80);
+    let expected = effects(Pixfmt::<Rgba8>::new(w,h));
+
+    // Bottom-up, padded framebuffer
+    let stride = 420;
+    let mut buf = vec![0xAA_u8; stride * h];
+    {
+        let pix = Pixfmt::<Rgba8,_>::attach(&mut buf[..], w, h, -(stride as i64)).unwrap();
+        let pix = effects(pix);
+        assert!(pix.packed() == expected.packed());
+    }
+    assert!(buf.chunks(stride).all(|row| row[w * 4..].iter().all(|&v| v == 0xAA)));
+
+    // Views may be used as image sources
+    let mut pix = Pixfmt::<Rgba8,_>::attach(&mut buf[..], w, h, -(stride as i64)).unwrap();
+    let view = pix.view(50, 40, 10, 10);
+    assert_eq!(agg::ImageSource::pixel(&view, 2, 3), expected.get((52,43)));
+    assert_eq!(agg::ImageSource::pixel(&view, 10, 3), Rgba8::new(0,0,0,0));
+    let acc = agg::ImageAccessor::new(&view, agg::Wrap::Repeat, agg::Wrap::Clip);
+    assert_eq!(agg::ImageSource::pixel(&acc, 12, 3), expected.get((52,43)));
+}
@@ -0,0 +1,89 @@
+extern crate agg;
+
+use agg::{Pixfmt,Rgb8,Rgba8,RenderingBase,Source,SpanGouraudRgba};
+
+fn new_base() -> RenderingBase<Pixfmt<Rgb8>> {
+    let mut ren_base = RenderingBase::new(Pixfmt::<Rgb8>::new(200,200));
+    ren_base.clear(Rgba8::white());
+    ren_base
+}
+
+fn draw(ren_base: &mut RenderingBase<Pixfmt<Rgb8>>, tri: &mut SpanGouraudRgba) {
+    let mut ras = agg::RasterizerScanline::new();
+    ras.add_path(tri);
+    agg::render_scanlines_aa(&mut ras, ren_base, tri);
+}
+
+#[test]
+fn gouraud_triangle() {
+    let mut ren_base = new_base();
+    let mut tri = SpanGouraudRgba::new(Rgba8::new(255,0,0,255),
+                                       Rgba8::new(0,255,0,255),
+                                       Rgba8::new(0,0,255,255),
+                                       (10.0, 10.0), (190.0, 40.0), (60.0, 190.0),
+                                       0.0);
+    draw(&mut ren_base, &mut tri);
+    ren_base.to_file("tests/tmp/gouraud.png").unwrap();
+
+    // Vertex colors
+    let c = ren_base.pixf.get((15,14));
+    assert!(c.r > 240 && c.g < 15 && c.b < 15, "{:?}", c);
+    let c = ren_base.pixf.get((182,41));
+    assert!(c.g > 240 && c.r < 15 && c.b < 15, "{:?}", c);
+    let c = ren_base.pixf.get((61,182));
+    assert!(c.b > 240 && c.r < 15 && c.g < 15, "{:?}", c);
+    // Centroid is the average
+    let c = ren_base.pixf.get((86,80));
+    for v in &[c.r, c.g, c.b] {
+        assert!((i64::from(*v) - 85).abs() <= 3, "{:?}", c);
+    }
+    // Midpoint of an edge
+    let c = ren_base.pixf.get((100,26));
+    assert!((i64::from(c.r) - 127).abs() <= 4, "{:?}", c);
+    assert!((i64::from(c.g) - 127).abs() <= 4, "{:?}", c);
+    assert!(c.b <= 4, "{:?}", c);
+    // Outside
+    assert_eq!(ren_base.pixf.get((190,190)), Rgba8::white());
+}
+
+#[test]
+fn gouraud_alpha() {
+    let mut ren_base = new_base();
+    let black = Rgba8::black();
+    let clear = Rgba8::new(0,0,0,0);
+    let mut tri = SpanGouraudRgba::new(black, clear, clear,
+                                       (0.0, 0.0), (200.0, 0.0), (0.0, 200.0),
+                                       0.0);
+    draw(&mut ren_base, &mut tri);
+    let c = ren_base.pixf.get((2,2));
+    assert!(c.r < 10, "{:?}", c);
+    let c = ren_base.pixf.get((50,50));
+    assert!((i64::from(c.r) - 129).abs() <= 4, "{:?}", c);
+    let c = ren_base.pixf.get((100,2));
+    assert!((i64::from(c.r) - 129).abs() <= 4, "{:?}", c);
+}
+
+/// Two black triangles sharing the diagonal of a square
+fn seam(d: f64) -> u8 {
+    let mut ren_base = new_base();
+    let black = Rgba8::black();
+    let mut tri = SpanGouraudRgba::new(black, black, black,
+                                       (20.0, 20.0), (180.0, 20.0), (180.0, 180.0),
+                                       d);
+    draw(&mut ren_base, &mut tri);
+    let mut tri = SpanGouraudRgba::new(black, black, black,
+                                       (20.0, 20.0), (180.0, 180.0), (20.0, 180.0),
+                                       d);
+    draw(&mut ren_base, &mut tri);
+    ren_base.to_file(format!("tests/tmp/gouraud_seam_{}.png", d)).unwrap();
+    (30 .. 170).map(|i| ren_base.pixf.get((i,i)).r).max().unwrap()
+}
+
+#[test]
+fn gouraud_dilation() {
+    let without = seam(0.0);
+    let with = seam(0.175);
+    assert!(without > 32, "seam {}", without);
+    assert!(with < without, "seam {} {}", with, without);
+    assert_eq!(seam(0.5), 0);
+}
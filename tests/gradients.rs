@@ -0,0 +1,232 @@
+extern crate agg;
+
+use agg::{Pixfmt,Rgb8,Rgba8,RenderingBase,Source,Transform};
+use agg::{SpanGradient,GradientFunction};
+
+/// Fill a 200x200 image with gradient `g` centered at (100,100) from 0 to 100
+fn fill<G: GradientFunction>(g: G, mtx: Transform, name: &str) -> RenderingBase<Pixfmt<Rgb8>> {
+    let colors : Vec<_> = (0..=255).map(|v| Rgb8::new(v,v,v)).collect();
+    let mut ren_base = RenderingBase::new(Pixfmt::<Rgb8>::new(200,200));
+    ren_base.clear(Rgba8::new(255,0,0,255));
+
+    let mut path = agg::Path::new();
+    path.move_to(0.0, 0.0);
+    path.line_to(200.0, 0.0);
+    path.line_to(200.0, 200.0);
+    path.line_to(0.0, 200.0);
+    path.close_polygon();
+
+    let mut ras = agg::RasterizerScanline::new();
+    ras.add_path(&path);
+    let mut span = SpanGradient::new(mtx, g, &colors, 0.0, 100.0);
+    agg::render_scanlines_aa(&mut ras, &mut ren_base, &mut span);
+    ren_base.to_file(format!("tests/tmp/gradients_{}.png", name)).unwrap();
+    ren_base
+}
+
+fn center() -> Transform {
+    Transform::new_translate(-100.0, -100.0)
+}
+
+fn gray(ren_base: &RenderingBase<Pixfmt<Rgb8>>, x: usize, y: usize) -> u8 {
+    let c = ren_base.pixf.get((x,y));
+    assert_eq!(c.r, c.g);
+    assert_eq!(c.r, c.b);
+    c.r
+}
+
+/// Gray value at (x,y) within 2 of `v`, pixels are sampled at their centers
+fn assert_gray(ren_base: &RenderingBase<Pixfmt<Rgb8>>, x: usize, y: usize, v: u8) {
+    let g = gray(ren_base, x, y);
+    assert!((i64::from(g) - i64::from(v)).abs() <= 2,
+            "pixel ({},{}) = {} expected {}", x, y, g, v);
+}
+
+#[test]
+fn gradient_x_y() {
+    let ren = fill(agg::GradientX{}, center(), "x");
+    assert_gray(&ren, 50, 10, 0);
+    assert_gray(&ren, 150, 10, 127);
+    assert_gray(&ren, 150, 190, 127);
+    let ren = fill(agg::GradientY{}, center(), "y");
+    assert_gray(&ren, 10, 50, 0);
+    assert_gray(&ren, 10, 150, 127);
+    assert_gray(&ren, 190, 150, 127);
+}
+
+#[test]
+fn gradient_radial() {
+    let ren = fill(agg::GradientRadial{}, center(), "radial");
+    assert_gray(&ren, 100, 100, 1);
+    assert_gray(&ren, 150, 100, 128);
+    assert_gray(&ren, 100, 50, 127);
+    assert_gray(&ren, 65, 135, 127);
+    assert_gray(&ren, 0, 0, 255);
+}
+
+#[test]
+fn gradient_radial_focus() {
+    let g = agg::GradientRadialFocus::new(100.0, 50.0, 0.0);
+    let ren = fill(g, center(), "radial_focus");
+    // Darkest at the focal point, brightest on the circle
+    assert_gray(&ren, 150, 100, 1);
+    assert_gray(&ren, 199, 100, 254);
+    assert_gray(&ren, 1, 100, 254);
+    // Halfway between the focal point and the circle
+    assert_gray(&ren, 175, 100, 128);
+    assert_gray(&ren, 75, 100, 128);
+}
+
+#[test]
+fn gradient_diamond() {
+    let ren = fill(agg::GradientDiamond{}, center(), "diamond");
+    assert_gray(&ren, 150, 100, 128);
+    assert_gray(&ren, 150, 130, 128);
+    assert_gray(&ren, 70, 50, 127);
+}
+
+#[test]
+fn gradient_conic() {
+    let ren = fill(agg::GradientConic{}, center(), "conic");
+    assert_gray(&ren, 190, 100, 0);
+    assert_gray(&ren, 100, 10, 127);
+    assert_gray(&ren, 100, 190, 128);
+    assert_gray(&ren, 10, 100, 255);
+}
+
+#[test]
+fn gradient_xy() {
+    let ren = fill(agg::GradientXY{}, center(), "xy");
+    assert_gray(&ren, 100, 10, 0);
+    assert_gray(&ren, 10, 100, 0);
+    assert_gray(&ren, 150, 150, 64);
+    let ren = fill(agg::GradientSqrtXY{}, center(), "sqrt_xy");
+    // Steep near the axes, sqrt(0.5 * 89.5) pixels
+    assert_gray(&ren, 100, 10, 17);
+    assert_gray(&ren, 150, 150, 128);
+    assert_gray(&ren, 50, 150, 128);
+}
+
+#[test]
+fn gradient_transform() {
+    // Rotated by 90 degrees, x gradient becomes a y gradient
+    let mut mtx = Transform::new_rotate(std::f64::consts::FRAC_PI_2);
+    mtx.translate(100.0, 100.0);
+    mtx.invert();
+    let ren = fill(agg::GradientX{}, mtx, "x_rotated");
+    let expected = fill(agg::GradientY{}, center(), "y");
+    assert_eq!(gray(&ren, 10, 150), gray(&expected, 10, 150));
+    assert_eq!(gray(&ren, 190, 150), gray(&expected, 190, 150));
+    assert_gray(&ren, 10, 50, 0);
+}
+
+#[test]
+fn gradient_user_function() {
+    // Concentric rings 25 pixels apart
+    let rings = |x: i64, y: i64, d: i64| {
+        let r = agg::GradientRadial{}.calculate(x, y, d);
+        (r % (d / 4)) * 4
+    };
+    let ren = fill(rings, center(), "rings");
+    assert_gray(&ren, 100, 100, 5);
+    assert_gray(&ren, 124, 100, 250);
+    assert_gray(&ren, 126, 100, 15);
+    assert_gray(&ren, 100, 176, 15);
+}
+
+fn lut_fill(lut: agg::GradientLut, name: &str) -> RenderingBase<Pixfmt<Rgb8>> {
+    let mut ren_base = RenderingBase::new(Pixfmt::<Rgb8>::new(200,20));
+    ren_base.clear(Rgba8::white());
+
+    let mut path = agg::Path::new();
+    path.move_to(0.0, 0.0);
+    path.line_to(200.0, 0.0);
+    path.line_to(200.0, 20.0);
+    path.line_to(0.0, 20.0);
+    path.close_polygon();
+
+    let mut ras = agg::RasterizerScanline::new();
+    ras.add_path(&path);
+    // Gradient from x = 50 to 100
+    let mtx = Transform::new_translate(-50.0, 0.0);
+    let mut span = SpanGradient::new(mtx, agg::GradientX{}, lut, 0.0, 50.0);
+    agg::render_scanlines_aa(&mut ras, &mut ren_base, &mut span);
+    ren_base.to_file(format!("tests/tmp/gradients_lut_{}.png", name)).unwrap();
+    ren_base
+}
+
+fn black_white() -> agg::GradientLut {
+    let mut lut = agg::GradientLut::new();
+    lut.add_stop(0.0, Rgba8::black());
+    lut.add_stop(1.0, Rgba8::white());
+    lut
+}
+
+#[test]
+fn gradient_lut_spread() {
+    let ren = lut_fill(black_white(), "pad");
+    assert_gray(&ren, 10, 10, 0);
+    assert_gray(&ren, 75, 10, 130);
+    assert_gray(&ren, 190, 10, 255);
+
+    let mut lut = black_white();
+    lut.spread(agg::Spread::Repeat);
+    let ren = lut_fill(lut, "repeat");
+    assert_gray(&ren, 25, 10, 130);
+    assert_gray(&ren, 75, 10, 130);
+    assert_gray(&ren, 125, 10, 130);
+    assert_gray(&ren, 149, 10, 253);
+    assert_gray(&ren, 151, 10, 5);
+
+    let mut lut = black_white();
+    lut.spread(agg::Spread::Reflect);
+    let ren = lut_fill(lut, "reflect");
+    assert_gray(&ren, 25, 10, 125);
+    assert_gray(&ren, 75, 10, 130);
+    assert_gray(&ren, 125, 10, 125);
+    assert_gray(&ren, 149, 10, 2);
+    assert_gray(&ren, 151, 10, 5);
+}
+
+#[test]
+fn gradient_lut_alpha() {
+    // Opaque black to transparent black over white
+    let mut lut = agg::GradientLut::new();
+    lut.add_stop(0.0, Rgba8::black());
+    lut.add_stop(1.0, Rgba8::new(0,0,0,0));
+    let ren = lut_fill(lut, "alpha");
+    assert_gray(&ren, 10, 10, 0);
+    assert_gray(&ren, 75, 10, 130);
+    assert_gray(&ren, 190, 10, 255);
+}
+
+#[test]
+fn gradient_lut_stops() {
+    let mut lut = agg::GradientLut::new();
+    lut.add_stop(0.5, Rgba8::new(0,0,255,255));
+    lut.add_stop(0.0, Rgba8::new(255,0,0,255));
+    lut.add_stop(0.5, Rgba8::new(0,255,0,255));
+    let c = lut.colors();
+    assert_eq!(c[0], Rgba8::new(255,0,0,255));
+    assert_eq!(c[127], Rgba8::new(1,0,254,255));
+    // Sharp transition at equal offsets, constant after the last stop
+    assert_eq!(c[128], Rgba8::new(0,255,0,255));
+    assert_eq!(c[255], Rgba8::new(0,255,0,255));
+
+    let ren = lut_fill(lut, "stops");
+    assert_eq!(ren.pixf.get((10,10)), Rgba8::new(255,0,0,255));
+    assert_eq!(ren.pixf.get((80,10)), Rgba8::new(0,255,0,255));
+}
+
+#[test]
+fn gradient_lut_interpolation() {
+    let mut lut = black_white();
+    assert_eq!(lut.colors()[128], Rgba8::new(128,128,128,255));
+    lut.interpolation(agg::ColorInterpolation::Srgb);
+    // sRGB 0.5 is darker in linear RGB
+    assert_eq!(lut.colors()[128], Rgba8::new(55,55,55,255));
+    assert_eq!(lut.colors()[0], Rgba8::black());
+    assert_eq!(lut.colors()[255], Rgba8::white());
+    let ren = lut_fill(lut, "srgb");
+    assert_gray(&ren, 75, 10, 55);
+}
@@ -0,0 +1,63 @@
+extern crate agg;
+
+use agg::{Pixfmt,Rgb8,Rgba8,RenderingBase,Source,Pixel,Transform};
+
+/// 64x64 image with a different color in each quadrant
+fn quadrants() -> Pixfmt<Rgba8> {
+    let mut pix = Pixfmt::<Rgba8>::new(64,64);
+    let colors = [Rgba8::new(255,0,0,255), Rgba8::new(0,255,0,255),
+                  Rgba8::new(0,0,255,255), Rgba8::new(255,255,0,255)];
+    for y in 0 .. 64 {
+        for x in 0 .. 64 {
+            pix.set((x,y), colors[x / 32 + 2 * (y / 32)]);
+        }
+    }
+    pix
+}
+
+#[test]
+fn image1() {
+    let src = quadrants();
+    let mut ren_base = RenderingBase::new(Pixfmt::<Rgb8>::new(200,200));
+    ren_base.clear(Rgba8::white());
+
+    // Center of the image at (100,100), scaled by 2 and rotated by 90 degrees
+    let mut mtx = Transform::new_translate(-32.0, -32.0);
+    mtx.scale(2.0, 2.0);
+    mtx.rotate(std::f64::consts::FRAC_PI_2);
+    mtx.translate(100.0, 100.0);
+    mtx.invert();
+
+    let mut span = agg::SpanImageFilterBilinear::new(&src, mtx);
+    let mut ras = agg::RasterizerScanline::new();
+    ras.add_path(&agg::Ellipse::new(100.0, 100.0, 60.0, 50.0, 128));
+    agg::render_scanlines_aa(&mut ras, &mut ren_base, &mut span);
+    ren_base.to_file("tests/tmp/image1.png").unwrap();
+
+    // Rotation moves the top left quadrant to the top right
+    assert_eq!(ren_base.pixf.get((130,70)), Rgba8::new(255,0,0,255));
+    assert_eq!(ren_base.pixf.get((130,130)), Rgba8::new(0,255,0,255));
+    assert_eq!(ren_base.pixf.get((70,70)), Rgba8::new(0,0,255,255));
+    assert_eq!(ren_base.pixf.get((70,130)), Rgba8::new(255,255,0,255));
+    // Outside the ellipse
+    assert_eq!(ren_base.pixf.get((100,40)), Rgba8::white());
+    assert_eq!(ren_base.pixf.get((35,100)), Rgba8::white());
+    // Bilinear blending along the quadrant edge
+    let c = ren_base.pixf.get((130,100));
+    assert!(c.r > 32 && c.g > 32 && c.b == 0, "{:?}", c);
+}
+
+#[test]
+fn image1_alpha() {
+    let mut src = Pixfmt::<Rgba8>::new(10,10);
+    src.fill(Rgba8::new(0,0,0,128));
+    let mut ren_base = RenderingBase::new(Pixfmt::<Rgb8>::new(20,20));
+    ren_base.clear(Rgba8::white());
+    let mut span = agg::SpanImageFilterBilinear::new(&src, Transform::new());
+    let mut ras = agg::RasterizerScanline::new();
+    ras.add_path(&agg::Ellipse::new(10.0, 10.0, 10.0, 10.0, 64));
+    agg::render_scanlines_aa(&mut ras, &mut ren_base, &mut span);
+
+    assert_eq!(ren_base.pixf.get((5,5)), Rgba8::new(127,127,127,255));
+    assert_eq!(ren_base.pixf.get((15,15)), Rgba8::white());
+}
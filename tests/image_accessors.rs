@@ -0,0 +1,152 @@
+extern crate agg;
+
+use agg::{Pixfmt,Rgb8,Rgba8,RenderingBase,Source,Pixel,Transform};
+use agg::{ImageSource,ImageAccessor,Wrap};
+
+/// 4x4 image with unique colors
+fn source() -> Pixfmt<Rgba8> {
+    let mut pix = Pixfmt::<Rgba8>::new(4,4);
+    for y in 0 .. 4 {
+        for x in 0 .. 4 {
+            pix.set((x,y), Rgba8::new(x as u8 * 64, y as u8 * 64, 255, 255));
+        }
+    }
+    pix
+}
+
+/// Draw `src` over the whole 16x16 image, offset by (4,4)
+fn draw<S: ImageSource>(src: S, name: &str) -> RenderingBase<Pixfmt<Rgb8>> {
+    let mut ren_base = RenderingBase::new(Pixfmt::<Rgb8>::new(16,16));
+    ren_base.clear(Rgba8::white());
+    let mut path = agg::Path::new();
+    path.move_to(0.0, 0.0);
+    path.line_to(16.0, 0.0);
+    path.line_to(16.0, 16.0);
+    path.line_to(0.0, 16.0);
+    path.close_polygon();
+    let mut span = agg::SpanImageFilterNN::new(src, Transform::new_translate(-4.0, -4.0));
+    let mut ras = agg::RasterizerScanline::new();
+    ras.add_path(&path);
+    agg::render_scanlines_aa(&mut ras, &mut ren_base, &mut span);
+    ren_base.to_file(format!("tests/tmp/image_accessors_{}.png", name)).unwrap();
+    ren_base
+}
+
+#[test]
+fn image_accessors_default() {
+    let src = source();
+    let ren = draw(&src, "default");
+    assert_eq!(ren.pixf.get((4,4)), src.get((0,0)));
+    assert_eq!(ren.pixf.get((7,7)), src.get((3,3)));
+    assert_eq!(ren.pixf.get((3,4)), Rgba8::white());
+    assert_eq!(ren.pixf.get((8,8)), Rgba8::white());
+}
+
+#[test]
+fn image_accessors_clip() {
+    let src = source();
+    let ren = draw(ImageAccessor::clip(&src, Rgba8::new(0,0,0,255)), "clip");
+    assert_eq!(ren.pixf.get((5,6)), src.get((1,2)));
+    assert_eq!(ren.pixf.get((3,4)), Rgba8::black());
+    assert_eq!(ren.pixf.get((15,15)), Rgba8::black());
+}
+
+#[test]
+fn image_accessors_wrap() {
+    let src = source();
+    for &(wx, wy, name) in &[(Wrap::Clone, Wrap::Clone, "clone"),
+                             (Wrap::Repeat, Wrap::Reflect, "repeat_reflect"),
+                             (Wrap::Reflect, Wrap::Clip, "reflect_clip")] {
+        let ren = draw(ImageAccessor::new(&src, wx, wy), name);
+        for y in 0 .. 16 {
+            for x in 0 .. 16 {
+                let sx = wx.apply(x - 4, 4);
+                let sy = wy.apply(y - 4, 4);
+                let c = match (sx, sy) {
+                    (Some(sx), Some(sy)) => src.get((sx as usize, sy as usize)),
+                    _ => Rgba8::white(),
+                };
+                assert_eq!(ren.pixf.get((x as usize, y as usize)), c, "{} ({},{})", name, x, y);
+            }
+        }
+    }
+    // Spot checks
+    let ren = draw(ImageAccessor::new(&src, Wrap::Repeat, Wrap::Reflect), "spot");
+    assert_eq!(ren.pixf.get((0,4)), src.get((0,0)));
+    assert_eq!(ren.pixf.get((9,4)), src.get((1,0)));
+    assert_eq!(ren.pixf.get((4,3)), src.get((0,0)));
+    assert_eq!(ren.pixf.get((4,8)), src.get((0,3)));
+    assert_eq!(ren.pixf.get((4,12)), src.get((0,0)));
+}
+
+#[test]
+fn image_accessors_pattern_filter() {
+    let src = source();
+    let filter = agg::PatternFilterBilinear::new();
+    let scale = 256;
+    // Inside, exactly on a pixel
+    assert_eq!(filter.pixel_high_res(&src, 2 * scale, scale), src.get((2,1)));
+    // Halfway past the right edge, transparent outside
+    let c = filter.pixel_high_res(&src, 3 * scale + scale / 2, 0);
+    assert_eq!(c.a, 127);
+    // Clamped edges keep the edge color
+    let img = ImageAccessor::new(&src, Wrap::Clone, Wrap::Clone);
+    assert_eq!(filter.pixel_high_res(&img, 3 * scale + scale / 2, 0), src.get((3,0)));
+    assert_eq!(filter.pixel_high_res(&img, -5 * scale, -scale), src.get((0,0)));
+    // Repeat blends the last and first columns
+    let img = ImageAccessor::new(&src, Wrap::Repeat, Wrap::Repeat);
+    let c = filter.pixel_high_res(&img, 3 * scale + scale / 2, 0);
+    assert_eq!((c.r, c.a), (96, 255));
+}
+
+#[test]
+fn image_accessors_line_pattern() {
+    let src = source();
+    let scale = 256;
+    let new_pattern = |wx, wy| {
+        let mut pattern = agg::LineImagePattern::new(agg::PatternFilterBilinear::new());
+        pattern.wrap(wx, wy);
+        pattern.create(&src);
+        pattern
+    };
+    // Default, repeated along and transparent across the line
+    let default = new_pattern(Wrap::Repeat, Wrap::Clip);
+    let edge = -default.line_width();
+    assert_eq!(default.pixel(0, edge + scale), src.get((0,0)));
+    assert_eq!(default.pixel(0, edge).a, 0);
+    assert_eq!(default.pixel(3 * scale + scale / 2, edge + scale).r, 96);
+
+    // Edges extended across, clamped along the line
+    let clone = new_pattern(Wrap::Clone, Wrap::Clone);
+    assert_eq!(clone.pixel(0, edge), src.get((0,0)));
+    assert_eq!(clone.pixel(3 * scale + scale / 2, edge + scale), src.get((3,0)));
+
+    // Column of a horizontal line, 4 pixels wide
+    let draw = |wrap: Option<(Wrap,Wrap)>| {
+        let mut pattern = agg::LineImagePatternPow2::new(agg::PatternFilterBilinear::new());
+        if let Some((wx, wy)) = wrap {
+            pattern.wrap(wx, wy);
+        }
+        pattern.create(&src);
+        let mut ren_base = RenderingBase::new(Pixfmt::<Rgb8>::new(16,16));
+        ren_base.clear(Rgba8::white());
+        {
+            let mut ren = agg::RendererOutlineImg::with_base_and_pattern(&mut ren_base, pattern);
+            let mut ras = agg::RasterizerOutlineAA::with_renderer(&mut ren);
+            let mut path = agg::Path::new();
+            path.move_to(0.0, 8.0);
+            path.line_to(16.0, 8.0);
+            ras.add_path(&path);
+        }
+        (0 .. 16).map(|y| ren_base.pixf.get((8,y))).collect::<Vec<_>>()
+    };
+    let line : Vec<_> = (0 .. 4).map(|g| Rgba8::new(32, g * 64, 255, 255)).collect();
+    let col = draw(None);
+    assert_eq!(col[6..10], line[..]);
+    assert_eq!((col[5], col[10]), (Rgba8::white(), Rgba8::white()));
+    // Edge rows extended across the line
+    let col = draw(Some((Wrap::Repeat, Wrap::Clone)));
+    assert_eq!(col[6..10], line[..]);
+    assert_eq!((col[5], col[10]), (line[0], line[3]));
+    assert_eq!((col[4], col[11]), (Rgba8::white(), Rgba8::white()));
+}
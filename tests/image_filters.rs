@@ -0,0 +1,146 @@
+extern crate agg;
+
+use agg::{Pixfmt,Rgb8,Rgba8,RenderingBase,Source,Pixel,Transform,SpanGenerator};
+use agg::{ImageFilter,ImageFilterLut,SpanImageFilter};
+
+/// 32x32 checkerboard with 4 pixel squares
+fn checker() -> Pixfmt<Rgba8> {
+    let mut pix = Pixfmt::<Rgba8>::new(32,32);
+    for y in 0 .. 32 {
+        for x in 0 .. 32 {
+            let c = if (x / 4 + y / 4) % 2 == 0 { Rgba8::black() } else { Rgba8::white() };
+            pix.set((x,y), c);
+        }
+    }
+    pix
+}
+
+fn render<G>(span: &mut G, name: &str) -> RenderingBase<Pixfmt<Rgb8>>
+    where G: SpanGenerator
+{
+    let mut ren_base = RenderingBase::new(Pixfmt::<Rgb8>::new(96,96));
+    ren_base.clear(Rgba8::new(255,0,0,255));
+    let mut path = agg::Path::new();
+    path.move_to(0.0, 0.0);
+    path.line_to(96.0, 0.0);
+    path.line_to(96.0, 96.0);
+    path.line_to(0.0, 96.0);
+    path.close_polygon();
+    let mut ras = agg::RasterizerScanline::new();
+    ras.add_path(&path);
+    agg::render_scanlines_aa(&mut ras, &mut ren_base, span);
+    ren_base.to_file(format!("tests/tmp/image_filters_{}.png", name)).unwrap();
+    ren_base
+}
+
+/// Source image scaled by 3
+fn scale3() -> Transform {
+    let mut mtx = Transform::new_scale(3.0, 3.0);
+    mtx.invert();
+    mtx
+}
+
+fn filters() -> Vec<(&'static str, Box<dyn ImageFilter>)> {
+    vec![("bilinear", Box::new(agg::ImageFilterBilinear{})),
+         ("bicubic",  Box::new(agg::ImageFilterBicubic{})),
+         ("spline16", Box::new(agg::ImageFilterSpline16{})),
+         ("spline36", Box::new(agg::ImageFilterSpline36{})),
+         ("gaussian", Box::new(agg::ImageFilterGaussian{})),
+         ("lanczos",  Box::new(agg::ImageFilterLanczos::new(3.0))),
+         ("mitchell", Box::new(agg::ImageFilterMitchell::default())),
+         ("hanning",  Box::new(agg::ImageFilterHanning{})),
+         ("hamming",  Box::new(agg::ImageFilterHamming{})),
+         ("hermite",  Box::new(agg::ImageFilterHermite{})),
+         ("quadric",  Box::new(agg::ImageFilterQuadric{})),
+         ("catrom",   Box::new(agg::ImageFilterCatrom{})),
+         ("sinc",     Box::new(agg::ImageFilterSinc::new(3.0))),
+         ("blackman", Box::new(agg::ImageFilterBlackman::new(3.0)))]
+}
+
+/// Wrapper to build a lookup table from a boxed filter
+struct Boxed<'a>(&'a dyn ImageFilter);
+impl ImageFilter for Boxed<'_> {
+    fn radius(&self) -> f64 { self.0.radius() }
+    fn calc_weight(&self, x: f64) -> f64 { self.0.calc_weight(x) }
+}
+
+#[test]
+fn image_filters_nn() {
+    let src = checker();
+    let mut span = agg::SpanImageFilterNN::new(&src, scale3());
+    let ren = render(&mut span, "nn");
+    for y in 0 .. 96 {
+        for x in 0 .. 96 {
+            assert_eq!(ren.pixf.get((x,y)), src.get((x/3,y/3)));
+        }
+    }
+}
+
+#[test]
+fn image_filters_identity() {
+    // Interpolating filters reproduce the image without a transform
+    let src = checker();
+    let mut bilinear = agg::SpanImageFilterBilinear::new(&src, Transform::new());
+    let ren = render(&mut bilinear, "identity_bilinear");
+    for (name, f) in filters() {
+        if ! ["bilinear","spline16","spline36","lanczos","catrom","sinc","blackman",
+              "hanning","hermite"].contains(&name) {
+            continue;
+        }
+        let lut = ImageFilterLut::new(&Boxed(f.as_ref()));
+        let mut span = SpanImageFilter::new(&src, Transform::new(), lut);
+        let mut out = vec![Rgba8::default(); 32];
+        for y in 0 .. 32 {
+            span.generate(&mut out, 0, y);
+            for (x, &v) in out.iter().enumerate() {
+                let c = src.get((x, y as usize));
+                assert_eq!(v, c, "{} ({},{})", name, x, y);
+                assert_eq!(ren.pixf.get((x, y as usize)), c);
+            }
+        }
+    }
+}
+
+#[test]
+fn image_filters_scaled() {
+    let src = checker();
+    for (name, f) in filters() {
+        let lut = ImageFilterLut::new(&Boxed(f.as_ref()));
+        let mut span = SpanImageFilter::new(&src, scale3(), lut);
+        let ren = render(&mut span, name);
+        // Centers of the checker squares keep their color, within
+        //   the ringing of filters with negative lobes
+        let tol = if name == "sinc" { 96 } else { 24 };
+        for &(x,y,v) in &[(18,18,0), (30,18,255), (42,42,0), (54,42,255)] {
+            let c = ren.pixf.get((x,y));
+            assert!((i64::from(c.r) - v).abs() <= tol, "{} ({},{}) {:?}", name, x, y, c);
+            assert_eq!(c.r, c.g);
+            assert_eq!(c.r, c.b);
+        }
+        // Edges are smoothed
+        let c = ren.pixf.get((24,18));
+        assert!(c.r > 16 && c.r < 240, "{} {:?}", name, c);
+        // Outside of the image, blended with the background
+        let c = ren.pixf.get((95,10));
+        assert!(c.r > 200, "{} {:?}", name, c);
+    }
+}
+
+#[test]
+fn image_filters_constant() {
+    // Normalized filters preserve a constant color
+    let mut src = Pixfmt::<Rgba8>::new(32,32);
+    src.fill(Rgba8::new(10,100,200,255));
+    let mut mtx = Transform::new_scale(2.7, 2.3);
+    mtx.rotate(0.3);
+    mtx.invert();
+    for (name, f) in filters() {
+        let lut = ImageFilterLut::new(&Boxed(f.as_ref()));
+        let mut span = SpanImageFilter::new(&src, mtx, lut);
+        let mut out = vec![Rgba8::default(); 20];
+        span.generate(&mut out, 20, 30);
+        for c in &out {
+            assert_eq!(*c, Rgba8::new(10,100,200,255), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,92 @@
+extern crate agg;
+
+use agg::{Pixfmt,Rgb8,Rgba8,RenderingBase,Source,Pixel,Transform};
+
+/// 16x8 image with unique colors
+fn source() -> Pixfmt<Rgba8> {
+    let mut pix = Pixfmt::<Rgba8>::new(16,8);
+    for y in 0 .. 8 {
+        for x in 0 .. 16 {
+            pix.set((x,y), Rgba8::new(x as u8 * 16, y as u8 * 32, 128, 255));
+        }
+    }
+    pix
+}
+
+/// Draw `src` through `mtx` inside polygon `pts`
+fn draw(src: &Pixfmt<Rgba8>, mtx: Transform, pts: &[(f64,f64)], name: &str)
+        -> RenderingBase<Pixfmt<Rgb8>> {
+    let mut ren_base = RenderingBase::new(Pixfmt::<Rgb8>::new(64,64));
+    ren_base.clear(Rgba8::white());
+    let mut path = agg::Path::new();
+    path.move_to(pts[0].0, pts[0].1);
+    for p in &pts[1..] {
+        path.line_to(p.0, p.1);
+    }
+    path.close_polygon();
+    let mut inv = mtx;
+    inv.invert();
+    let mut span = agg::SpanImageFilterNN::new(src, inv);
+    let mut ras = agg::RasterizerScanline::new();
+    ras.add_path(&path);
+    agg::render_scanlines_aa(&mut ras, &mut ren_base, &mut span);
+    ren_base.to_file(format!("tests/tmp/image_transforms_{}.png", name)).unwrap();
+    ren_base
+}
+
+fn full() -> Vec<(f64,f64)> {
+    vec![(0.0,0.0), (64.0,0.0), (64.0,64.0), (0.0,64.0)]
+}
+
+#[test]
+fn image_translate() {
+    let src = source();
+    let ren = draw(&src, Transform::new_translate(10.0, 20.0), &full(), "translate");
+    for y in 0 .. 8 {
+        for x in 0 .. 16 {
+            assert_eq!(ren.pixf.get((x+10,y+20)), src.get((x,y)));
+        }
+    }
+    assert_eq!(ren.pixf.get((9,20)), Rgba8::white());
+    assert_eq!(ren.pixf.get((26,20)), Rgba8::white());
+}
+
+#[test]
+fn image_scale() {
+    let src = source();
+    let ren = draw(&src, Transform::new_scale(3.0, 2.0), &full(), "scale");
+    for y in 0 .. 16 {
+        for x in 0 .. 48 {
+            assert_eq!(ren.pixf.get((x,y)), src.get((x/3,y/2)));
+        }
+    }
+    assert_eq!(ren.pixf.get((48,0)), Rgba8::white());
+    assert_eq!(ren.pixf.get((0,16)), Rgba8::white());
+}
+
+#[test]
+fn image_rotate() {
+    // 90 degrees clockwise, (x,y) -> (7-y,x)
+    let src = source();
+    let mut mtx = Transform::new_rotate(std::f64::consts::FRAC_PI_2);
+    mtx.translate(8.0, 0.0);
+    let ren = draw(&src, mtx, &full(), "rotate");
+    for y in 0 .. 8 {
+        for x in 0 .. 16 {
+            assert_eq!(ren.pixf.get((7-y,x)), src.get((x,y)));
+        }
+    }
+    assert_eq!(ren.pixf.get((8,0)), Rgba8::white());
+}
+
+#[test]
+fn image_clipped_polygon() {
+    // Scaled image only drawn inside a triangle
+    let src = source();
+    let tri = vec![(0.0,0.0), (48.0,0.0), (0.0,32.0)];
+    let ren = draw(&src, Transform::new_scale(3.0, 4.0), &tri, "polygon");
+    assert_eq!(ren.pixf.get((2,2)), src.get((0,0)));
+    assert_eq!(ren.pixf.get((20,10)), src.get((6,2)));
+    assert_eq!(ren.pixf.get((40,25)), Rgba8::white());
+    assert_eq!(ren.pixf.get((47,1)), Rgba8::white());
+}
@@ -22,3 +22,42 @@ fn t20_outline_render() {
     assert!(agg::ppm::img_diff("tests/tmp/outline_aa.png", "images/outline_aa.png").unwrap());
 
 }
+
+#[test]
+fn outline_aa_gamma() {
+    use agg::{Pixfmt,Rgb8,Rgba8,DrawOutline,Pixel};
+    use agg::{RendererOutlineAA,RasterizerOutlineAA};
+    fn draw<F: Fn(&mut RendererOutlineAA<Pixfmt<Rgb8>>)>(f: F) -> Vec<u8> {
+        let pix = Pixfmt::<Rgb8>::new(100,100);
+        let mut ren_base = agg::RenderingBase::new(pix);
+        ren_base.clear( Rgba8::new(255, 255, 255, 255) );
+        {
+            let mut ren = RendererOutlineAA::with_base(&mut ren_base);
+            ren.color(agg::Rgba8::new(0,0,0,255));
+            ren.width(20.0);
+            f(&mut ren);
+
+            let mut path = agg::Path::new();
+            path.move_to(10.0, 10.0);
+            path.line_to(50.0, 90.0);
+            path.line_to(90.0, 10.0);
+
+            let mut ras = RasterizerOutlineAA::with_renderer(&mut ren);
+            ras.round_cap(true);
+            ras.add_path(&path);
+        }
+        ren_base.pixf.as_bytes().to_vec()
+    }
+    let default = draw(|_| {});
+    assert_eq!(draw(|ren| ren.set_gamma(agg::GammaNone::new())), default);
+
+    // Anti-aliasing removed
+    let bin = draw(|ren| ren.set_gamma(agg::GammaThreshold::new(0.5)));
+    assert_eq!(draw(|ren| ren.gamma(|v| if v < 0.5 { 0.0 } else { 1.0 })), bin);
+    assert!(bin.iter().all(|&v| v == 0 || v == 255));
+    assert!(default.iter().any(|&v| v != 0 && v != 255));
+    assert!(bin.contains(&0));
+
+    let pow = draw(|ren| ren.set_gamma(agg::GammaPower::new(2.0)));
+    assert_eq!(draw(|ren| ren.gamma(|v| v.powf(2.0))), pow);
+}
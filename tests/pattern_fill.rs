@@ -0,0 +1,97 @@
+extern crate agg;
+
+use agg::{Pixfmt,Rgb8,Rgba8,RenderingBase,Source,Pixel,Transform,Wrap};
+use agg::SpanPattern;
+
+/// 6x4 tile with unique colors
+fn tile() -> Pixfmt<Rgba8> {
+    let mut pix = Pixfmt::<Rgba8>::new(6,4);
+    for y in 0 .. 4 {
+        for x in 0 .. 6 {
+            pix.set((x,y), Rgba8::new(x as u8 * 40, y as u8 * 60, 100, 255));
+        }
+    }
+    pix
+}
+
+fn fill(span: &mut SpanPattern<Pixfmt<Rgba8>>, name: &str) -> RenderingBase<Pixfmt<Rgb8>> {
+    let mut ren_base = RenderingBase::new(Pixfmt::<Rgb8>::new(64,64));
+    ren_base.clear(Rgba8::white());
+    let mut ras = agg::RasterizerScanline::new();
+    ras.add_path(&agg::Ellipse::new(32.0, 32.0, 28.0, 28.0, 64));
+    agg::render_scanlines_aa(&mut ras, &mut ren_base, span);
+    ren_base.to_file(format!("tests/tmp/pattern_fill_{}.png", name)).unwrap();
+    ren_base
+}
+
+#[test]
+fn pattern_fill_repeat() {
+    let src = tile();
+    let mut span = SpanPattern::new(&src, 0, 0);
+    let ren = fill(&mut span, "repeat");
+    for y in 16 .. 48 {
+        for x in 16 .. 48 {
+            assert_eq!(ren.pixf.get((x,y)), src.get((x % 6, y % 4)));
+        }
+    }
+    // Outside the shape
+    assert_eq!(ren.pixf.get((1,1)), Rgba8::white());
+    assert_eq!(ren.pixf.get((62,32)), Rgba8::white());
+}
+
+#[test]
+fn pattern_fill_offset() {
+    let src = tile();
+    let mut span = SpanPattern::new(&src, 2, -1);
+    let ren = fill(&mut span, "offset");
+    for y in 16 .. 48 {
+        for x in 16 .. 48 {
+            assert_eq!(ren.pixf.get((x,y)), src.get(((x + 2) % 6, (y + 3) % 4)));
+        }
+    }
+    span.offset_x(0);
+    span.offset_y(0);
+    let ren = fill(&mut span, "offset0");
+    assert_eq!(ren.pixf.get((30,30)), src.get((0,2)));
+}
+
+#[test]
+fn pattern_fill_reflect() {
+    let src = tile();
+    let mut span = SpanPattern::new(&src, 0, 0);
+    span.wrap(Wrap::Reflect, Wrap::Repeat);
+    let ren = fill(&mut span, "reflect");
+    for y in 16 .. 48 {
+        for x in 16 .. 48 {
+            let sx = if (x / 6) % 2 == 0 { x % 6 } else { 5 - x % 6 };
+            assert_eq!(ren.pixf.get((x,y)), src.get((sx, y % 4)), "({},{})", x, y);
+        }
+    }
+}
+
+#[test]
+fn pattern_fill_transform() {
+    // Horizontal stripes, rotated to vertical hatching and scaled by 2
+    let mut src = Pixfmt::<Rgba8>::new(1,4);
+    src.set((0,0), Rgba8::black());
+    src.set((0,1), Rgba8::black());
+    src.set((0,2), Rgba8::new(0,0,0,0));
+    src.set((0,3), Rgba8::new(0,0,0,0));
+    let mut mtx = Transform::new_scale(2.0, 2.0);
+    mtx.rotate(std::f64::consts::FRAC_PI_2);
+    mtx.invert();
+    let mut span = SpanPattern::new(&src, 0, 0);
+    span.transform(mtx);
+    let ren = fill(&mut span, "transform");
+    for y in 16 .. 48 {
+        for x in 16 .. 48 {
+            // Pattern y = -x / 2, rounded down
+            let c = if (x / 4) % 2 == 1 { Rgba8::black() } else { Rgba8::white() };
+            assert_eq!(ren.pixf.get((x,y)), c, "({},{})", x, y);
+        }
+    }
+    span.clear_transform();
+    let ren = fill(&mut span, "transform_cleared");
+    assert_eq!(ren.pixf.get((30,32)), Rgba8::black());
+    assert_eq!(ren.pixf.get((30,34)), Rgba8::white());
+}
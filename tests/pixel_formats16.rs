@@ -0,0 +1,118 @@
+extern crate agg;
+
+use agg::{Pixfmt,Rgba8,Gray16,Rgb48,Rgba64,Rgba64pre,Pixel,Source,RenderingBase,Render};
+
+/// Horizontal gradient from black to red across 1000 pixels
+fn gradient<T: Pixel>(pix: T) -> RenderingBase<T> {
+    let mut ren_base = RenderingBase::new(pix);
+    for x in 0 .. 1000 {
+        let v = x as f64 / 999.0;
+        ren_base.pixf.set((x,0), Rgba64::new((v * 65535.0).round() as u16, 0, 0, 65535));
+    }
+    ren_base
+}
+
+#[test]
+fn pixel_formats16_precision() {
+    // More than 256 distinct values in a gradient
+    let ren = gradient(Pixfmt::<Rgba64>::new(1000,1));
+    let mut values : Vec<_> = (0 .. 1000).map(|x| ren.pixf.raw((x,0)).r).collect();
+    values.dedup();
+    assert_eq!(values.len(), 1000);
+    assert_eq!(ren.pixf.raw((999,0)), Rgba64::new(65535,0,0,65535));
+    // Downconverted as a Source
+    assert_eq!(ren.pixf.get((999,0)), Rgba8::new(255,0,0,255));
+
+    let mut pix = Pixfmt::<Gray16>::new(2,1);
+    pix.set((0,0), Gray16::new(1000));
+    assert_eq!(pix.raw((0,0)), Gray16::new_with_alpha(1000, 65535));
+    assert_eq!(pix.raw((1,0)), Gray16::new_with_alpha(0, 0));
+    assert_eq!(pix.as_bytes().len(), 2 * 2 * 2);
+
+    let mut pix = Pixfmt::<Rgb48>::new(3,1);
+    pix.fill(Rgb48::new(1,2,3));
+    pix.setn((1,0), 2, Rgb48::new(300,400,500));
+    assert_eq!(pix.raw((0,0)), Rgb48::new(1,2,3));
+    assert_eq!(pix.raw((2,0)), Rgb48::new(300,400,500));
+    assert_eq!(pix.as_bytes().len(), 3 * 3 * 2);
+}
+
+#[test]
+fn pixel_formats16_blend() {
+    // Half coverage of white onto black
+    let mut pix = Pixfmt::<Rgba64>::new(1,1);
+    pix.fill(Rgba64::new(0,0,0,65535));
+    pix.blend_pix((0,0), Rgba64::new(65535,65535,65535,65535), 128);
+    assert_eq!(pix.raw((0,0)), Rgba64::new(32896,32896,32896,65535));
+
+    // Half transparent color, full coverage
+    let mut pix = Pixfmt::<Rgb48>::new(1,1);
+    pix.fill(Rgb48::new(0,0,0));
+    pix.blend_pix((0,0), Rgba64::new(65535,0,65535,32768), 255);
+    assert_eq!(pix.raw((0,0)), Rgb48::new(32768,0,32768));
+
+    // Alpha accumulates on a transparent background
+    let mut pix = Pixfmt::<Gray16>::new(1,1);
+    pix.blend_pix((0,0), Gray16::new_with_alpha(65535, 32768), 255);
+    assert_eq!(pix.raw((0,0)), Gray16::new_with_alpha(32768, 32768));
+    pix.blend_pix((0,0), Gray16::new_with_alpha(65535, 32768), 255);
+    assert_eq!(pix.raw((0,0)), Gray16::new_with_alpha(49152, 49152));
+
+    // Premultiplied
+    let mut pix = Pixfmt::<Rgba64pre>::new(1,1);
+    pix.blend_pix((0,0), Rgba64pre::new(32768,0,0,32768), 255);
+    assert_eq!(pix.raw((0,0)), Rgba64pre::new(32768,0,0,32768));
+    pix.blend_pix((0,0), Rgba64pre::new(0,0,32768,32768), 255);
+    assert_eq!(pix.raw((0,0)), Rgba64pre::new(16384,0,32768,49152));
+}
+
+#[test]
+fn pixel_formats16_render() {
+    // Same coverage as an 8-bit format, expanded to 16 bits
+    fn draw<T: Pixel>(pix: T) -> RenderingBase<T> {
+        let mut ren_base = RenderingBase::new(pix);
+        ren_base.clear(Rgba8::white());
+        let mut ras = agg::RasterizerScanline::new();
+        ras.add_path(&agg::Ellipse::new(50.0, 50.0, 40.0, 30.0, 64));
+        let mut ren = agg::RenderingScanlineAASolid::with_base(&mut ren_base);
+        ren.color(Rgba8::black());
+        agg::render_scanlines(&mut ras, &mut ren);
+        ren_base
+    }
+    let ren8 = draw(Pixfmt::<agg::Rgb8>::new(100,100));
+    let ren16 = draw(Pixfmt::<Rgb48>::new(100,100));
+    for y in 0 .. 100 {
+        for x in 0 .. 100 {
+            let (a, b) = (ren8.pixf.get((x,y)), ren16.pixf.get((x,y)));
+            assert!((i64::from(a.r) - i64::from(b.r)).abs() <= 1, "({},{}) {:?} {:?}", x, y, a, b);
+        }
+    }
+    assert_eq!(ren16.pixf.raw((50,50)), Rgb48::new(0,0,0));
+    assert_eq!(ren16.pixf.raw((2,2)), Rgb48::new(65535,65535,65535));
+}
+
+#[test]
+fn pixel_formats16_to_file() {
+    let ren = gradient(Pixfmt::<Rgba64>::new(1000,1));
+    ren.to_file("tests/tmp/pixel_formats16_rgba64.png").unwrap();
+    let img = image::open("tests/tmp/pixel_formats16_rgba64.png").unwrap();
+    assert_eq!(img.color(), image::ColorType::Rgba16);
+    let img = img.to_rgba16();
+    for x in 0 .. 1000 {
+        let p = ren.pixf.raw((x,0));
+        assert_eq!(img.get_pixel(x as u32, 0).0, [p.r, p.g, p.b, p.a]);
+    }
+
+    let mut pix = Pixfmt::<Gray16>::new(4,4);
+    pix.fill(Gray16::new(12345));
+    pix.to_file("tests/tmp/pixel_formats16_gray16.png").unwrap();
+    let img = image::open("tests/tmp/pixel_formats16_gray16.png").unwrap();
+    assert_eq!(img.color(), image::ColorType::La16);
+
+    let mut pix = Pixfmt::<Rgb48>::new(4,4);
+    pix.fill(Rgb48::new(1,257,65534));
+    pix.to_file("tests/tmp/pixel_formats16_rgb48.png").unwrap();
+    let img = image::open("tests/tmp/pixel_formats16_rgb48.png").unwrap();
+    assert_eq!(img.color(), image::ColorType::Rgb16);
+    assert_eq!(img.to_rgb16().get_pixel(3,3).0, [1,257,65534]);
+}
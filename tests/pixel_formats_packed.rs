@@ -0,0 +1,92 @@
+extern crate agg;
+
+use agg::{Pixfmt,Rgb8,Rgba8,Rgb565,Rgb555,Color,Pixel,Source,RenderingBase,Render};
+
+fn draw<T: Pixel>(pix: T) -> RenderingBase<T> {
+    let mut ren_base = RenderingBase::new(pix);
+    ren_base.clear(Rgba8::new(255,255,255,255));
+    let mut ras = agg::RasterizerScanline::new();
+    ras.add_path(&agg::Ellipse::new(50.0, 50.0, 40.0, 30.0, 64));
+    let mut ren = agg::RenderingScanlineAASolid::with_base(&mut ren_base);
+    ren.color(Rgba8::new(200,100,40,160));
+    agg::render_scanlines(&mut ras, &mut ren);
+    ren_base
+}
+
+#[test]
+fn pixel_formats_packed_layout() {
+    assert_eq!(Rgb565::new(255,0,0).0, 0xF800);
+    assert_eq!(Rgb565::new(0,255,0).0, 0x07E0);
+    assert_eq!(Rgb565::new(0,0,255).0, 0x001F);
+    assert_eq!(Rgb555::new(255,0,0).0, 0x7C00);
+    assert_eq!(Rgb555::new(0,255,0).0, 0x03E0);
+    assert_eq!(Rgb555::new(255,255,255).0, 0x7FFF);
+
+    let mut pix = Pixfmt::<Rgb565>::new(2,1);
+    pix.set((1,0), Rgba8::new(255,0,0,255));
+    assert_eq!(pix.as_bytes(), &[0,0, 0x00,0xF8]);
+    assert_eq!(pix.raw((1,0)), Rgb565(0xF800));
+    pix.fill(Rgba8::white());
+    assert_eq!(pix.as_bytes(), &[0xFF; 4]);
+}
+
+#[test]
+fn pixel_formats_packed_conversion() {
+    // Black and white are exact
+    assert_eq!(Rgba8::from(Rgb565::from(Rgba8::white())), Rgba8::white());
+    assert_eq!(Rgba8::from(Rgb555::from(Rgba8::black())), Rgba8::black());
+    // Alpha is dropped
+    assert_eq!(Rgba8::from(Rgb565::from(Rgba8::new(255,0,0,10))), Rgba8::new(255,0,0,255));
+
+    // Every packed value survives a round trip through Rgba8
+    for v in 0 ..= 0xFFFF_u16 {
+        assert_eq!(Rgb565::from(Rgba8::from(Rgb565(v))), Rgb565(v));
+        let v = v & 0x7FFF;
+        assert_eq!(Rgb555::from(Rgba8::from(Rgb555(v))), Rgb555(v));
+    }
+    // Conversion to fewer bits rounds
+    let c = Rgba8::from(Rgb565::from(Rgba8::new(100,100,100,255)));
+    assert_eq!((c.r, c.g, c.b), (99, 101, 99));
+    assert_eq!(Rgb565::new(1,1,1).red8(), 0);
+    assert_eq!(Rgb565::new(5,5,5).red8(), 8);
+}
+
+#[test]
+fn pixel_formats_packed_blend() {
+    let mut pix = Pixfmt::<Rgb565>::new(1,1);
+    pix.fill(Rgba8::white());
+    // 127 is quantized to 5 and 6 bits
+    pix.blend_pix((0,0), Rgba8::black(), 128);
+    assert_eq!(pix.get((0,0)), Rgba8::new(123,125,123,255));
+    pix.blend_pix((0,0), Rgba8::black(), 0);
+    assert_eq!(pix.get((0,0)), Rgba8::new(123,125,123,255));
+    pix.blend_pix((0,0), Rgba8::new(0,0,0,0), 255);
+    assert_eq!(pix.get((0,0)), Rgba8::new(123,125,123,255));
+    pix.blend_pix((0,0), Rgba8::black(), 255);
+    assert_eq!(pix.get((0,0)), Rgba8::black());
+
+    // Anti-aliased rendering matches 8-bit RGB within quantization
+    let rgb = draw(Pixfmt::<Rgb8>::new(100,100));
+    let p565 = draw(Pixfmt::<Rgb565>::new(100,100));
+    let p555 = draw(Pixfmt::<Rgb555>::new(100,100));
+    for y in 0 .. 100 {
+        for x in 0 .. 100 {
+            let c0 = rgb.pixf.get((x,y));
+            for c in [p565.pixf.get((x,y)), p555.pixf.get((x,y))] {
+                assert!((c.r as i32 - c0.r as i32).abs() <= 4, "{:?} {:?}", c, c0);
+                assert!((c.g as i32 - c0.g as i32).abs() <= 4, "{:?} {:?}", c, c0);
+                assert!((c.b as i32 - c0.b as i32).abs() <= 4, "{:?} {:?}", c, c0);
+            }
+        }
+    }
+    // Edges are blended
+    let c = p565.pixf.get((10,50));
+    assert!(c.g > 100 && c.g < 255, "{:?}", c);
+
+    // Written to file as 8-bit RGB
+    p565.to_file("tests/tmp/pixel_formats_rgb565.png").unwrap();
+    let img = image::open("tests/tmp/pixel_formats_rgb565.png").unwrap().to_rgb8();
+    assert_eq!(img.dimensions(), (100,100));
+    let c = p565.pixf.get((50,50));
+    assert_eq!(img.get_pixel(50,50).0, [c.r, c.g, c.b]);
+}
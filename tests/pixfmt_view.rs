@@ -0,0 +1,97 @@
+extern crate agg;
+
+use agg::{Pixfmt,Rgb8,Rgba8,Pixel,Source,RenderingBase,Render};
+
+/// Circle larger than a 100x100 panel
+fn draw<T: Pixel>(pix: T, color: Rgba8) -> RenderingBase<T> {
+    let mut ren_base = RenderingBase::new(pix);
+    let mut ras = agg::RasterizerScanline::new();
+    ras.add_path(&agg::Ellipse::new(50.0, 50.0, 60.0, 60.0, 64));
+    let mut ren = agg::RenderingScanlineAASolid::with_base(&mut ren_base);
+    ren.color(color);
+    agg::render_scanlines(&mut ras, &mut ren);
+    ren_base
+}
+
+#[test]
+fn pixfmt_view_panels() {
+    let red = Rgba8::new(255,0,0,255);
+    let blue = Rgba8::new(0,0,255,255);
+    let mut pix = Pixfmt::<Rgb8>::new(200,120);
+    pix.fill(Rgba8::white());
+    draw(pix.view(0, 10, 100, 100), red);
+    {
+        let mut ren_base = draw(pix.view(100, 10, 100, 100), blue);
+        // Local coordinates
+        assert_eq!(ren_base.pixf.get((0,50)), blue);
+        ren_base.clear(Rgba8::black());
+        draw(ren_base.pixf, blue);
+    }
+    pix.to_file("tests/tmp/pixfmt_view_panels.png").unwrap();
+
+    // Each panel is clipped to its own region
+    assert_eq!(pix.get((99,60)), red);
+    assert_eq!(pix.get((100,60)), blue);
+    assert_eq!(pix.get((50,5)), Rgba8::white());
+    assert_eq!(pix.get((150,5)), Rgba8::white());
+    assert_eq!(pix.get((50,115)), Rgba8::white());
+
+    // Matches rendering into a separate image
+    let mut panel = Pixfmt::<Rgb8>::new(100,100);
+    panel.fill(Rgba8::black());
+    let panel = draw(panel, blue);
+    for y in 0 .. 100 {
+        for x in 0 .. 100 {
+            assert_eq!(pix.get((100+x,10+y)), panel.pixf.get((x,y)), "({},{})", x, y);
+        }
+    }
+}
+
+#[test]
+fn pixfmt_view_clipping() {
+    let mut pix = Pixfmt::<Rgb8>::new(10,10);
+    pix.fill(Rgba8::white());
+    {
+        let mut view = pix.view(6, 7, 10, 10);
+        assert_eq!((view.width(), view.height()), (4, 3));
+        view.fill(Rgba8::black());
+        // Nested views
+        let mut inner = view.view(1, 1, 2, 2);
+        assert_eq!((inner.width(), inner.height()), (2, 2));
+        inner.set((1,1), Rgba8::new(255,0,0,255));
+    }
+    assert_eq!(pix.get((5,7)), Rgba8::white());
+    assert_eq!(pix.get((6,6)), Rgba8::white());
+    assert_eq!(pix.get((6,7)), Rgba8::black());
+    assert_eq!(pix.get((9,9)), Rgba8::black());
+    assert_eq!(pix.get((8,9)), Rgba8::new(255,0,0,255));
+
+    // Empty regions
+    assert!(pix.try_view(10, 0, 5, 5).is_err());
+    assert!(pix.try_view(0, 0, 5, 0).is_err());
+    assert!(pix.try_view(9, 9, 5, 5).is_ok());
+}
+
+#[test]
+fn pixfmt_view_bottom_up() {
+    // 4x3 image, stored bottom-up
+    let mut buf = [0u8; 4 * 3 * 3];
+    {
+        let mut pix = Pixfmt::<Rgb8,_>::attach(&mut buf[..], 4, 3, -12).unwrap();
+        let mut view = pix.view(1, 0, 2, 2);
+        assert_eq!(view.stride(), -12);
+        view.fill(Rgba8::white());
+        view.to_file("tests/tmp/pixfmt_view_bottom_up.png").unwrap();
+    }
+    let white = [255u8; 6];
+    let black = [0u8; 6];
+    // Rows 0 and 1 are the last two rows in memory
+    assert_eq!(buf[24+3 .. 24+9], white);
+    assert_eq!(buf[12+3 .. 12+9], white);
+    assert_eq!(buf[3 .. 9], black);
+    assert_eq!(buf[24 .. 27], black[..3]);
+
+    let img = image::open("tests/tmp/pixfmt_view_bottom_up.png").unwrap().to_rgb8();
+    assert_eq!(img.dimensions(), (2,2));
+    assert!(img.pixels().all(|p| p.0 == [255,255,255]));
+}
@@ -0,0 +1,156 @@
+extern crate agg;
+
+use agg::{Pixfmt,Rgb8,Rgba8,Rgba8pre,Gray8,Bgr8,Bgra8,Argb8,Abgr8,Bgra8pre,Argb8pre,Abgr8pre,Gray16,Rgb48,Rgba64,Rgba64pre,Rgb565,Rgb555,Srgb8,Srgba8,Srgba8pre,Pixel,Source,RenderingBase,DrawOutline,Render};
+use agg::{DynPixfmt,PixelFormat};
+
+/// Draw with each kind of renderer
+fn scene<T: Pixel>(ren_base: &mut RenderingBase<T>) {
+    ren_base.clear(Rgba8::white());
+    // Anti-aliased fill
+    {
+        let mut ren = agg::RenderingScanlineAASolid::with_base(ren_base);
+        ren.color(Rgba8::new(200,40,40,200));
+        let mut ras = agg::RasterizerScanline::new();
+        ras.add_path(&agg::Ellipse::new(50.0, 50.0, 40.0, 30.0, 64));
+        agg::render_scanlines(&mut ras, &mut ren);
+    }
+    // Aliased fill
+    {
+        let mut ren = agg::RenderingScanlineBinSolid::with_base(ren_base);
+        ren.color(Rgba8::new(20,20,160,255));
+        let mut ras = agg::RasterizerScanline::new();
+        ras.add_path(&agg::Ellipse::new(20.0, 80.0, 12.0, 12.0, 32));
+        agg::render_scanlines(&mut ras, &mut ren);
+    }
+    // Span generator
+    {
+        let mut span = agg::SpanGouraudRgba::new(Rgba8::new(255,0,0,255),
+                                                 Rgba8::new(0,255,0,255),
+                                                 Rgba8::new(0,0,255,255),
+                                                 (60.0,60.0), (95.0,65.0), (80.0,95.0), 0.0);
+        let mut ras = agg::RasterizerScanline::new();
+        ras.add_path(&span);
+        agg::render_scanlines_aa(&mut ras, ren_base, &mut span);
+    }
+    let mut path = agg::Path::new();
+    path.move_to(10.0, 10.0);
+    path.line_to(50.0, 90.0);
+    path.line_to(90.0, 10.0);
+    // Anti-aliased outline
+    {
+        let mut ren = agg::RendererOutlineAA::with_base(ren_base);
+        ren.color(Rgba8::new(0,0,0,255));
+        ren.width(5.0);
+        let mut ras = agg::RasterizerOutlineAA::with_renderer(&mut ren);
+        ras.round_cap(true);
+        ras.add_path(&path);
+    }
+    // Aliased outline
+    {
+        let mut ren = agg::RendererPrimatives::with_base(ren_base);
+        ren.line_color(Rgba8::new(0,120,0,255));
+        let mut ras = agg::RasterizerOutline::with_primative(&mut ren);
+        ras.add_path(&path);
+    }
+}
+
+fn draw<T: Pixel>(pix: T) -> RenderingBase<T> {
+    let mut ren_base = RenderingBase::new(pix);
+    scene(&mut ren_base);
+    ren_base
+}
+
+#[test]
+fn polymorphic_renderer() {
+    for name in &["rgb8", "rgba8", "Rgba8pre", "GRAY8", "bgr8", "Bgra8", "ARGB8", "abgr8",
+                  "bgra8pre", "Argb8pre", "ABGR8PRE",
+                  "gray16", "Rgb48", "rgba64", "rgba64pre", "rgb565", "RGB555", "srgb8", "srgba8", "srgba8pre"] {
+        let format : PixelFormat = name.parse().unwrap();
+        let ren = draw(DynPixfmt::new(format, 100, 100));
+        ren.to_file(format!("tests/tmp/polymorphic_renderer_{}.png", name)).unwrap();
+        assert_eq!(ren.pixf.format(), format);
+        assert_eq!(ren.pixf.width(), 100);
+        assert_eq!(ren.pixf.height(), 100);
+        assert_eq!(ren.pixf.as_bytes().len(), 100 * 100 * format.bpp());
+        assert_eq!(ren.pixf.bpp(), format.bpp());
+
+        // Identical to the statically chosen pixel format
+        let expected = match format {
+            PixelFormat::Rgb8     => draw(Pixfmt::<Rgb8>::new(100,100)).pixf.as_bytes().to_vec(),
+            PixelFormat::Rgba8    => draw(Pixfmt::<Rgba8>::new(100,100)).pixf.as_bytes().to_vec(),
+            PixelFormat::Rgba8pre => draw(Pixfmt::<Rgba8pre>::new(100,100)).pixf.as_bytes().to_vec(),
+            PixelFormat::Gray8    => draw(Pixfmt::<Gray8>::new(100,100)).pixf.as_bytes().to_vec(),
+            PixelFormat::Bgr8     => draw(Pixfmt::<Bgr8>::new(100,100)).pixf.as_bytes().to_vec(),
+            PixelFormat::Bgra8    => draw(Pixfmt::<Bgra8>::new(100,100)).pixf.as_bytes().to_vec(),
+            PixelFormat::Argb8    => draw(Pixfmt::<Argb8>::new(100,100)).pixf.as_bytes().to_vec(),
+            PixelFormat::Abgr8    => draw(Pixfmt::<Abgr8>::new(100,100)).pixf.as_bytes().to_vec(),
+            PixelFormat::Bgra8pre => draw(Pixfmt::<Bgra8pre>::new(100,100)).pixf.as_bytes().to_vec(),
+            PixelFormat::Argb8pre => draw(Pixfmt::<Argb8pre>::new(100,100)).pixf.as_bytes().to_vec(),
+            PixelFormat::Abgr8pre => draw(Pixfmt::<Abgr8pre>::new(100,100)).pixf.as_bytes().to_vec(),
+            PixelFormat::Gray16   => draw(Pixfmt::<Gray16>::new(100,100)).pixf.as_bytes().to_vec(),
+            PixelFormat::Rgb48    => draw(Pixfmt::<Rgb48>::new(100,100)).pixf.as_bytes().to_vec(),
+            PixelFormat::Rgba64   => draw(Pixfmt::<Rgba64>::new(100,100)).pixf.as_bytes().to_vec(),
+            PixelFormat::Rgba64pre => draw(Pixfmt::<Rgba64pre>::new(100,100)).pixf.as_bytes().to_vec(),
+            PixelFormat::Rgb565   => draw(Pixfmt::<Rgb565>::new(100,100)).pixf.as_bytes().to_vec(),
+            PixelFormat::Rgb555   => draw(Pixfmt::<Rgb555>::new(100,100)).pixf.as_bytes().to_vec(),
+            PixelFormat::Srgb8    => draw(Pixfmt::<Srgb8>::new(100,100)).pixf.as_bytes().to_vec(),
+            PixelFormat::Srgba8   => draw(Pixfmt::<Srgba8>::new(100,100)).pixf.as_bytes().to_vec(),
+            PixelFormat::Srgba8pre => draw(Pixfmt::<Srgba8pre>::new(100,100)).pixf.as_bytes().to_vec(),
+        };
+        assert!(ren.pixf.as_bytes() == &expected[..], "{}", name);
+    }
+}
+
+#[test]
+fn polymorphic_renderer_source() {
+    let mut pix : DynPixfmt = Pixfmt::<Rgb8>::new(4,4).into();
+    pix.set((1,2), Rgba8::new(10,20,30,255));
+    assert_eq!(pix.get((1,2)), Rgba8::new(10,20,30,255));
+
+    let mut pix : DynPixfmt = Pixfmt::<Gray8>::new(4,4).into();
+    pix.set((1,2), Gray8::new(90));
+    assert_eq!(pix.get((1,2)), Rgba8::new(90,90,90,255));
+
+    // Used as an image source
+    let mut src = DynPixfmt::new(PixelFormat::Rgba8, 8, 8);
+    src.fill(Rgba8::new(0,0,255,255));
+    let mut ren_base = RenderingBase::new(DynPixfmt::new(PixelFormat::Rgb8, 16, 16));
+    ren_base.clear(Rgba8::white());
+    let mut span = agg::SpanImageFilterNN::new(&src, agg::Transform::new());
+    let mut ras = agg::RasterizerScanline::new();
+    ras.add_path(&agg::Ellipse::new(8.0, 8.0, 8.0, 8.0, 32));
+    agg::render_scanlines_aa(&mut ras, &mut ren_base, &mut span);
+    assert_eq!(ren_base.pixf.get((5,5)), Rgba8::new(0,0,255,255));
+    assert_eq!(ren_base.pixf.get((12,12)), Rgba8::white());
+}
+
+/// Bytes per pixel of any Pixel Format
+fn bpp<T: Pixel>(pix: &T) -> usize {
+    pix.bpp()
+}
+
+#[test]
+fn polymorphic_renderer_wrapped() {
+    // Masked drawing onto a format chosen at runtime
+    let mut mask = agg::AlphaMask::new(Pixfmt::<Gray8>::new(100,100), agg::MaskChannel::Luminance);
+    mask.add_path(&agg::Ellipse::new(50.0, 50.0, 30.0, 30.0, 64), Rgba8::white());
+    for format in [PixelFormat::Rgb8, PixelFormat::Rgba64, PixelFormat::Rgb565] {
+        let pix = agg::PixfmtAlphaMask::new(DynPixfmt::new(format, 100, 100), &mask);
+        assert_eq!(bpp(&pix), format.bpp());
+        let ren = draw(pix);
+        assert_eq!(bpp(&ren.pixf), format.bpp());
+        assert_eq!(ren.pixf.as_bytes().len(), 100 * 100 * format.bpp());
+        // Drawn inside the mask only
+        let blank = DynPixfmt::new(format, 100, 100);
+        assert_eq!(ren.pixf.pixf.get((5,5)), blank.get((5,5)));
+        assert_ne!(ren.pixf.pixf.get((50,40)), blank.get((50,40)));
+    }
+}
+
+#[test]
+fn polymorphic_renderer_errors() {
+    assert!("rgb9".parse::<PixelFormat>().is_err());
+    let err = "cmyk".parse::<PixelFormat>().unwrap_err();
+    assert_eq!(err.to_string(), "unknown pixel format: cmyk");
+    assert!(DynPixfmt::try_new(PixelFormat::Rgb8, 0, 10).is_err());
+}
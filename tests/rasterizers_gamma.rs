@@ -1,6 +1,6 @@
 
 extern crate agg;
-use agg::Render;
+use agg::{Render,Pixel};
 
 fn rgb64(r: f64, g: f64,b: f64,a: f64) -> agg::Rgba8 {
     agg::Rgba8::new((r * 255.0).round() as u8,
@@ -59,3 +59,70 @@ fn rasterizers_gamma() {
     ren_base.to_file("tests/tmp/rasterizers_gamma.png").unwrap();
     assert_eq!(agg::ppm::img_diff("tests/tmp/rasterizers_gamma.png", "images/rasterizers_gamma.png").unwrap(), true);
 }
+
+#[test]
+fn rasterizers_gamma_functions() {
+    let (w,h) = (500,330);
+
+    let m_x = [100.+120., 369.+120., 143.+120.];
+    let m_y = [60.,       170.,      310.0];
+
+    let pixf = agg::Pixfmt::<agg::Rgb8>::new(w,h);
+    let mut ren_base = agg::RenderingBase::new(pixf);
+    ren_base.clear( agg::Rgba8::new(255, 255, 255, 255) );
+
+    let alpha = 0.5;
+
+    // Anti-Aliased
+    {
+        let mut ras = agg::RasterizerScanline::new();
+        ras.set_gamma(agg::GammaPower::new(2.0));
+        let mut ren_aa = agg::RenderingScanlineAASolid::with_base(&mut ren_base);
+        let mut path = agg::Path::new();
+
+        path.move_to(m_x[0], m_y[0]);
+        path.line_to(m_x[1], m_y[1]);
+        path.line_to(m_x[2], m_y[2]);
+        path.close_polygon();
+        ren_aa.color( rgb64(0.7, 0.5, 0.1, alpha));
+        ras.add_path(&path);
+        agg::render_scanlines(&mut ras, &mut ren_aa);
+    }
+
+    // Aliased
+    {
+        let mut ras = agg::RasterizerScanline::new();
+        ras.set_gamma(agg::GammaThreshold::new(1.0));
+        let mut ren_bin = agg::RenderingScanlineBinSolid::with_base(&mut ren_base);
+        let mut path = agg::Path::new();
+
+        path.move_to(m_x[0] - 200., m_y[0]);
+        path.line_to(m_x[1] - 200., m_y[1]);
+        path.line_to(m_x[2] - 200., m_y[2]);
+        path.close_polygon();
+        ren_bin.color( rgb64(0.1, 0.5, 0.7, alpha) );
+        ras.add_path(&path);
+        agg::render_scanlines(&mut ras, &mut ren_bin);
+    }
+    ren_base.to_file("tests/tmp/rasterizers_gamma_functions.png").unwrap();
+    assert!(agg::ppm::img_diff("tests/tmp/rasterizers_gamma_functions.png", "images/rasterizers_gamma.png").unwrap());
+}
+
+#[test]
+fn rasterizers_gamma_new_with_gamma() {
+    let draw = |mut ras: agg::RasterizerScanline| {
+        let mut ren_base = agg::RenderingBase::new(agg::Pixfmt::<agg::Rgb8>::new(100,100));
+        ren_base.clear( agg::Rgba8::new(255, 255, 255, 255) );
+        let mut ren_aa = agg::RenderingScanlineAASolid::with_base(&mut ren_base);
+        ren_aa.color( rgb64(0.7, 0.5, 0.1, 0.5) );
+        ras.add_path(&agg::Ellipse::new(50.0, 50.0, 35.0, 25.0, 64));
+        agg::render_scanlines(&mut ras, &mut ren_aa);
+        ren_base.pixf.as_bytes().to_vec()
+    };
+    let mut ras = agg::RasterizerScanline::new();
+    ras.set_gamma(agg::GammaPower::new(2.0));
+    let expected = draw(ras);
+    assert_eq!(draw(agg::RasterizerScanline::new_with_gamma(agg::GammaPower::new(2.0))), expected);
+    assert_eq!(draw(agg::RasterizerScanline::new_with_gamma(|v: f64| v.powf(2.0))), expected);
+    assert_ne!(draw(agg::RasterizerScanline::new()), expected);
+}
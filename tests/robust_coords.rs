@@ -0,0 +1,194 @@
+extern crate agg;
+
+use agg::{Pixfmt,Rgb8,Rgba8,RenderingBase,Source};
+use agg::{Vertex,PathCommand,VertexSource,DrawOutline};
+
+/// Vertices returned as is, including non-finite values
+struct Verts(Vec<Vertex<f64>>);
+
+impl VertexSource for Verts {
+    fn xconvert(&self) -> Vec<Vertex<f64>> {
+        self.0.clone()
+    }
+}
+
+fn close() -> Vertex<f64> {
+    Vertex::new(0.0, 0.0, PathCommand::Close)
+}
+
+fn square() -> Vec<Vertex<f64>> {
+    vec![Vertex::move_to(10.0, 10.0),
+         Vertex::line_to(60.0, 15.0),
+         Vertex::line_to(55.0, 70.0),
+         Vertex::line_to(12.0, 65.0),
+         close()]
+}
+
+fn new_base() -> RenderingBase<Pixfmt<Rgb8>> {
+    let mut ren_base = RenderingBase::new(Pixfmt::<Rgb8>::new(100,100));
+    ren_base.clear(Rgba8::white());
+    ren_base
+}
+
+fn fill<VS: VertexSource>(path: &VS) -> Vec<u8> {
+    let mut ren_base = new_base();
+    let mut ras = agg::RasterizerScanline::new();
+    ras.add_path(path);
+    agg::render_scanlines_aa_solid(&mut ras, &mut ren_base, Rgba8::black());
+    ren_base.as_bytes().to_vec()
+}
+
+fn outline_aa<VS: VertexSource>(path: &VS) -> Vec<u8> {
+    let mut ren_base = new_base();
+    {
+        let mut ren = agg::RendererOutlineAA::with_base(&mut ren_base);
+        ren.color(Rgba8::black());
+        ren.width(2.0);
+        let mut ras = agg::RasterizerOutlineAA::with_renderer(&mut ren);
+        ras.add_path(path);
+    }
+    ren_base.as_bytes().to_vec()
+}
+
+fn outline<VS: VertexSource>(path: &VS) -> Vec<u8> {
+    let mut ren_base = new_base();
+    {
+        let mut ren = agg::RendererPrimatives::with_base(&mut ren_base);
+        ren.line_color(Rgba8::black());
+        let mut ras = agg::RasterizerOutline::with_primative(&mut ren);
+        ras.add_path(path);
+    }
+    ren_base.as_bytes().to_vec()
+}
+
+fn with_nan() -> Vec<Vertex<f64>> {
+    vec![Vertex::move_to(10.0, 10.0),
+         Vertex::line_to(60.0, 15.0),
+         Vertex::line_to(f64::NAN, 30.0),
+         Vertex::line_to(55.0, 70.0),
+         Vertex::line_to(1e3, f64::INFINITY),
+         Vertex::line_to(12.0, 65.0),
+         close()]
+}
+
+fn with_nan_move_to() -> Vec<Vertex<f64>> {
+    vec![Vertex::move_to(f64::NAN, f64::NEG_INFINITY),
+         Vertex::line_to(10.0, 10.0),
+         Vertex::line_to(60.0, 15.0),
+         Vertex::line_to(55.0, 70.0),
+         Vertex::line_to(12.0, 65.0),
+         close()]
+}
+
+#[test]
+fn rasterizer_skips_non_finite() {
+    let expected = fill(&Verts(square()));
+    assert_eq!(fill(&Verts(with_nan())), expected);
+    assert_eq!(fill(&Verts(with_nan_move_to())), expected);
+}
+
+#[test]
+fn outline_skips_non_finite() {
+    let expected = outline_aa(&Verts(square()));
+    assert_eq!(outline_aa(&Verts(with_nan())), expected);
+    assert_eq!(outline_aa(&Verts(with_nan_move_to())), expected);
+
+    let expected = outline(&Verts(square()));
+    assert_eq!(outline(&Verts(with_nan())), expected);
+    assert_eq!(outline(&Verts(with_nan_move_to())), expected);
+}
+
+#[test]
+fn stroke_skips_non_finite() {
+    let expected = fill(&agg::Stroke::new(Verts(square())));
+    assert_eq!(fill(&agg::Stroke::new(Verts(with_nan()))), expected);
+    assert_eq!(fill(&agg::Stroke::new(Verts(with_nan_move_to()))), expected);
+    // Only non-finite values
+    let nan = Verts(vec![Vertex::move_to(f64::NAN, f64::NAN),
+                         Vertex::line_to(f64::NAN, 1.0),
+                         close()]);
+    assert!(agg::Stroke::new(nan).xconvert().is_empty());
+}
+
+#[test]
+fn huge_coordinates() {
+    // Half plane y > x, vertices far outside the image
+    let path = Verts(vec![Vertex::move_to(-1e12, -1e12),
+                          Vertex::line_to(1e12, 1e12),
+                          Vertex::line_to(-1e12, 1e12),
+                          close()]);
+    let mut ren_base = new_base();
+    let mut ras = agg::RasterizerScanline::new();
+    ras.clip_box(0.0, 0.0, 100.0, 100.0);
+    ras.add_path(&path);
+    agg::render_scanlines_aa_solid(&mut ras, &mut ren_base, Rgba8::black());
+    ren_base.to_file("tests/tmp/robust_huge.png").unwrap();
+    assert_eq!(ren_base.pixf.get((10,80)), Rgba8::black());
+    assert_eq!(ren_base.pixf.get((80,10)), Rgba8::white());
+
+    let path = Verts(vec![Vertex::move_to(-1e12, 50.0),
+                          Vertex::line_to(1e12, 50.0)]);
+    let pix = outline_aa(&path);
+    assert_eq!(pix.len(), 100 * 100 * 3);
+}
+
+#[test]
+fn double_precision_clipper() {
+    // Half plane y > 2x, vertices beyond the integer coordinate range
+    let path = Verts(vec![Vertex::move_to(-1e9, -2e9),
+                          Vertex::line_to(1e9, 2e9),
+                          Vertex::line_to(-1e9, 2e9),
+                          close()]);
+    let mut ren_base = new_base();
+    let mut ras = agg::RasterizerScanline::new_dbl();
+    ras.clip_box(0.0, 0.0, 100.0, 100.0);
+    ras.add_path(&path);
+    agg::render_scanlines_aa_solid(&mut ras, &mut ren_base, Rgba8::black());
+    ren_base.to_file("tests/tmp/robust_dbl.png").unwrap();
+
+    assert_eq!(ren_base.pixf.get((10,50)), Rgba8::black());
+    assert_eq!(ren_base.pixf.get((30,45)), Rgba8::white());
+    assert_eq!(ren_base.pixf.get((45,95)), Rgba8::black());
+    assert_eq!(ren_base.pixf.get((60,95)), Rgba8::white());
+}
+
+#[test]
+fn long_edge_split_once() {
+    // Edges wider than 16384 pixels are split in half, and only the halves
+    //   are added to the cells
+    let path = Verts(vec![Vertex::move_to(0.0, 0.0),
+                          Vertex::line_to(40000.0, 100.0),
+                          Vertex::line_to(0.0, 100.0),
+                          close()]);
+    let pix = fill(&path);
+    // Coverage of (x,0) is about 1 - (x + 0.5) / 400
+    let cover = |x: usize| 255 - pix[x * 3];
+    assert_eq!(cover(0), 255);
+    assert_eq!(cover(50), 224);
+    assert_eq!(cover(99), 193);
+    // Rows below the edge are covered
+    assert!(pix[3 * 100 ..].iter().all(|&v| v == 0));
+}
+
+#[test]
+fn outline_aa_wide_horizontal_line() {
+    // Single segment, bounded at both ends, stepping horizontally and wide
+    //   enough to cover several pixels on both sides
+    let path = Verts(vec![Vertex::move_to(10.0, 50.5),
+                          Vertex::line_to(90.0, 54.5)]);
+    let mut ren_base = new_base();
+    {
+        let mut ren = agg::RendererOutlineAA::with_base(&mut ren_base);
+        ren.color(Rgba8::black());
+        ren.width(6.0);
+        let mut ras = agg::RasterizerOutlineAA::with_renderer(&mut ren);
+        ras.add_path(&path);
+    }
+    let at = |x: usize, y: usize| ren_base.pixf.get((x,y)).r;
+    for (x, y) in [(30, 51), (50, 52), (70, 53)] {
+        // Both sides of the line are covered, and no further
+        assert!((y-2 ..= y+2).all(|y| at(x,y) == 0), "({},{})", x, y);
+        assert!(at(x, y-3) > 0 && at(x, y+3) > 0, "({},{})", x, y);
+        assert_eq!((at(x, y-4), at(x, y+4)), (255, 255), "({},{})", x, y);
+    }
+}
@@ -0,0 +1,192 @@
+extern crate agg;
+
+use agg::{Pixfmt,Rgb8,Rgba8,Rgba8pre,Gray8,Bgra8,Argb8,Abgr8,Pixel,Source,RenderingBase,Render};
+use agg::{stack_blur,recursive_blur,Rectangle,PixelSize};
+
+/// Black square on white, rendered anti-aliased
+fn square() -> Pixfmt<Rgb8> {
+    let mut ren_base = RenderingBase::new(Pixfmt::<Rgb8>::new(64,64));
+    ren_base.clear(Rgba8::white());
+    let mut ren = agg::RenderingScanlineAASolid::with_base(&mut ren_base);
+    ren.color(Rgba8::black());
+    let mut ras = agg::RasterizerScanline::new();
+    ras.move_to(16.0, 16.0);
+    ras.line_to(48.0, 16.0);
+    ras.line_to(48.0, 48.0);
+    ras.line_to(16.0, 48.0);
+    agg::render_scanlines(&mut ras, &mut ren);
+    ren_base.pixf
+}
+
+fn red(pix: &Pixfmt<Rgb8>, x: usize, y: usize) -> u8 {
+    pix.get((x,y)).r
+}
+
+#[test]
+fn simple_blur_stack() {
+    let mut pix = square();
+    stack_blur(&mut pix, 4, 4, None);
+    pix.to_file("tests/tmp/simple_blur_stack.png").unwrap();
+    // Far from the edge, unchanged
+    assert_eq!(red(&pix, 32, 32), 0);
+    assert_eq!(red(&pix, 2, 2), 255);
+    // Edge is halfway, corners mostly background
+    let edge = (i64::from(red(&pix, 15, 32)) + i64::from(red(&pix, 16, 32))) / 2;
+    assert!((edge - 128).abs() <= 8, "{}", edge);
+    assert!(red(&pix, 16, 16) > 160, "{}", red(&pix, 16, 16));
+    // Gradient across the edge
+    for x in 12 .. 20 {
+        assert!(red(&pix, x, 32) > red(&pix, x + 1, 32), "{}", x);
+    }
+    // Symmetric
+    for i in 0 .. 8 {
+        assert_eq!(red(&pix, 12 + i, 32), red(&pix, 51 - i, 32));
+        assert_eq!(red(&pix, 32, 12 + i), red(&pix, 12 + i, 32));
+    }
+}
+
+#[test]
+fn simple_blur_recursive() {
+    let mut pix = square();
+    recursive_blur(&mut pix, 4.0, 4.0, None);
+    pix.to_file("tests/tmp/simple_blur_recursive.png").unwrap();
+    assert!(red(&pix, 32, 32) <= 2);
+    assert!(red(&pix, 2, 2) >= 253);
+    let edge = (i64::from(red(&pix, 15, 32)) + i64::from(red(&pix, 16, 32))) / 2;
+    assert!((edge - 128).abs() <= 8, "{}", edge);
+    for x in 12 .. 20 {
+        assert!(red(&pix, x, 32) >= red(&pix, x + 1, 32), "{}", x);
+    }
+    assert!(red(&pix, 12, 32) > red(&pix, 20, 32) + 100);
+    for i in 0 .. 8 {
+        let (a, b) = (red(&pix, 12 + i, 32), red(&pix, 51 - i, 32));
+        assert!((i64::from(a) - i64::from(b)).abs() <= 2, "{} {} {}", i, a, b);
+    }
+}
+
+#[test]
+fn simple_blur_radii() {
+    // Horizontal only, rows are unchanged in y
+    let mut pix = square();
+    stack_blur(&mut pix, 6, 0, None);
+    assert_eq!(red(&pix, 32, 14), 255);
+    assert_eq!(red(&pix, 32, 17), 0);
+    assert!(red(&pix, 16, 32) > 0 && red(&pix, 16, 32) < 255);
+
+    let mut pix = square();
+    recursive_blur(&mut pix, 0.0, 6.0, None);
+    assert_eq!(red(&pix, 14, 32), 255);
+    assert_eq!(red(&pix, 17, 32), 0);
+    assert!(red(&pix, 32, 16) > 0 && red(&pix, 32, 16) < 255);
+
+    // Larger radius spreads further
+    let mut a = square();
+    let mut b = square();
+    stack_blur(&mut a, 2, 2, None);
+    stack_blur(&mut b, 8, 8, None);
+    assert!(red(&a, 12, 32) > red(&b, 12, 32));
+}
+
+#[test]
+fn simple_blur_rect() {
+    let orig = square();
+    let mut pix = square();
+    let rect = Rectangle::new(0, 0, 31, 63);
+    stack_blur(&mut pix, 4, 4, Some(rect));
+    // Right half unchanged
+    for y in 0 .. 64 {
+        for x in 32 .. 64 {
+            assert_eq!(pix.get((x,y)), orig.get((x,y)));
+        }
+    }
+    // Left edge blurred
+    assert!(red(&pix, 16, 32) > 0 && red(&pix, 16, 32) < 255);
+    // Right edge of the region is not blurred with pixels beyond it
+    assert_eq!(red(&pix, 31, 32), 0);
+
+    // Regions outside of the image do nothing
+    let mut pix = square();
+    recursive_blur(&mut pix, 4.0, 4.0, Some(Rectangle::new(100, 100, 200, 200)));
+    assert_eq!(pix.as_bytes(), orig.as_bytes());
+}
+
+#[test]
+fn simple_blur_formats() {
+    // Constant images are unchanged
+    let c = Rgba8::new(10,100,200,128);
+
+    let mut pix = Pixfmt::<Rgba8>::new(20,20);
+    pix.fill(c);
+    stack_blur(&mut pix, 3, 5, None);
+    recursive_blur(&mut pix, 3.0, 5.0, None);
+    assert_eq!(pix.get((7,9)), c);
+
+    let mut pix = Pixfmt::<Rgba8pre>::new(20,20);
+    pix.fill(c);
+    let before = pix.get((7,9));
+    stack_blur(&mut pix, 3, 5, None);
+    recursive_blur(&mut pix, 3.0, 5.0, None);
+    assert_eq!(pix.get((7,9)), before);
+
+    // Alpha is blurred, color is weighted by alpha
+    let mut pix = Pixfmt::<Rgba8>::new(21,21);
+    pix.set((10,10), Rgba8::new(90,90,90,90));
+    stack_blur(&mut pix, 2, 0, None);
+    assert_eq!(pix.get((9,10)), Rgba8::new(90,90,90,20));
+
+    let mut pix = Pixfmt::<Gray8>::new(21,21);
+    pix.fill(Gray8::new_with_alpha(0, 255));
+    pix.set((10,10), Gray8::new_with_alpha(90, 255));
+    stack_blur(&mut pix, 2, 0, None);
+    assert_eq!(pix.raw((9,10)), Gray8::new_with_alpha(20, 255));
+    assert_eq!(pix.raw((10,10)), Gray8::new_with_alpha(30, 255));
+    assert_eq!(pix.raw((10,9)), Gray8::new_with_alpha(0, 255));
+    recursive_blur(&mut pix, 0.0, 3.0, None);
+    assert!(pix.raw((10,9)).value > 0);
+    assert_eq!(pix.raw((10,9)).alpha, 255);
+}
+
+/// Opaque white square on a transparent background
+fn white_square<T>() -> Pixfmt<T> where Pixfmt<T>: Pixel + PixelSize {
+    let mut pix = Pixfmt::<T>::new(32,32);
+    pix.fill(Rgba8::new(0,0,0,0));
+    for y in 8 .. 24 {
+        pix.setn((8,y), 16, Rgba8::white());
+    }
+    pix
+}
+
+fn check_white<T>(pix: &Pixfmt<T>) where Pixfmt<T>: Source {
+    // Edge fades out without darkening
+    for x in 4 .. 12 {
+        let c = pix.get((x,16));
+        if c.a > 0 {
+            assert_eq!((c.r, c.g, c.b), (255,255,255), "{} {:?}", x, c);
+        }
+    }
+    let c = pix.get((8,16));
+    assert!(c.a > 64 && c.a < 192, "{:?}", c);
+    assert_eq!(pix.get((16,16)), Rgba8::white());
+    assert_eq!(pix.get((0,0)).a, 0);
+}
+
+#[test]
+fn simple_blur_straight_alpha() {
+    let mut pix = white_square::<Rgba8>();
+    stack_blur(&mut pix, 3, 3, None);
+    pix.to_file("tests/tmp/simple_blur_straight_alpha.png").unwrap();
+    check_white(&pix);
+    let mut pix = white_square::<Rgba8>();
+    recursive_blur(&mut pix, 3.0, 3.0, None);
+    check_white(&pix);
+
+    let mut pix = white_square::<Bgra8>();
+    stack_blur(&mut pix, 3, 3, None);
+    check_white(&pix);
+    let mut pix = white_square::<Argb8>();
+    recursive_blur(&mut pix, 3.0, 3.0, None);
+    check_white(&pix);
+    let mut pix = white_square::<Abgr8>();
+    stack_blur(&mut pix, 3, 3, None);
+    check_white(&pix);
+}
@@ -0,0 +1,106 @@
+extern crate agg;
+
+use agg::{Pixfmt,Rgb8,Rgba8,RenderingBase,Source,SpanGenerator};
+use agg::{SpanConverter,SpanAlpha};
+
+/// Checkerboard with 10 pixel squares
+#[derive(Debug)]
+struct Checker {
+    prepared: usize,
+}
+
+impl SpanGenerator for Checker {
+    type Output = Rgba8;
+    fn prepare(&mut self) {
+        self.prepared += 1;
+    }
+    fn generate(&mut self, span: &mut [Rgba8], x: i64, y: i64) {
+        for (i, c) in span.iter_mut().enumerate() {
+            let x = x + i as i64;
+            *c = if (x / 10 + y / 10) % 2 == 0 {
+                Rgba8::new(255,0,0,255)
+            } else {
+                Rgba8::new(0,0,255,255)
+            };
+        }
+    }
+}
+
+fn rect(x1: f64, y1: f64, x2: f64, y2: f64) -> agg::Path {
+    let mut path = agg::Path::new();
+    path.move_to(x1, y1);
+    path.line_to(x2, y1);
+    path.line_to(x2, y2);
+    path.line_to(x1, y2);
+    path.close_polygon();
+    path
+}
+
+fn new_base() -> RenderingBase<Pixfmt<Rgb8>> {
+    let mut ren_base = RenderingBase::new(Pixfmt::<Rgb8>::new(100,100));
+    ren_base.clear(Rgba8::white());
+    ren_base
+}
+
+#[test]
+fn span_generator_renderer() {
+    let mut ren_base = new_base();
+    let mut ras = agg::RasterizerScanline::new();
+    ras.add_path(&rect(0.0, 0.0, 50.0, 50.0));
+    {
+        let mut ren = agg::RenderingScanlineAA::new(&mut ren_base, Checker { prepared: 0 });
+        agg::render_scanlines(&mut ras, &mut ren);
+        assert_eq!(ren.span().prepared, 1);
+    }
+    ren_base.to_file("tests/tmp/span_generator.png").unwrap();
+
+    assert_eq!(ren_base.pixf.get((5,5)), Rgba8::new(255,0,0,255));
+    assert_eq!(ren_base.pixf.get((15,5)), Rgba8::new(0,0,255,255));
+    assert_eq!(ren_base.pixf.get((15,15)), Rgba8::new(255,0,0,255));
+    assert_eq!(ren_base.pixf.get((75,75)), Rgba8::white());
+}
+
+#[test]
+fn render_scanlines_aa() {
+    let mut ren_base = new_base();
+    let mut ras = agg::RasterizerScanline::new();
+    ras.add_path(&rect(0.0, 0.0, 50.0, 50.0));
+    let mut span = Checker { prepared: 0 };
+    agg::render_scanlines_aa(&mut ras, &mut ren_base, &mut span);
+    assert_eq!(span.prepared, 1);
+
+    assert_eq!(ren_base.pixf.get((5,5)), Rgba8::new(255,0,0,255));
+    assert_eq!(ren_base.pixf.get((15,5)), Rgba8::new(0,0,255,255));
+    assert_eq!(ren_base.pixf.get((75,75)), Rgba8::white());
+}
+
+#[test]
+fn span_converter_alpha() {
+    let mut ren_base = new_base();
+    let mut ras = agg::RasterizerScanline::new();
+    ras.add_path(&rect(0.0, 0.0, 50.0, 50.0));
+    let mut span = SpanConverter::new(Checker { prepared: 0 }, SpanAlpha::new(0.5));
+    agg::render_scanlines_aa(&mut ras, &mut ren_base, &mut span);
+    assert_eq!(span.gen.prepared, 1);
+
+    assert_eq!(ren_base.pixf.get((5,5)), Rgba8::new(255,127,127,255));
+    assert_eq!(ren_base.pixf.get((15,5)), Rgba8::new(127,127,255,255));
+}
+
+#[test]
+fn span_converter_closure() {
+    let mut ren_base = new_base();
+    let mut ras = agg::RasterizerScanline::new();
+    ras.add_path(&rect(0.0, 0.0, 50.0, 50.0));
+    // Fade out along x
+    let fade = |span: &mut [Rgba8], x: i64, _y: i64| {
+        for (i, c) in span.iter_mut().enumerate() {
+            c.a = if x + (i as i64) < 25 { 255 } else { 0 };
+        }
+    };
+    let mut span = SpanConverter::new(Checker { prepared: 0 }, fade);
+    agg::render_scanlines_aa(&mut ras, &mut ren_base, &mut span);
+
+    assert_eq!(ren_base.pixf.get((5,5)), Rgba8::new(255,0,0,255));
+    assert_eq!(ren_base.pixf.get((35,5)), Rgba8::white());
+}
@@ -0,0 +1,89 @@
+extern crate agg;
+
+use agg::{Pixfmt,Rgb8,Rgba8,Rgba8pre,Srgb8,Srgba8,Srgba8pre,Pixel,Source,RenderingBase,Render};
+
+fn draw<T: Pixel>(pix: T) -> RenderingBase<T> {
+    let mut ren_base = RenderingBase::new(pix);
+    ren_base.clear(Rgba8::white());
+    let mut ras = agg::RasterizerScanline::new();
+    ras.add_path(&agg::Ellipse::new(50.0, 50.0, 40.0, 30.0, 64));
+    let mut ren = agg::RenderingScanlineAASolid::with_base(&mut ren_base);
+    ren.color(Rgba8::black());
+    agg::render_scanlines(&mut ras, &mut ren);
+    ren_base
+}
+
+#[test]
+fn srgb_blending_half_cover() {
+    // Half of white in linear light is about 188 in sRGB
+    let mut pix = Pixfmt::<Srgb8>::new(1,1);
+    pix.fill(Srgb8::new(255,255,255));
+    pix.blend_pix((0,0), Rgba8::black(), 128);
+    assert_eq!(pix.as_bytes(), &[187,187,187]);
+    assert_eq!(pix.raw((0,0)), [187,187,187]);
+    // Source returns linear values
+    assert_eq!(pix.get((0,0)), Rgba8::new(127,127,127,255));
+
+    let mut pix = Pixfmt::<Rgb8>::new(1,1);
+    pix.fill(Rgb8::white());
+    pix.blend_pix((0,0), Rgba8::black(), 128);
+    assert_eq!(pix.as_bytes(), &[127,127,127]);
+
+    let mut pix = Pixfmt::<Srgba8>::new(1,1);
+    pix.fill(Srgba8::new(255,255,255,255));
+    pix.blend_pix((0,0), Rgba8::new(0,0,0,128), 255);
+    assert_eq!(pix.as_bytes(), &[187,187,187,255]);
+    pix.fill(Srgba8::new(255,0,0,0));
+    pix.blend_pix((0,0), Rgba8::new(0,0,255,255), 128);
+    assert_eq!(pix.as_bytes(), &[187,0,188,128]);
+
+    let mut pix = Pixfmt::<Srgba8pre>::new(1,1);
+    pix.fill(Srgba8pre::new(0,0,0,0));
+    pix.blend_pix((0,0), Rgba8pre::new(255,255,255,255), 128);
+    assert_eq!(pix.as_bytes(), &[188,188,188,128]);
+    pix.blend_pix((0,0), Rgba8pre::new(255,255,255,255), 128);
+    assert_eq!(pix.as_bytes(), &[225,225,225,192]);
+}
+
+#[test]
+fn srgb_blending_copy() {
+    // Encoded colors are stored unchanged
+    let mut pix = Pixfmt::<Srgba8>::new(3,1);
+    pix.set((0,0), Srgba8::new(10,20,30,40));
+    pix.setn((1,0), 2, Srgba8::new(100,150,200,255));
+    assert_eq!(pix.as_bytes(), &[10,20,30,40, 100,150,200,255, 100,150,200,255]);
+    pix.copy_or_blend_pix((2,0), Srgba8::new(1,2,3,255));
+    assert_eq!(pix.raw((2,0)), [1,2,3,255]);
+
+    // Linear colors are encoded
+    let mut pix = Pixfmt::<Srgb8>::new(1,1);
+    pix.set((0,0), Rgba8::new(0,128,255,255));
+    assert_eq!(pix.as_bytes(), &[0,188,255]);
+}
+
+#[test]
+fn srgb_blending_render() {
+    let rgb = draw(Pixfmt::<Rgb8>::new(100,100));
+    let srgb = draw(Pixfmt::<Srgb8>::new(100,100));
+    let srgba = draw(Pixfmt::<Srgba8>::new(100,100));
+    let srgba_pre = draw(Pixfmt::<Srgba8pre>::new(100,100));
+    srgb.to_file("tests/tmp/srgb_blending.png").unwrap();
+
+    // Edges are lighter than when blending the encoded values
+    let (rgb, srgb_bytes) = (rgb.pixf.as_bytes(), srgb.pixf.as_bytes());
+    assert!(rgb.iter().zip(srgb_bytes).all(|(a,b)| a <= b));
+    let lighter = rgb.iter().zip(srgb_bytes).filter(|(a,b)| a < b).count();
+    assert!(lighter > 100, "{}", lighter);
+    // Interior and exterior are unchanged
+    assert_eq!(srgb.pixf.raw((50,50)), [0,0,0]);
+    assert_eq!(srgb.pixf.raw((2,2)), [255,255,255]);
+
+    let rgb_of = |p: &[u8]| -> Vec<u8> {
+        p.chunks(4).flat_map(|c| c[..3].to_vec()).collect()
+    };
+    assert!(rgb_of(srgba.pixf.as_bytes()) == srgb_bytes);
+    assert!(rgb_of(srgba_pre.pixf.as_bytes()) == srgb_bytes);
+
+    let img = image::open("tests/tmp/srgb_blending.png").unwrap().to_rgb8();
+    assert!(img.as_raw() == srgb_bytes);
+}